@@ -0,0 +1,30 @@
+//! Draws a screen full of sprites with `Screen::draw_byte`, both within the
+//! rows and across the right edge.
+
+fn draw(c: &mut criterion::Criterion) {
+	let mut group = c.benchmark_group("draw_byte");
+	for (name, offset) in [("aligned", 0), ("wrapping", 4)] {
+		group.bench_function(name, |b| {
+			let mut screen = chip_8::screen::Screen::new();
+			screen.set_clip_quirk(chip_8::quirks::ClipQuirk::Wrap);
+			b.iter(|| {
+				let mut is_erased = false;
+				for y in 0..screen.height() {
+					for x in (offset..screen.width() + offset).step_by(8) {
+						is_erased |= screen.draw_byte(
+							1,
+							criterion::black_box(0b1011_0110),
+							x,
+							y,
+						);
+					}
+				}
+				is_erased
+			});
+		});
+	}
+	group.finish();
+}
+
+criterion::criterion_group!(benches, draw);
+criterion::criterion_main!(benches);
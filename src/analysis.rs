@@ -0,0 +1,297 @@
+//! A report of a ROM for its authors, see `analyze`. It puts together what
+//! `--info`, `--disasm` and `--warn-uninit-reads` show and what a short
+//! headless run finds:
+//!
+//! ```text
+//! ROM
+//!   Size:      8 bytes
+//! ...
+//! Disassembly
+//!   L200:
+//! * 200: 00E0  CLS
+//!   202: 1200  JP #200
+//! ...
+//! ```
+
+/// What the headless run of `analyze` saw.
+#[derive(Default)]
+struct Run {
+	/// How often the instruction at each address ran.
+	executed: std::collections::BTreeMap<u16, u64>,
+	/// How often each mnemonic ran.
+	mnemonics: std::collections::BTreeMap<&'static str, u64>,
+	/// Addresses of the ROM that ran an instruction the ROM did not have
+	/// there, with the instruction.
+	modified_code: std::collections::BTreeMap<u16, u16>,
+	/// Addresses of instructions and the uninitialized addresses they read.
+	uninit_reads: Vec<(u16, u16)>,
+	/// Addresses of `Dxyn` that drew a blank sprite.
+	empty_draws: std::collections::BTreeSet<u16>,
+	/// Why the run stopped early.
+	error: Option<crate::cpu::CpuError>,
+}
+
+/// Writes a report of the ROM `data`: its size and hashes, the quirks of
+/// the platform it needs, a listing with labels at the jump and call
+/// targets, which instructions ran in the first `cycles` instructions and
+/// the issues they showed: code that modified itself, reads of
+/// uninitialized memory and draws of blank sprites.
+///
+/// The run is headless and reproducible: the timers tick with the
+/// instructions, the random generator is seeded and no key is pressed.
+///
+/// # Errors
+///
+/// If the ROM does not fit into the memory of its platform.
+pub fn analyze(
+	data: &[u8],
+	cycles: usize,
+) -> Result<String, crate::ram::RamError> {
+	let info = crate::rom::RomInfo::scan(data);
+	let profile = match info.platform {
+		crate::platform::Platform::Chip8 => crate::quirks::QuirkProfile::Chip8,
+		crate::platform::Platform::SuperChip => {
+			crate::quirks::QuirkProfile::SuperChip
+		}
+		crate::platform::Platform::XoChip => {
+			crate::quirks::QuirkProfile::XoChip
+		}
+	};
+	let config = crate::config::Config { profile, ..Default::default() };
+	let mut emulator = crate::emulator::Emulator::with_config(&config);
+	emulator.set_timer_mode(crate::timer::TimerMode::Cycles);
+	// Waiting for the vertical blank would only repeat instructions
+	emulator.set_display_wait_quirk(crate::quirks::DisplayWaitQuirk::Off);
+	emulator.set_rng_seed(0);
+	emulator.set_warn_uninit_reads(true);
+	emulator.load_rom(data)?;
+	let mut report = String::new();
+	write_summary(&mut report, data, &info, &emulator);
+	let run = run(&mut emulator, data, cycles);
+	write_listing(&mut report, data, &run);
+	write_coverage(&mut report, data, &run, cycles);
+	write_issues(&mut report, &run);
+	Ok(report)
+}
+
+/// Runs up to `cycles` instructions of the loaded ROM `data`, stopping
+/// early at an error or once the ROM has ended.
+fn run(
+	emulator: &mut crate::emulator::Emulator,
+	data: &[u8],
+	cycles: usize,
+) -> Run {
+	let mut run = Run::default();
+	for _ in 0..cycles {
+		let pc = emulator.cpu().pc();
+		let Ok(&[high, low]) =
+			emulator.ram().snapshot(pc..pc.saturating_add(2))
+		else {
+			break;
+		};
+		let opcode = u16::from_be_bytes([high, low]);
+		if let Some(rom_opcode) = rom_word(data, pc) {
+			if rom_opcode != opcode {
+				run.modified_code.insert(pc, opcode);
+			}
+		}
+		if is_empty_draw(emulator, opcode) {
+			run.empty_draws.insert(pc);
+		}
+
+		if let Err(e) = emulator.run_cycles(1) {
+			run.error = Some(e);
+			break;
+		}
+		*run.executed.entry(pc).or_default() += 1;
+		*run.mnemonics.entry(crate::disasm::mnemonic(opcode)).or_default() +=
+			1;
+		run.uninit_reads.extend(
+			emulator
+				.take_uninit_reads()
+				.into_iter()
+				.map(|address| (pc, address)),
+		);
+		if emulator.has_ended() {
+			break;
+		}
+	}
+	run
+}
+
+/// Returns the word of the ROM `data` at `address` once it is loaded, if
+/// both of its bytes are in the ROM.
+fn rom_word(data: &[u8], address: u16) -> Option<u16> {
+	let offset = usize::from(
+		address.checked_sub(crate::consts::RAM_ROM_START_ADDRESS)?,
+	);
+	match data.get(offset..offset + 2)? {
+		&[high, low] => Some(u16::from_be_bytes([high, low])),
+		_ => None,
+	}
+}
+
+/// Whether `opcode` is a `Dxyn` whose sprite at `I` has no pixel set.
+/// `Dxy0` draws 32 bytes, a 16x16 sprite.
+fn is_empty_draw(emulator: &crate::emulator::Emulator, opcode: u16) -> bool {
+	if opcode & 0xF000 != 0xD000 {
+		return false;
+	}
+	let length = match opcode & 0x000F {
+		0 => 32,
+		n => n,
+	};
+	let start = emulator.cpu().index();
+	emulator
+		.ram()
+		.snapshot(start..start.saturating_add(length))
+		.is_ok_and(|sprite| sprite.iter().all(|&byte| byte == 0))
+}
+
+/// Writes the size and hashes of the ROM `data`, its platform and the
+/// quirks of the headless run, which differ from the profile of the
+/// platform as the `Run:` line says.
+fn write_summary(
+	report: &mut String,
+	data: &[u8],
+	info: &crate::rom::RomInfo,
+	emulator: &crate::emulator::Emulator,
+) {
+	use std::fmt::Write as _;
+
+	let _ = writeln!(report, "ROM");
+	let _ = writeln!(report, "  Size:      {} bytes", data.len());
+	let _ = writeln!(report, "  CRC32:     {:08x}", crate::rom::crc32(data));
+	let _ =
+		writeln!(report, "  fnv1a:     {:016x}", crate::rom::checksum(data));
+	let _ = writeln!(report, "\nQuirks");
+	let _ = writeln!(
+		report,
+		"  Platform:  {} ({} extended instructions)",
+		info.platform,
+		info.extended_instructions.len()
+	);
+	let _ = writeln!(report, "  Quirks:    {}", emulator.quirks_text());
+	let _ = writeln!(
+		report,
+		"  Run:       display_wait=Off, timers=Cycles, seed=0, no keys"
+	);
+}
+
+/// Writes the listing of `crate::disasm::disassemble_rom` with a label
+/// before every jump and call target and a `*` before the instructions that
+/// ran.
+fn write_listing(report: &mut String, data: &[u8], run: &Run) {
+	use std::fmt::Write as _;
+
+	let listing = crate::disasm::disassemble_rom(data);
+	let targets: std::collections::BTreeSet<u16> = listing
+		.iter()
+		.filter(|(_, opcode, _)| {
+			matches!(crate::disasm::mnemonic(*opcode), "JP" | "CALL")
+		})
+		.map(|(_, opcode, _)| opcode & 0x0FFF)
+		.collect();
+
+	let _ = writeln!(report, "\nDisassembly");
+	for (address, opcode, text) in listing {
+		if targets.contains(&address) {
+			let _ = writeln!(report, "  L{address:03X}:");
+		}
+		let marker =
+			if run.executed.contains_key(&address) { '*' } else { ' ' };
+		let _ =
+			writeln!(report, "{marker} {address:03X}: {opcode:04X}  {text}");
+	}
+}
+
+fn write_coverage(report: &mut String, data: &[u8], run: &Run, cycles: usize) {
+	use std::fmt::Write as _;
+
+	let words = data.len() / 2;
+	let covered = run
+		.executed
+		.keys()
+		.filter(|&&address| rom_word(data, address).is_some())
+		.count();
+	let ran: u64 = run.executed.values().sum();
+	let _ = writeln!(report, "\nCoverage");
+	let _ = writeln!(
+		report,
+		"  Ran {ran} of {cycles} instructions, {covered} of the {words} words \
+		 of the ROM"
+	);
+	for (mnemonic, count) in &run.mnemonics {
+		let _ = writeln!(report, "  {mnemonic:<6} {count}");
+	}
+}
+
+fn write_issues(report: &mut String, run: &Run) {
+	use std::fmt::Write as _;
+
+	let _ = writeln!(report, "\nIssues");
+	for (address, opcode) in &run.modified_code {
+		let _ = writeln!(
+			report,
+			"  {address:03X}: self-modifying code, ran {opcode:04X}  {}",
+			crate::disasm::disassemble_instruction(*opcode)
+		);
+	}
+	for (pc, address) in &run.uninit_reads {
+		let _ = writeln!(
+			report,
+			"  {pc:03X}: read of uninitialized memory at {address:03X}"
+		);
+	}
+	for address in &run.empty_draws {
+		let _ = writeln!(report, "  {address:03X}: drew an empty sprite");
+	}
+	if let Some(e) = &run.error {
+		let _ = writeln!(report, "  Stopped: {e}");
+	}
+	if run.modified_code.is_empty()
+		&& run.uninit_reads.is_empty()
+		&& run.empty_draws.is_empty()
+		&& run.error.is_none()
+	{
+		let _ = writeln!(report, "  None found");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn report_has_every_section() {
+		let rom = [
+			0xA3, 0x00, // LD I, #300
+			0xD0, 0x15, // DRW V0, V1, 5: blank and never written
+			0xA2, 0x0A, // LD I, #20A
+			0x60, 0x12, // LD V0, #12
+			0xF0, 0x55, // LD [I], V0: turns the next word into JP #20A
+			0x00, 0x0A, // SYS #00A
+		];
+		let report = super::analyze(&rom, 100).unwrap();
+
+		let sections: Vec<&str> = report
+			.lines()
+			.filter(|line| !line.is_empty() && !line.starts_with(' '))
+			.filter(|line| !line.starts_with('*'))
+			.collect();
+		assert_eq!(sections, [
+			"ROM",
+			"Quirks",
+			"Disassembly",
+			"Coverage",
+			"Issues"
+		]);
+		assert!(report.contains("  Platform:  CHIP-8"));
+		assert!(report.contains("  Quirks:    shift=ShiftVy"));
+		assert!(report.contains("display_wait=Off"));
+		assert!(!report.contains("display_wait=Draw"));
+		assert!(report.contains("* 20A: 000A  SYS #00A"));
+		assert!(report.contains("  Ran 6 of 100 instructions, 6 of the 6"));
+		assert!(report.contains("20A: self-modifying code, ran 120A  JP #20A"));
+		assert!(report.contains("202: read of uninitialized memory at 300"));
+		assert!(report.contains("202: drew an empty sprite"));
+	}
+}
@@ -0,0 +1,334 @@
+//! Assembles the mnemonics of `disasm` back into a ROM, so small programs
+//! can be written without an external toolchain:
+//!
+//! ```text
+//! start:              ; Labels end with a colon
+//!     LD V0, #0A      ; Hex with # or 0x, decimal otherwise
+//!     LD I, digit
+//!     DRW V0, V1, 5
+//!     JP start
+//! digit:
+//!     DB #F0          ; Raw bytes and words: DB and DW
+//! ```
+//!
+//! Mnemonics and registers are case insensitive, labels are not. The XO-CHIP
+//! `F000 nnnn` is written `LD I, LONG <addr>`, and `5xy2` and `5xy3` that
+//! save and load a range of registers are `SAVE Vx, Vy` and `LOAD Vx, Vy`.
+
+/// An error on a line of the source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AsmError {
+	/// 1-based line number.
+	pub line: usize,
+	pub message: String,
+}
+
+impl std::fmt::Display for AsmError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Line {}: {}", self.line, self.message)
+	}
+}
+
+impl std::error::Error for AsmError {}
+
+/// Mnemonics of the instructions and the data directives.
+const MNEMONICS: [&str; 32] = [
+	"CLS", "RET", "SCD", "SCR", "SCL", "LOW", "HIGH", "SYS", "JP", "CALL",
+	"SE", "SNE", "LD", "ADD", "OR", "AND", "XOR", "SUB", "SHR", "SUBN", "SHL",
+	"RND", "DRW", "SKP", "SKNP", "PLANE", "AUDIO", "PITCH", "SAVE", "LOAD",
+	"DB", "DW",
+];
+
+/// An instruction operand, resolved in the second pass.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Operand {
+	/// `V0` through `VF`.
+	Register(u8),
+	/// `I`, `DT`, `ST`, `K`, `F`, `HF`, `B`, `[I]` or `R`.
+	Keyword(&'static str),
+	/// A number or the address of a label.
+	Value(u16),
+	/// `LONG <addr>`.
+	Long(u16),
+}
+
+/// A line with an instruction, split in the first pass.
+struct Instruction<'a> {
+	line: usize,
+	mnemonic: String,
+	operands: Vec<&'a str>,
+}
+
+/// Assembles the `source` into a ROM loaded at
+/// `consts::RAM_ROM_START_ADDRESS`.
+///
+/// # Errors
+///
+/// If a line has an unknown instruction, invalid operands, a value out of
+/// range or a label defined twice or never.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+	// First pass: find the address of every label
+	let mut labels = std::collections::HashMap::new();
+	let mut instructions = vec![];
+	let mut address = crate::consts::RAM_ROM_START_ADDRESS;
+	for (index, text) in source.lines().enumerate() {
+		let line = index + 1;
+		let error = |message: String| AsmError { line, message };
+
+		let mut text = text.split(';').next().unwrap_or_default().trim();
+		if let Some((label, rest)) = text.split_once(':') {
+			let label = label.trim();
+			if !is_identifier(label) {
+				return Err(error(format!("Invalid label: {label:?}.")));
+			}
+			if labels.insert(label, address).is_some() {
+				return Err(error(format!("Label {label} is defined twice.")));
+			}
+			text = rest.trim();
+		}
+		if text.is_empty() {
+			continue;
+		}
+
+		let (mnemonic, operands) =
+			text.split_once(char::is_whitespace).unwrap_or((text, ""));
+		let mnemonic = mnemonic.to_ascii_uppercase();
+		let operands: Vec<_> = if operands.trim().is_empty() {
+			vec![]
+		} else {
+			operands.split(',').map(str::trim).collect()
+		};
+		let size = match mnemonic.as_str() {
+			"DB" => 1,
+			"LD" if operands.last().is_some_and(|o| is_long(o)) => 4,
+			_ => 2,
+		};
+		address = address.checked_add(size).ok_or_else(|| {
+			error("The program does not fit into memory.".to_owned())
+		})?;
+		instructions.push(Instruction { line, mnemonic, operands });
+	}
+
+	// Second pass: encode the instructions with the labels known
+	let mut rom = vec![];
+	for Instruction { line, mnemonic, operands } in instructions {
+		let error = |message| AsmError { line, message };
+		let operands = operands
+			.iter()
+			.map(|operand| parse_operand(operand, &labels))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(error)?;
+		rom.extend(encode(&mnemonic, &operands).map_err(error)?);
+	}
+	Ok(rom)
+}
+
+fn is_identifier(s: &str) -> bool {
+	let mut chars = s.chars();
+	chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_long(operand: &str) -> bool {
+	operand
+		.split_whitespace()
+		.next()
+		.is_some_and(|word| word.eq_ignore_ascii_case("LONG"))
+}
+
+/// Parses `#FF` or `0xFF` as hex and anything else starting with a digit as
+/// decimal.
+fn parse_number(s: &str) -> Option<Result<u16, String>> {
+	let hex = s
+		.strip_prefix('#')
+		.or_else(|| s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")));
+	let parsed = match hex {
+		Some(hex) => u16::from_str_radix(hex, 16),
+		None if s.starts_with(|c: char| c.is_ascii_digit()) => s.parse(),
+		None => return None,
+	};
+	Some(parsed.map_err(|_| format!("Invalid number: {s}.")))
+}
+
+fn parse_operand(
+	s: &str,
+	labels: &std::collections::HashMap<&str, u16>,
+) -> Result<Operand, String> {
+	let value = |s: &str| match parse_number(s) {
+		Some(number) => number,
+		None => labels
+			.get(s)
+			.copied()
+			.ok_or_else(|| format!("Unknown label: {s}.")),
+	};
+
+	if is_long(s) {
+		let address = s.split_whitespace().nth(1).unwrap_or_default();
+		return value(address).map(Operand::Long);
+	}
+	let upper = s.to_ascii_uppercase();
+	if let Some(register) = upper.strip_prefix('V').filter(|r| r.len() == 1) {
+		if let Ok(register) = u8::from_str_radix(register, 16) {
+			return Ok(Operand::Register(register));
+		}
+	}
+	let keyword = match upper.as_str() {
+		"I" => "I",
+		"DT" => "DT",
+		"ST" => "ST",
+		"K" => "K",
+		"F" => "F",
+		"HF" => "HF",
+		"B" => "B",
+		"[I]" => "[I]",
+		"R" => "R",
+		_ => return value(s).map(Operand::Value),
+	};
+	Ok(Operand::Keyword(keyword))
+}
+
+/// Checks that `value` is at most `max`, the largest `what`.
+fn check(value: u16, max: u16, what: &str) -> Result<u16, String> {
+	if value <= max {
+		Ok(value)
+	} else {
+		Err(format!("{value:#X} is too large for {what}, at most {max:#X}."))
+	}
+}
+
+/// Encodes one instruction or directive.
+fn encode(mnemonic: &str, operands: &[Operand]) -> Result<Vec<u8>, String> {
+	use Operand::{Keyword as K, Long, Register as V, Value as N};
+
+	let addr = |nnn: u16| check(nnn, 0xFFF, "an address");
+	let byte = |nn: u16| check(nn, 0xFF, "a byte");
+	let nibble = |n: u16| check(n, 0xF, "a nibble");
+	let x = |x: u8| u16::from(x) << 8;
+	let xy = |x: u8, y: u8| u16::from(x) << 8 | u16::from(y) << 4;
+
+	let word = match (mnemonic, operands) {
+		("CLS", []) => 0x00E0,
+		("RET", []) => 0x00EE,
+		("SCD", &[N(n)]) => 0x00C0 | nibble(n)?,
+		("SCR", []) => 0x00FB,
+		("SCL", []) => 0x00FC,
+		("LOW", []) => 0x00FE,
+		("HIGH", []) => 0x00FF,
+		("SYS", &[N(nnn)]) => addr(nnn)?,
+		("JP", &[N(nnn)]) => 0x1000 | addr(nnn)?,
+		("JP", &[V(0), N(nnn)]) => 0xB000 | addr(nnn)?,
+		("CALL", &[N(nnn)]) => 0x2000 | addr(nnn)?,
+		("SE", &[V(vx), N(nn)]) => 0x3000 | x(vx) | byte(nn)?,
+		("SE", &[V(vx), V(vy)]) => 0x5000 | xy(vx, vy),
+		("SNE", &[V(vx), N(nn)]) => 0x4000 | x(vx) | byte(nn)?,
+		("SNE", &[V(vx), V(vy)]) => 0x9000 | xy(vx, vy),
+		("LD", &[V(vx), N(nn)]) => 0x6000 | x(vx) | byte(nn)?,
+		("LD", &[V(vx), V(vy)]) => 0x8000 | xy(vx, vy),
+		("LD", &[K("I"), N(nnn)]) => 0xA000 | addr(nnn)?,
+		("LD", &[K("I"), Long(nnnn)]) => {
+			let [high, low] = nnnn.to_be_bytes();
+			return Ok(vec![0xF0, 0x00, high, low]);
+		}
+		("LD", &[V(vx), K("DT")]) => 0xF007 | x(vx),
+		("LD", &[V(vx), K("K")]) => 0xF00A | x(vx),
+		("LD", &[K("DT"), V(vx)]) => 0xF015 | x(vx),
+		("LD", &[K("ST"), V(vx)]) => 0xF018 | x(vx),
+		("LD", &[K("F"), V(vx)]) => 0xF029 | x(vx),
+		("LD", &[K("HF"), V(vx)]) => 0xF030 | x(vx),
+		("LD", &[K("B"), V(vx)]) => 0xF033 | x(vx),
+		("LD", &[K("[I]"), V(vx)]) => 0xF055 | x(vx),
+		("LD", &[V(vx), K("[I]")]) => 0xF065 | x(vx),
+		("LD", &[K("R"), V(vx)] | &[V(vx), K("R")]) if vx >= 8 => {
+			return Err("Only V0 through V7 fit into R.".to_owned());
+		}
+		("LD", &[K("R"), V(vx)]) => 0xF075 | x(vx),
+		("LD", &[V(vx), K("R")]) => 0xF085 | x(vx),
+		("ADD", &[V(vx), N(nn)]) => 0x7000 | x(vx) | byte(nn)?,
+		("ADD", &[V(vx), V(vy)]) => 0x8004 | xy(vx, vy),
+		("ADD", &[K("I"), V(vx)]) => 0xF01E | x(vx),
+		("OR", &[V(vx), V(vy)]) => 0x8001 | xy(vx, vy),
+		("AND", &[V(vx), V(vy)]) => 0x8002 | xy(vx, vy),
+		("XOR", &[V(vx), V(vy)]) => 0x8003 | xy(vx, vy),
+		("SUB", &[V(vx), V(vy)]) => 0x8005 | xy(vx, vy),
+		("SHR", &[V(vx)]) => 0x8006 | xy(vx, vx),
+		("SHR", &[V(vx), V(vy)]) => 0x8006 | xy(vx, vy),
+		("SUBN", &[V(vx), V(vy)]) => 0x8007 | xy(vx, vy),
+		("SHL", &[V(vx)]) => 0x800E | xy(vx, vx),
+		("SHL", &[V(vx), V(vy)]) => 0x800E | xy(vx, vy),
+		("RND", &[V(vx), N(nn)]) => 0xC000 | x(vx) | byte(nn)?,
+		("DRW", &[V(vx), V(vy), N(n)]) => 0xD000 | xy(vx, vy) | nibble(n)?,
+		("SKP", &[V(vx)]) => 0xE09E | x(vx),
+		("SKNP", &[V(vx)]) => 0xE0A1 | x(vx),
+		("PLANE", &[N(n)]) => 0xF001 | nibble(n)? << 8,
+		("AUDIO", []) => 0xF002,
+		("PITCH", &[V(vx)]) => 0xF03A | x(vx),
+		("SAVE", &[V(vx), V(vy)]) => 0x5002 | xy(vx, vy),
+		("LOAD", &[V(vx), V(vy)]) => 0x5003 | xy(vx, vy),
+		("DB", &[N(nn)]) => return Ok(vec![byte(nn)?.to_be_bytes()[1]]),
+		("DW", &[N(nnnn)]) => nnnn,
+		_ if MNEMONICS.contains(&mnemonic) => {
+			return Err(format!("Invalid operands for {mnemonic}."));
+		}
+		_ => return Err(format!("Unknown instruction: {mnemonic}.")),
+	};
+	Ok(word.to_be_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn disassembly_round_trips() {
+		for opcode in 0..=u16::MAX {
+			let text = crate::disasm::disassemble_instruction(opcode);
+			// The address follows in the next word
+			if text == "LD I, LONG" {
+				continue;
+			}
+			let rom = super::assemble(&text).unwrap_or_else(|e| {
+				panic!("{} ({:#06X}): {}", text, opcode, e)
+			});
+			// Some opcodes ignore a nibble, like `01E0` that is `CLS` too,
+			// so compare the text
+			let reassembled = u16::from_be_bytes([rom[0], rom[1]]);
+			assert_eq!(
+				crate::disasm::disassemble_instruction(reassembled),
+				text,
+				"{opcode:#06X}"
+			);
+		}
+
+		// Whole ROMs, with the long index load and a trailing byte
+		let rom = [
+			0x00, 0xE0, 0xF0, 0x00, 0x12, 0x34, 0xA2, 0x0A, 0xD0, 0x15, 0x12,
+			0x00, 0xFF,
+		];
+		let source: Vec<String> = crate::disasm::disassemble_rom(&rom)
+			.into_iter()
+			.map(|(_, _, text)| text)
+			.collect();
+		assert_eq!(super::assemble(&source.join("\n")), Ok(rom.to_vec()));
+	}
+
+	#[test]
+	fn resolves_labels_in_both_directions() {
+		let source = "
+			start: JP end ; A comment
+			LD I, LONG data
+			end:
+			JP start
+			data: DB 255
+		";
+		assert_eq!(
+			super::assemble(source),
+			Ok(vec![0x12, 0x06, 0xF0, 0x00, 0x02, 0x08, 0x12, 0x00, 0xFF])
+		);
+	}
+
+	#[test]
+	fn reports_the_line_of_an_error() {
+		let error = super::assemble("CLS\n\nLD V0, #100").unwrap_err();
+		assert_eq!(error.line, 3);
+		let error = super::assemble("JP nowhere").unwrap_err();
+		assert_eq!(error.message, "Unknown label: nowhere.");
+	}
+}
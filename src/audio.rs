@@ -0,0 +1,204 @@
+use anyhow::{Context as _, Result};
+
+/// Plays the Chip-8 buzzer: a square wave tone of
+/// `consts::AUDIO_FREQUENCY` hertz, or the looped audio pattern of XO-CHIP.
+///
+/// A single [`rodio::Sink`] holding an endless tone is created once and is
+/// only paused and resumed, so retriggering the sound timer never stacks up
+/// overlapping sinks and the tone stops as soon as it is paused. The XO-CHIP
+/// pattern has a sink of its own, whose source reads the pattern and pitch
+/// shared with `self.set_pattern_playing`.
+pub struct Audio {
+	// Must be kept alive for the sink to play.
+	_stream: rodio::OutputStream,
+	sink: rodio::Sink,
+	pattern_sink: rodio::Sink,
+	pattern: std::sync::Arc<std::sync::Mutex<Pattern>>,
+}
+
+impl Audio {
+	/// Opens the default output device.
+	///
+	/// # Errors
+	///
+	/// If there is no output device or it could not be opened.
+	pub fn new() -> Result<Self> {
+		use rodio::Source as _;
+
+		let (stream, handle) = rodio::OutputStream::try_default()
+			.context("Failed to open the output stream.")?;
+		let sink = rodio::Sink::try_new(&handle)
+			.context("Failed to create a sink.")?;
+		sink.pause();
+		sink.append(
+			SquareWave::new(crate::consts::AUDIO_FREQUENCY)
+				.amplify(crate::consts::AUDIO_VOLUME),
+		);
+
+		let pattern =
+			std::sync::Arc::new(std::sync::Mutex::new(Pattern::new(
+				crate::consts::AUDIO_PATTERN,
+				crate::consts::AUDIO_PITCH,
+			)));
+		let pattern_sink = rodio::Sink::try_new(&handle)
+			.context("Failed to create a sink.")?;
+		pattern_sink.pause();
+		pattern_sink.append(
+			PatternWave::new(std::sync::Arc::clone(&pattern))
+				.amplify(crate::consts::AUDIO_VOLUME),
+		);
+		Ok(Self { _stream: stream, sink, pattern_sink, pattern })
+	}
+
+	/// Starts or stops the tone. Does nothing if it is already in the
+	/// requested state.
+	#[inline]
+	pub fn set_playing(&self, playing: bool) {
+		if playing && self.sink.is_paused() {
+			self.sink.play();
+		} else if !playing && !self.sink.is_paused() {
+			self.sink.pause();
+		}
+	}
+
+	/// Loops the XO-CHIP audio `pattern` at the `pitch` (see
+	/// `Cpu::audio_pattern` and `Cpu::pitch`), or stops it if `None`.
+	pub fn set_pattern_playing(&self, pattern: Option<([u8; 16], u8)>) {
+		let Some((samples, pitch)) = pattern else {
+			if !self.pattern_sink.is_paused() {
+				self.pattern_sink.pause();
+			}
+			return;
+		};
+
+		// The pattern is copied in and out whole, so a panic can never leave
+		// it half written
+		*self
+			.pattern
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner) =
+			Pattern::new(samples, pitch);
+		if self.pattern_sink.is_paused() {
+			self.pattern_sink.play();
+		}
+	}
+}
+
+/// The XO-CHIP audio pattern with the rate it is played at.
+#[derive(Clone, Copy)]
+struct Pattern {
+	samples: [u8; 16],
+	/// One-bit samples per second.
+	rate: f32,
+}
+
+impl Pattern {
+	fn new(samples: [u8; 16], pitch: u8) -> Self {
+		// XO-CHIP plays 4000 samples per second at pitch 64, an octave
+		// higher every 48 steps
+		let rate = 4000.0 * 2_f32.powf((f32::from(pitch) - 64.0) / 48.0);
+		Self { samples, rate }
+	}
+}
+
+/// An endless mono wave of the shared `Pattern`, looped.
+struct PatternWave {
+	pattern: std::sync::Arc<std::sync::Mutex<Pattern>>,
+	/// Position inside the pattern, in samples of it.
+	position: f32,
+}
+
+impl PatternWave {
+	const SAMPLE_RATE: u32 = 48000;
+
+	const fn new(pattern: std::sync::Arc<std::sync::Mutex<Pattern>>) -> Self {
+		Self { pattern, position: 0.0 }
+	}
+}
+
+impl Iterator for PatternWave {
+	type Item = f32;
+
+	// The position is within 0..128
+	#[allow(
+		clippy::cast_possible_truncation,
+		clippy::cast_precision_loss,
+		clippy::cast_sign_loss
+	)]
+	fn next(&mut self) -> Option<f32> {
+		let pattern = *self
+			.pattern
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner);
+		let bits = pattern.samples.len() * 8;
+		self.position = (self.position
+			+ pattern.rate / Self::SAMPLE_RATE as f32)
+			% bits as f32;
+
+		let bit = self.position as usize;
+		let byte = pattern.samples[bit / 8];
+		Some(if byte & (0x80 >> (bit % 8)) == 0 { -1.0 } else { 1.0 })
+	}
+}
+
+impl rodio::Source for PatternWave {
+	fn current_frame_len(&self) -> Option<usize> {
+		None
+	}
+
+	fn channels(&self) -> u16 {
+		1
+	}
+
+	fn sample_rate(&self) -> u32 {
+		Self::SAMPLE_RATE
+	}
+
+	fn total_duration(&self) -> Option<std::time::Duration> {
+		None
+	}
+}
+
+/// An endless mono square wave.
+struct SquareWave {
+	frequency: u32,
+	sample: u32,
+}
+
+impl SquareWave {
+	const SAMPLE_RATE: u32 = 48000;
+
+	const fn new(frequency: u32) -> Self {
+		Self { frequency, sample: 0 }
+	}
+}
+
+impl Iterator for SquareWave {
+	type Item = f32;
+
+	fn next(&mut self) -> Option<f32> {
+		self.sample = (self.sample + 1) % Self::SAMPLE_RATE;
+		// Position inside the current period, in half periods
+		let half_periods =
+			self.sample * self.frequency * 2 / Self::SAMPLE_RATE;
+		Some(if half_periods & 1 == 0 { 1.0 } else { -1.0 })
+	}
+}
+
+impl rodio::Source for SquareWave {
+	fn current_frame_len(&self) -> Option<usize> {
+		None
+	}
+
+	fn channels(&self) -> u16 {
+		1
+	}
+
+	fn sample_rate(&self) -> u32 {
+		Self::SAMPLE_RATE
+	}
+
+	fn total_duration(&self) -> Option<std::time::Duration> {
+		None
+	}
+}
@@ -0,0 +1,303 @@
+//! Settings that can be loaded from a TOML file instead of being passed one
+//! by one on the command line, e.g.:
+//!
+//! ```toml
+//! clock_hz = 700
+//! profile = "schip"
+//! memory_quirk = "increment_index"
+//! window_multiplier = 8
+//! palette = "octo"
+//! fg = "FFCC00"
+//!
+//! [keys]
+//! G = 0x5
+//! ```
+//!
+//! Every field is optional. The quirks default to those of the `profile`,
+//! which defaults to plain Chip-8, and the colors to those of the
+//! `palette`. Unknown fields are rejected, so typos do not go unnoticed.
+
+use anyhow::{Context as _, Result};
+
+/// Settings the `Emulator` is created with, see `Emulator::with_config`.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	/// Instructions per second.
+	pub clock_hz: std::num::NonZeroU32,
+	/// How many times faster the emulator runs while fast-forwarding.
+	pub turbo_multiplier: std::num::NonZeroU32,
+	/// Preset every quirk comes from, unless it is set explicitly.
+	pub profile: crate::quirks::QuirkProfile,
+	pub shift_quirk: Option<crate::quirks::ShiftQuirk>,
+	pub memory_quirk: Option<crate::quirks::MemoryQuirk>,
+	pub jump_quirk: Option<crate::quirks::JumpQuirk>,
+	pub display_wait_quirk: Option<crate::quirks::DisplayWaitQuirk>,
+	/// Whether `00E0` waits along with `Dxyn`, see `Cpu::set_clear_waits`.
+	pub clear_waits: Option<bool>,
+	pub logic_quirk: Option<crate::quirks::LogicQuirk>,
+	pub clip_quirk: Option<crate::quirks::ClipQuirk>,
+	pub index_overflow_quirk: Option<crate::quirks::IndexOverflowQuirk>,
+	pub draw_batch_quirk: Option<crate::quirks::DrawBatchQuirk>,
+	/// Whether the XO-CHIP instructions are enabled, see `Cpu::set_xochip`.
+	pub xochip: Option<bool>,
+	/// Size of the memory in bytes, see `Ram::set_size`.
+	pub ram_size: Option<usize>,
+	/// Size of a low resolution pixel in the window, see
+	/// `Screen::set_window_multiplier`.
+	pub window_multiplier: usize,
+	/// Preset every color comes from, unless it is set explicitly.
+	pub palette: crate::palette::PalettePreset,
+	/// Color of the lit pixels, `RRGGBB` in the file.
+	#[serde(deserialize_with = "deserialize_color")]
+	pub fg: Option<u32>,
+	/// Color of the unlit pixels, `RRGGBB` in the file.
+	#[serde(deserialize_with = "deserialize_color")]
+	pub bg: Option<u32>,
+	/// Color of the pixels lit on the second XO-CHIP plane only, `RRGGBB`
+	/// in the file.
+	#[serde(deserialize_with = "deserialize_color")]
+	pub fg2: Option<u32>,
+	/// Color of the pixels lit on both XO-CHIP planes, `RRGGBB` in the file.
+	#[serde(deserialize_with = "deserialize_color")]
+	pub blend: Option<u32>,
+	/// Bindings applied on top of the default key map, a `[keys]` table of
+	/// key names (see `Keyboard::parse_key`) and Chip-8 key codes in the
+	/// file.
+	#[cfg(feature = "window")]
+	#[serde(rename = "keys", deserialize_with = "deserialize_key_map")]
+	pub key_map: Vec<(minifb::Key, u8)>,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			clock_hz: crate::consts::CPU_CLOCK_HZ,
+			turbo_multiplier: crate::consts::CPU_TURBO_MULTIPLIER,
+			profile: crate::quirks::QuirkProfile::default(),
+			shift_quirk: None,
+			memory_quirk: None,
+			jump_quirk: None,
+			display_wait_quirk: None,
+			clear_waits: None,
+			logic_quirk: None,
+			clip_quirk: None,
+			index_overflow_quirk: None,
+			draw_batch_quirk: None,
+			xochip: None,
+			ram_size: None,
+			window_multiplier: crate::consts::WINDOW_MULTIPLIER,
+			palette: crate::palette::PalettePreset::default(),
+			fg: None,
+			bg: None,
+			fg2: None,
+			blend: None,
+			#[cfg(feature = "window")]
+			key_map: vec![],
+		}
+	}
+}
+
+impl Config {
+	/// Parses the TOML `source`.
+	///
+	/// # Errors
+	///
+	/// If `source` is not valid TOML, has unknown fields or invalid values.
+	#[inline]
+	pub fn from_toml(source: &str) -> Result<Self> {
+		let config: Self = toml::from_str(source)?;
+		config.validate()?;
+		Ok(config)
+	}
+
+	/// Checks the values that cannot be checked while parsing.
+	///
+	/// # Errors
+	///
+	/// If `self.window_multiplier` is not in
+	/// `consts::WINDOW_MIN_MULTIPLIER..=consts::WINDOW_MAX_MULTIPLIER` or
+	/// `self.ram_size` is not a multiple of 64 in
+	/// `consts::RAM_SIZE..=consts::RAM_XOCHIP_SIZE`.
+	#[inline]
+	pub fn validate(&self) -> Result<()> {
+		let (min, max) = (
+			crate::consts::WINDOW_MIN_MULTIPLIER,
+			crate::consts::WINDOW_MAX_MULTIPLIER,
+		);
+		anyhow::ensure!(
+			(min..=max).contains(&self.window_multiplier),
+			"Invalid window multiplier {}, expected {min} through {max}.",
+			self.window_multiplier
+		);
+		let (min, max) =
+			(crate::consts::RAM_SIZE, crate::consts::RAM_XOCHIP_SIZE);
+		if let Some(size) = self.ram_size {
+			anyhow::ensure!(
+				(min..=max).contains(&size) && size % 64 == 0,
+				"Invalid memory size {size}, expected a multiple of 64 from \
+				 {min} through {max}."
+			);
+		}
+		Ok(())
+	}
+
+	/// Returns the explicit shift quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn shift_quirk(&self) -> crate::quirks::ShiftQuirk {
+		self.shift_quirk.unwrap_or(self.profile.shift_quirk())
+	}
+
+	/// Returns the explicit memory quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn memory_quirk(&self) -> crate::quirks::MemoryQuirk {
+		self.memory_quirk.unwrap_or(self.profile.memory_quirk())
+	}
+
+	/// Returns the explicit jump quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn jump_quirk(&self) -> crate::quirks::JumpQuirk {
+		self.jump_quirk.unwrap_or(self.profile.jump_quirk())
+	}
+
+	/// Returns the explicit display wait quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn display_wait_quirk(&self) -> crate::quirks::DisplayWaitQuirk {
+		self.display_wait_quirk.unwrap_or(self.profile.display_wait_quirk())
+	}
+
+	/// Returns the explicit clear wait or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn clear_waits(&self) -> bool {
+		self.clear_waits.unwrap_or(self.profile.clear_waits())
+	}
+
+	/// Returns the explicit logic quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn logic_quirk(&self) -> crate::quirks::LogicQuirk {
+		self.logic_quirk.unwrap_or(self.profile.logic_quirk())
+	}
+
+	/// Returns the explicit clip quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn clip_quirk(&self) -> crate::quirks::ClipQuirk {
+		self.clip_quirk.unwrap_or(self.profile.clip_quirk())
+	}
+
+	/// Returns the explicit index overflow quirk or the one of
+	/// `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn index_overflow_quirk(&self) -> crate::quirks::IndexOverflowQuirk {
+		self.index_overflow_quirk
+			.unwrap_or(self.profile.index_overflow_quirk())
+	}
+
+	/// Returns the explicit draw batch quirk or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn draw_batch_quirk(&self) -> crate::quirks::DrawBatchQuirk {
+		self.draw_batch_quirk.unwrap_or(self.profile.draw_batch_quirk())
+	}
+
+	/// Returns the explicit XO-CHIP switch or the one of `self.profile`.
+	#[inline]
+	#[must_use]
+	pub fn xochip(&self) -> bool {
+		self.xochip.unwrap_or(self.profile.xochip())
+	}
+
+	/// Returns the explicit memory size, or the 64KB of XO-CHIP or the 4KB
+	/// of the others depending on `self.xochip`.
+	#[inline]
+	#[must_use]
+	pub fn ram_size(&self) -> usize {
+		self.ram_size.unwrap_or(if self.xochip() {
+			crate::consts::RAM_XOCHIP_SIZE
+		} else {
+			crate::consts::RAM_SIZE
+		})
+	}
+
+	/// Returns the colors of `self.palette` with the explicit ones on top.
+	#[inline]
+	#[must_use]
+	pub fn colors(&self) -> crate::palette::Palette {
+		let preset = self.palette.palette();
+		crate::palette::Palette {
+			background: self.bg.unwrap_or(preset.background),
+			plane1: self.fg.unwrap_or(preset.plane1),
+			plane2: self.fg2.unwrap_or(preset.plane2),
+			both: self.blend.unwrap_or(preset.both),
+		}
+	}
+
+	/// Reads and parses the TOML file at `path`.
+	///
+	/// # Errors
+	///
+	/// If the file cannot be read or `Self::from_toml` fails.
+	#[inline]
+	pub fn load(path: &std::path::Path) -> Result<Self> {
+		let source = std::fs::read_to_string(path)
+			.with_context(|| format!("Failed to read {}.", path.display()))?;
+		Self::from_toml(&source)
+			.with_context(|| format!("Invalid config {}.", path.display()))
+	}
+}
+
+/// Parses a `RRGGBB` hex color, optionally prefixed with `#`.
+///
+/// # Errors
+///
+/// If `s` is not 6 hex digits.
+#[inline]
+pub fn parse_color(s: &str) -> Result<u32> {
+	let hex = s.strip_prefix('#').unwrap_or(s);
+	anyhow::ensure!(
+		hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()),
+		"Expected 6 hex digits like FFCC00, got {s:?}."
+	);
+	Ok(u32::from_str_radix(hex, 16)?)
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	let s: String = serde::Deserialize::deserialize(deserializer)?;
+	parse_color(&s).map(Some).map_err(serde::de::Error::custom)
+}
+
+#[cfg(feature = "window")]
+fn deserialize_key_map<'de, D>(
+	deserializer: D,
+) -> Result<Vec<(minifb::Key, u8)>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	let table: std::collections::BTreeMap<String, u8> =
+		serde::Deserialize::deserialize(deserializer)?;
+	table
+		.into_iter()
+		.map(|(name, code)| {
+			let key = crate::keyboard::Keyboard::parse_key(&name).ok_or_else(
+				|| serde::de::Error::custom(format!("Unknown key: {name:?}.")),
+			)?;
+			if code > 0xF {
+				return Err(serde::de::Error::custom(format!(
+					"Invalid key code {code:#X} for {name:?}, expected 0 \
+					 through 0xF."
+				)));
+			}
+			Ok((key, code))
+		})
+		.collect()
+}
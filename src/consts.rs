@@ -3,24 +3,156 @@ pub const LOG_LEVEL: &str = "INFO";
 #[cfg(feature = "tracing")]
 pub const LOGS_FILENAME: &str = "logs.log";
 
+// Audio options
+#[cfg(feature = "audio")]
+pub const AUDIO_FREQUENCY: u32 = 440;
+#[cfg(feature = "audio")]
+pub const AUDIO_VOLUME: f32 = 0.2;
+/// The XO-CHIP audio pattern until `F002` loads one: a square wave of 250Hz
+/// at `AUDIO_PITCH`.
+pub const AUDIO_PATTERN: [u8; 16] = [
+	0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00,
+	0xFF, 0x00, 0xFF, 0x00,
+];
+/// The XO-CHIP pitch until `Fx3A` sets one, playing 4000 bits per second.
+pub const AUDIO_PITCH: u8 = 64;
+
+// Timer options
+/// One tick of the 60Hz delay and sound timers.
+pub const TIMER_TICK_INTERVAL: std::time::Duration =
+	std::time::Duration::from_nanos(16_666_667);
+
+// Cpu options
+pub const CPU_CLOCK_HZ: std::num::NonZeroU32 =
+	std::num::NonZeroU32::new(500).unwrap();
+/// How many times faster everything runs while fast-forwarding.
+pub const CPU_TURBO_MULTIPLIER: std::num::NonZeroU32 =
+	std::num::NonZeroU32::new(4).unwrap();
+/// How many times slower everything runs in slow motion.
+pub const CPU_SLOW_MOTION_DIVISOR: std::num::NonZeroU32 =
+	std::num::NonZeroU32::new(4).unwrap();
+/// How much the clock hotkeys change `Cpu::clock_hz`.
+pub const CPU_CLOCK_STEP_HZ: std::num::NonZeroU32 =
+	std::num::NonZeroU32::new(100).unwrap();
+pub const CPU_STACK_DEPTH: usize = 16;
+pub const CPU_RPL_FLAGS: usize = 8;
+/// `i` is kept within the 12-bit address space, unless XO-CHIP is enabled.
+pub const CPU_INDEX_MASK: u16 = 0x0FFF;
+
+// Keyboard options, the default layout of the Chip-8 keys on a QWERTY
+// keyboard
+#[cfg(feature = "window")]
+pub const KEYBOARD_KEY_MAP: [(minifb::Key, u8); 16] = [
+	(minifb::Key::Key1, 0x1),
+	(minifb::Key::Key2, 0x2),
+	(minifb::Key::Key3, 0x3),
+	(minifb::Key::Key4, 0xC),
+	(minifb::Key::Q, 0x4),
+	(minifb::Key::W, 0x5),
+	(minifb::Key::E, 0x6),
+	(minifb::Key::R, 0xD),
+	(minifb::Key::A, 0x7),
+	(minifb::Key::S, 0x8),
+	(minifb::Key::D, 0x9),
+	(minifb::Key::F, 0xE),
+	(minifb::Key::Z, 0xA),
+	(minifb::Key::X, 0x0),
+	(minifb::Key::C, 0xB),
+	(minifb::Key::V, 0xF),
+];
+
+/// Covers all 16 Chip-8 keys. The directional pad is 2, 4, 6 and 8 and the
+/// bottom face button is 5, which most games use for movement and action.
+#[cfg(feature = "gamepad")]
+pub const GAMEPAD_BUTTON_MAP: [(gilrs::Button, u8); 16] = [
+	(gilrs::Button::DPadUp, 0x2),
+	(gilrs::Button::DPadLeft, 0x4),
+	(gilrs::Button::DPadRight, 0x6),
+	(gilrs::Button::DPadDown, 0x8),
+	(gilrs::Button::South, 0x5),
+	(gilrs::Button::East, 0x0),
+	(gilrs::Button::West, 0x7),
+	(gilrs::Button::North, 0x9),
+	(gilrs::Button::LeftTrigger, 0x1),
+	(gilrs::Button::RightTrigger, 0x3),
+	(gilrs::Button::LeftTrigger2, 0xA),
+	(gilrs::Button::RightTrigger2, 0xB),
+	(gilrs::Button::Select, 0xC),
+	(gilrs::Button::Start, 0xD),
+	(gilrs::Button::LeftThumb, 0xE),
+	(gilrs::Button::RightThumb, 0xF),
+];
+
+// Rewind options, one state per displayed frame (about 5 seconds)
+pub const REWIND_CAPACITY: usize = 500;
+
+// Execution trace options
+/// Size of the `--trace-exec` log at which it is moved aside and started
+/// anew, see `ExecTrace`.
+pub const EXEC_TRACE_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
 // Screen options
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
 pub const SCREEN_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
+/// The window is redrawn at most this often, about 100 times per second.
+pub const SCREEN_DISPLAY_INTERVAL: std::time::Duration =
+	std::time::Duration::from_millis(10);
+/// One refresh of a 60Hz monitor, see `Screen::set_display_interval`.
+pub const SCREEN_VSYNC_INTERVAL: std::time::Duration =
+	std::time::Duration::from_nanos(16_666_667);
+pub const SCREEN_HIRES_WIDTH: usize = 128;
+pub const SCREEN_HIRES_HEIGHT: usize = 64;
+pub const SCREEN_HIRES_SIZE: usize = SCREEN_HIRES_WIDTH * SCREEN_HIRES_HEIGHT;
+/// Displays over which a pixel that turned off fades out, see
+/// `Screen::set_ghosting`.
+pub const SCREEN_GHOST_FRAMES: u8 = 4;
+/// Brightness of the dimmed window rows of `Screen::set_crt`, in percent.
+pub const SCREEN_SCANLINE_BRIGHTNESS: u32 = 60;
+/// Pixels per line of `Screen::dump_pgm`, which keeps the lines within the
+/// 70 characters the format allows.
+pub const SCREEN_PGM_LINE_PIXELS: usize = 32;
+/// The frame of `Screen::set_visual_beep`, a red that stands out against
+/// all the palettes.
+pub const SCREEN_VISUAL_BEEP_COLOR: u32 = 0x00FF_3030;
 
 // Window options
 pub const WINDOW_TITLE: &str = "Chip-8 Emulator";
+/// Default size of a low resolution pixel in the window, see
+/// `Screen::set_window_multiplier`.
 pub const WINDOW_MULTIPLIER: usize = 10;
-pub const WINDOW_WIDTH: usize = SCREEN_WIDTH * WINDOW_MULTIPLIER;
-pub const WINDOW_HEIGHT: usize = SCREEN_HEIGHT * WINDOW_MULTIPLIER;
-pub const WINDOW_SIZE: usize = WINDOW_WIDTH * WINDOW_HEIGHT;
+/// Smallest multiplier, a 128x64 window that still shows every high
+/// resolution pixel.
+pub const WINDOW_MIN_MULTIPLIER: usize = 2;
+/// Largest multiplier, a 1920x960 window.
+pub const WINDOW_MAX_MULTIPLIER: usize = 30;
+
+/// How many ROMs `--recent` remembers.
+pub const RECENT_ROMS_CAPACITY: usize = 10;
+/// How many instructions `--info` disassembles.
+pub const ROM_INFO_INSTRUCTIONS: usize = 8;
+/// How many instructions `--analyze` runs, ten seconds at the default
+/// clock.
+pub const ANALYSIS_CYCLES: usize = 5000;
+/// Bytes of memory `Emulator::crash_dump` captures before the failed
+/// instruction, and after it.
+pub const CRASH_DUMP_MEMORY_CONTEXT: u16 = 16;
 
 // Colors
 pub const BLACK_COLOR: u32 = 0x0;
 pub const WHITE_COLOR: u32 = 0x00FF_FFFF;
+/// Octo's defaults for the XO-CHIP pixels on the second plane only and on
+/// both planes.
+pub const XOCHIP_PLANE2_COLOR: u32 = 0x00FF_6600;
+pub const XOCHIP_BLEND_COLOR: u32 = 0x0066_2200;
 
 // Roms
+/// Size of the memory, see `Ram::set_size`.
+pub const RAM_SIZE: usize = 4096;
+/// XO-CHIP addresses 64KB, all that `Cpu::i` can hold.
+pub const RAM_XOCHIP_SIZE: usize = 0x1_0000;
 pub const RAM_ROM_START_ADDRESS: u16 = 0x200;
+pub const RAM_DIGIT_SPRITES_ADDRESS: u16 = 0x0;
 pub const RAM_DIGIT_SPRITES: [[u8; 5]; 16] = [
 	[0xF0, 0x90, 0x90, 0x90, 0xF0], // 0
 	[0x20, 0x60, 0x20, 0x20, 0x70], // 1
@@ -39,3 +171,31 @@ pub const RAM_DIGIT_SPRITES: [[u8; 5]; 16] = [
 	[0xF0, 0x80, 0xF0, 0x80, 0xF0], // E
 	[0xF0, 0x80, 0xF0, 0x80, 0x80], // F
 ];
+/// Size of a custom font replacing `RAM_DIGIT_SPRITES`: 16 sprites of 5
+/// bytes, in the same order.
+pub const RAM_FONT_SIZE: usize = 80;
+
+// The SUPER-CHIP 8x10 digits go right after the 16 * 5 bytes above
+pub const RAM_BIG_DIGIT_SPRITES_ADDRESS: u16 =
+	RAM_DIGIT_SPRITES_ADDRESS + 0x50;
+/// Size of a custom font replacing `RAM_BIG_DIGIT_SPRITES`: 16 sprites of
+/// 10 bytes, in the same order.
+pub const RAM_BIG_FONT_SIZE: usize = 160;
+pub const RAM_BIG_DIGIT_SPRITES: [[u8; 10]; 16] = [
+	[0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF], // 0
+	[0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF], // 1
+	[0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF], // 2
+	[0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF], // 3
+	[0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03], // 4
+	[0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF], // 5
+	[0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF], // 6
+	[0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18], // 7
+	[0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF], // 8
+	[0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF], // 9
+	[0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3], // A
+	[0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC], // B
+	[0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C], // C
+	[0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC], // D
+	[0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF], // E
+	[0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0], // F
+];
@@ -1,3 +1,122 @@
+/// Initial values of the `Cpu` general purpose registers.
+///
+/// Some buggy ROMs read registers before writing them, and interpreters
+/// disagree on what they contain at startup.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InitialRegisters {
+	/// Every register is `0`.
+	Zero,
+	/// Registers are filled with random bytes generated from the seed, so
+	/// the same seed always produces the same values.
+	Random(u64),
+}
+
+impl std::str::FromStr for InitialRegisters {
+	type Err = anyhow::Error;
+
+	/// Parses `zero`, `random` or `random:<seed>`. Plain `random` picks a
+	/// random seed.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.split_once(':') {
+			None if s == "zero" => Ok(Self::Zero),
+			None if s == "random" => Ok(Self::Random(rand::random())),
+			Some(("random", seed)) => seed
+				.parse()
+				.map(Self::Random)
+				.map_err(|_| anyhow::anyhow!("Invalid seed: {seed}.")),
+			_ => Err(anyhow::anyhow!("Unknown register fill: {s}.")),
+		}
+	}
+}
+
+impl std::fmt::Display for InitialRegisters {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Zero => f.write_str("zero"),
+			Self::Random(seed) => write!(f, "random:{seed}"),
+		}
+	}
+}
+
+/// Snapshot of the `Cpu` registers, see `Cpu::save_state`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuState {
+	v: [u8; 16],
+	i: u16,
+	pc: u16,
+	return_stack: Vec<u16>,
+	rpl_flags: [u8; crate::consts::CPU_RPL_FLAGS],
+	audio_pattern: [u8; 16],
+	pitch: u8,
+}
+
+/// Progress of `Fx0A`, which waits for a key to be pressed and released.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum KeyWait {
+	/// Not waiting for a key.
+	Idle,
+	/// Waiting for a key press that happens after `Fx0A` started.
+	Press,
+	/// The key with this code was pressed, waiting for its release.
+	Release(u8),
+}
+
+/// Errors that stop the `Cpu` from running the loaded ROM.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CpuError {
+	/// The word at `pc` is not an instruction we know.
+	UnknownInstruction { pc: u16, opcode: u16 },
+	/// `2nnn` was run at `pc` with a full return stack.
+	StackOverflow { pc: u16 },
+	/// `00EE` was run at `pc` without a matching `2nnn` call.
+	StackUnderflow { pc: u16 },
+	/// The instruction at `pc` accessed memory out of bounds.
+	Ram { pc: u16, source: crate::ram::RamError },
+}
+
+impl std::fmt::Display for CpuError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnknownInstruction { pc, opcode } => {
+				write!(f, "Unknown instruction {opcode:#06X} at {pc:#05X}.")
+			}
+			Self::StackOverflow { pc } => {
+				write!(f, "Too many nested subroutine calls at {pc:#05X}.")
+			}
+			Self::StackUnderflow { pc } => {
+				write!(f, "Return without a subroutine call at {pc:#05X}.")
+			}
+			Self::Ram { pc, .. } => {
+				write!(f, "Invalid memory access at {pc:#05X}.")
+			}
+		}
+	}
+}
+
+impl CpuError {
+	/// Returns the address of the instruction that failed.
+	#[inline]
+	#[must_use]
+	pub const fn pc(&self) -> u16 {
+		match *self {
+			Self::UnknownInstruction { pc, .. }
+			| Self::StackOverflow { pc }
+			| Self::StackUnderflow { pc }
+			| Self::Ram { pc, .. } => pc,
+		}
+	}
+}
+
+impl std::error::Error for CpuError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Ram { source, .. } => Some(source),
+			_ => None,
+		}
+	}
+}
+
 /// Used by the `Emulator` to run the instructions of the loaded ROM in the
 /// `crate::ram::Ram`
 ///
@@ -12,14 +131,49 @@
 /// address.
 ///
 /// The `self.return_stack` is used to store the address that the interpreter
-/// shoud return to when finished with a subroutine.
+/// shoud return to when finished with a subroutine. It holds at most
+/// `self.max_stack_depth` addresses (16 on real hardware).
 pub struct Cpu {
 	v: [u8; 16],
 	i: u16,
 	pc: u16,
 	return_stack: Vec<u16>,
-	rng: rand::rngs::ThreadRng,
-	last_instruction_time: std::time::Instant,
+	max_stack_depth: usize,
+	rpl_flags: [u8; crate::consts::CPU_RPL_FLAGS],
+	audio_pattern: [u8; 16],
+	pitch: u8,
+	rng: rand::rngs::StdRng,
+	rng_seed: Option<u64>,
+	last_instruction_time: crate::time::Instant,
+	clock_hz: std::num::NonZeroU32,
+	speed_multiplier: std::num::NonZeroU32,
+	speed_divisor: std::num::NonZeroU32,
+	initial_registers: InitialRegisters,
+	shift_quirk: crate::quirks::ShiftQuirk,
+	memory_quirk: crate::quirks::MemoryQuirk,
+	jump_quirk: crate::quirks::JumpQuirk,
+	display_wait_quirk: crate::quirks::DisplayWaitQuirk,
+	/// Whether `00E0` waits along with `Dxyn`, see `self.set_clear_waits`.
+	clear_waits: bool,
+	index_overflow_quirk: crate::quirks::IndexOverflowQuirk,
+	logic_quirk: crate::quirks::LogicQuirk,
+	xochip: bool,
+	vblank_epoch: crate::time::Instant,
+	/// The frames ended by `self.end_frame`, if the vertical blank follows
+	/// them instead of the wall clock, see `self.set_vblank_follows_frames`.
+	ended_frames: Option<u64>,
+	vblank_wait_frame: Option<u64>,
+	key_wait: KeyWait,
+	/// Whether the last instruction jumped to itself, see `self.has_ended`.
+	ended: bool,
+	#[cfg(feature = "profile")]
+	profile: std::collections::HashMap<&'static str, u64>,
+}
+
+impl Default for Cpu {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl Cpu {
@@ -30,8 +184,403 @@ impl Cpu {
 			pc: crate::consts::RAM_ROM_START_ADDRESS,
 			v: [0; 16],
 			return_stack: vec![],
-			rng: rand::thread_rng(),
-			last_instruction_time: std::time::Instant::now(),
+			max_stack_depth: crate::consts::CPU_STACK_DEPTH,
+			rpl_flags: [0; crate::consts::CPU_RPL_FLAGS],
+			audio_pattern: crate::consts::AUDIO_PATTERN,
+			pitch: crate::consts::AUDIO_PITCH,
+			rng: rand::SeedableRng::from_entropy(),
+			rng_seed: None,
+			last_instruction_time: crate::time::Instant::now(),
+			clock_hz: crate::consts::CPU_CLOCK_HZ,
+			speed_multiplier: std::num::NonZeroU32::MIN,
+			speed_divisor: std::num::NonZeroU32::MIN,
+			initial_registers: InitialRegisters::Zero,
+			shift_quirk: crate::quirks::ShiftQuirk::default(),
+			memory_quirk: crate::quirks::MemoryQuirk::default(),
+			jump_quirk: crate::quirks::JumpQuirk::default(),
+			display_wait_quirk: crate::quirks::DisplayWaitQuirk::default(),
+			clear_waits: false,
+			index_overflow_quirk: crate::quirks::IndexOverflowQuirk::default(),
+			logic_quirk: crate::quirks::LogicQuirk::default(),
+			xochip: false,
+			vblank_epoch: crate::time::Instant::now(),
+			ended_frames: None,
+			vblank_wait_frame: None,
+			key_wait: KeyWait::Idle,
+			ended: false,
+			#[cfg(feature = "profile")]
+			profile: std::collections::HashMap::new(),
+		}
+	}
+
+	/// Returns how many times each mnemonic (see `disasm::mnemonic`) ran,
+	/// the most frequent first. Ties are ordered by mnemonic.
+	#[cfg(feature = "profile")]
+	#[must_use]
+	pub fn profile_report(&self) -> Vec<(String, u64)> {
+		let mut report: Vec<_> = self
+			.profile
+			.iter()
+			.map(|(&mnemonic, &count)| (mnemonic.to_owned(), count))
+			.collect();
+		report.sort_unstable_by(|a, b| {
+			b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))
+		});
+		report
+	}
+
+	/// Returns how many instructions per second the CPU runs.
+	#[inline]
+	#[must_use]
+	pub const fn clock_hz(&self) -> std::num::NonZeroU32 {
+		self.clock_hz
+	}
+
+	/// Sets how many instructions per second the CPU runs. See
+	/// `self.can_run_instruction`.
+	#[inline]
+	pub fn set_clock_hz(&mut self, clock_hz: std::num::NonZeroU32) {
+		self.clock_hz = clock_hz;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn speed_multiplier(&self) -> std::num::NonZeroU32 {
+		self.speed_multiplier
+	}
+
+	/// Runs `multiplier` times faster than `self.clock_hz`, for
+	/// fast-forwarding. The vertical blank of `self.display_wait_quirk`
+	/// comes that much more often too.
+	#[inline]
+	pub fn set_speed_multiplier(&mut self, multiplier: std::num::NonZeroU32) {
+		self.speed_multiplier = multiplier;
+		self.restart_vblank_frames();
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn speed_divisor(&self) -> std::num::NonZeroU32 {
+		self.speed_divisor
+	}
+
+	/// Runs `divisor` times slower than `self.clock_hz`, for slow motion,
+	/// like `self.set_speed_multiplier` in reverse. Both apply at once.
+	#[inline]
+	pub fn set_speed_divisor(&mut self, divisor: std::num::NonZeroU32) {
+		self.speed_divisor = divisor;
+		self.restart_vblank_frames();
+	}
+
+	/// Returns how much emulated time passes in the wall-clock `duration`
+	/// at the speed of `self.speed_multiplier` and `self.speed_divisor`.
+	#[inline]
+	#[must_use]
+	pub fn emulated_duration(
+		&self,
+		duration: std::time::Duration,
+	) -> std::time::Duration {
+		duration.saturating_mul(self.speed_multiplier.get())
+			/ self.speed_divisor.get()
+	}
+
+	/// Returns how long the emulated `duration` takes on the wall clock, the
+	/// reverse of `self.emulated_duration`.
+	#[inline]
+	#[must_use]
+	pub fn wall_clock_duration(
+		&self,
+		duration: std::time::Duration,
+	) -> std::time::Duration {
+		duration.saturating_mul(self.speed_divisor.get())
+			/ self.speed_multiplier.get()
+	}
+
+	/// Frame numbers of the previous speed mean nothing at the new one, so
+	/// they start over.
+	#[inline]
+	fn restart_vblank_frames(&mut self) {
+		self.vblank_epoch = crate::time::Instant::now();
+		self.vblank_wait_frame = None;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn initial_registers(&self) -> InitialRegisters {
+		self.initial_registers
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn max_stack_depth(&self) -> usize {
+		self.max_stack_depth
+	}
+
+	/// Sets how many nested subroutine calls are allowed before
+	/// `CpuError::StackOverflow`.
+	#[inline]
+	pub fn set_max_stack_depth(&mut self, depth: usize) {
+		self.max_stack_depth = depth;
+	}
+
+	/// Returns the SUPER-CHIP RPL user flags saved by `Fx75`. They survive
+	/// resets, so frontends may also persist them between runs.
+	#[inline]
+	#[must_use]
+	pub const fn rpl_flags(&self) -> [u8; crate::consts::CPU_RPL_FLAGS] {
+		self.rpl_flags
+	}
+
+	#[inline]
+	pub fn set_rpl_flags(
+		&mut self,
+		flags: [u8; crate::consts::CPU_RPL_FLAGS],
+	) {
+		self.rpl_flags = flags;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn shift_quirk(&self) -> crate::quirks::ShiftQuirk {
+		self.shift_quirk
+	}
+
+	#[inline]
+	pub fn set_shift_quirk(&mut self, quirk: crate::quirks::ShiftQuirk) {
+		self.shift_quirk = quirk;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn memory_quirk(&self) -> crate::quirks::MemoryQuirk {
+		self.memory_quirk
+	}
+
+	#[inline]
+	pub fn set_memory_quirk(&mut self, quirk: crate::quirks::MemoryQuirk) {
+		self.memory_quirk = quirk;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn jump_quirk(&self) -> crate::quirks::JumpQuirk {
+		self.jump_quirk
+	}
+
+	#[inline]
+	pub fn set_jump_quirk(&mut self, quirk: crate::quirks::JumpQuirk) {
+		self.jump_quirk = quirk;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn logic_quirk(&self) -> crate::quirks::LogicQuirk {
+		self.logic_quirk
+	}
+
+	#[inline]
+	pub fn set_logic_quirk(&mut self, quirk: crate::quirks::LogicQuirk) {
+		self.logic_quirk = quirk;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn index_overflow_quirk(
+		&self,
+	) -> crate::quirks::IndexOverflowQuirk {
+		self.index_overflow_quirk
+	}
+
+	#[inline]
+	pub fn set_index_overflow_quirk(
+		&mut self,
+		quirk: crate::quirks::IndexOverflowQuirk,
+	) {
+		self.index_overflow_quirk = quirk;
+	}
+
+	/// Returns the 128 one-bit samples the XO-CHIP buzzer loops while the
+	/// sound timer is nonzero, the first one in the most significant bit,
+	/// see `F002`.
+	#[inline]
+	#[must_use]
+	pub const fn audio_pattern(&self) -> [u8; 16] {
+		self.audio_pattern
+	}
+
+	/// Returns the XO-CHIP pitch that the `self.audio_pattern` is played at,
+	/// see `Fx3A`.
+	#[inline]
+	#[must_use]
+	pub const fn pitch(&self) -> u8 {
+		self.pitch
+	}
+
+	/// Whether the XO-CHIP instructions are enabled.
+	#[inline]
+	#[must_use]
+	pub const fn is_xochip(&self) -> bool {
+		self.xochip
+	}
+
+	/// Enables or disables the XO-CHIP instructions: `Fn01` selects the
+	/// `Screen` planes that the drawing, clearing and scrolling affect, and
+	/// `F000 nnnn` loads a 16-bit address into `self.i`, which is no longer
+	/// masked to 12 bits, and `F002` and `Fx3A` set the
+	/// `self.audio_pattern` and the `self.pitch`. `5xy2` and `5xy3` save and
+	/// load the registers `x` through `y` at `self.i`. Give the `Ram` the
+	/// `consts::RAM_XOCHIP_SIZE` to make all of it addressable. While
+	/// disabled they are unknown instructions, like on plain Chip-8.
+	#[inline]
+	pub fn set_xochip(&mut self, xochip: bool) {
+		self.xochip = xochip;
+		self.i &= self.index_mask();
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn display_wait_quirk(&self) -> crate::quirks::DisplayWaitQuirk {
+		self.display_wait_quirk
+	}
+
+	/// Sets which instructions wait for the vertical blank.
+	///
+	/// A waiting instruction is fetched again on every cycle without
+	/// advancing `self.pc`, so it still consumes the `self.clock_hz` budget
+	/// of `self.can_run_instruction`: at 500 Hz a draw takes up to about 8
+	/// cycles. The frames follow the wall clock even in `self.step`, unless
+	/// `self.set_vblank_follows_frames` is on.
+	#[inline]
+	pub fn set_display_wait_quirk(
+		&mut self,
+		quirk: crate::quirks::DisplayWaitQuirk,
+	) {
+		self.display_wait_quirk = quirk;
+		self.vblank_wait_frame = None;
+	}
+
+	/// Makes the vertical blank of `self.display_wait_quirk` come with every
+	/// `self.end_frame` instead of every 60th of a second of wall-clock time,
+	/// so headless runs with the quirk are reproducible. The `Emulator` turns
+	/// it on in every `TimerMode` but `TimerMode::WallClock`.
+	#[inline]
+	pub fn set_vblank_follows_frames(&mut self, enabled: bool) {
+		self.ended_frames = enabled.then_some(0);
+		self.vblank_wait_frame = None;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn clear_waits(&self) -> bool {
+		self.clear_waits
+	}
+
+	/// Makes `00E0` wait for the vertical blank like `Dxyn` while
+	/// `self.display_wait_quirk` is on. Off by default.
+	#[inline]
+	pub fn set_clear_waits(&mut self, clear_waits: bool) {
+		self.clear_waits = clear_waits;
+		self.vblank_wait_frame = None;
+	}
+
+	/// Sets how the general purpose registers are filled at construction
+	/// and on every `self.reset`, and applies it immediately.
+	pub fn set_initial_registers(&mut self, pattern: InitialRegisters) {
+		self.initial_registers = pattern;
+		self.fill_registers();
+	}
+
+	/// Reseeds the generator used by `Cxnn`, so runs with the same seed
+	/// produce the same random numbers. `self.reset` reseeds it again.
+	#[inline]
+	pub fn set_rng_seed(&mut self, seed: u64) {
+		self.rng = rand::SeedableRng::seed_from_u64(seed);
+		self.rng_seed = Some(seed);
+	}
+
+	/// Returns the registers v0 through vf.
+	#[inline]
+	#[must_use]
+	pub const fn registers(&self) -> &[u8; 16] {
+		&self.v
+	}
+
+	/// Returns the `i` register.
+	#[inline]
+	#[must_use]
+	pub const fn index(&self) -> u16 {
+		self.i
+	}
+
+	/// Returns the address of the next instruction.
+	#[inline]
+	#[must_use]
+	pub const fn pc(&self) -> u16 {
+		self.pc
+	}
+
+	/// Returns the return addresses of the active subroutine calls, the
+	/// innermost last.
+	#[inline]
+	#[must_use]
+	pub fn stack(&self) -> &[u16] {
+		&self.return_stack
+	}
+
+	/// Captures the registers, the program counter and the return stack.
+	/// The configuration and the random generator are not part of it.
+	#[must_use]
+	pub fn save_state(&self) -> CpuState {
+		CpuState {
+			v: self.v,
+			i: self.i,
+			pc: self.pc,
+			return_stack: self.return_stack.clone(),
+			rpl_flags: self.rpl_flags,
+			audio_pattern: self.audio_pattern,
+			pitch: self.pitch,
+		}
+	}
+
+	/// Restores the registers captured by `self.save_state`.
+	pub fn load_state(&mut self, state: &CpuState) {
+		self.v = state.v;
+		self.i = state.i & self.index_mask();
+		self.pc = state.pc;
+		self.return_stack.clone_from(&state.return_stack);
+		self.rpl_flags = state.rpl_flags;
+		self.audio_pattern = state.audio_pattern;
+		self.pitch = state.pitch;
+		self.key_wait = KeyWait::Idle;
+		self.ended = false;
+		self.last_instruction_time = crate::time::Instant::now();
+	}
+
+	/// Returns the CPU to its power-on state, keeping its configuration.
+	pub fn reset(&mut self) {
+		self.i = 0;
+		self.pc = crate::consts::RAM_ROM_START_ADDRESS;
+		self.return_stack.clear();
+		self.audio_pattern = crate::consts::AUDIO_PATTERN;
+		self.pitch = crate::consts::AUDIO_PITCH;
+		self.last_instruction_time = crate::time::Instant::now();
+		self.vblank_wait_frame = None;
+		self.key_wait = KeyWait::Idle;
+		self.ended = false;
+		if let Some(seed) = self.rng_seed {
+			self.rng = rand::SeedableRng::seed_from_u64(seed);
+		}
+		self.fill_registers();
+	}
+
+	fn fill_registers(&mut self) {
+		use rand::{Rng as _, SeedableRng as _};
+
+		match self.initial_registers {
+			InitialRegisters::Zero => self.v = [0; 16],
+			InitialRegisters::Random(seed) => {
+				rand::rngs::StdRng::seed_from_u64(seed).fill(&mut self.v);
+			}
 		}
 	}
 
@@ -41,6 +590,31 @@ impl Cpu {
 	/// # Debug panic
 	///
 	/// If `self.can_run_instruction()` is `false`.
+	///
+	/// # Errors
+	///
+	/// See `self.step`.
+	#[inline]
+	pub fn run_instruction(
+		&mut self,
+		ram: &mut crate::ram::Ram,
+		timer: &mut crate::timer::Timer,
+		screen: &mut crate::screen::Screen,
+		keyboard: &mut crate::keyboard::Keyboard,
+	) -> Result<(), CpuError> {
+		debug_assert!(self.can_run_instruction());
+		self.last_instruction_time = crate::time::Instant::now();
+		self.step(ram, timer, screen, keyboard)
+	}
+
+	/// Runs the following instruction right away, ignoring the
+	/// `self.can_run_instruction` timing.
+	///
+	/// # Errors
+	///
+	/// If the instruction is unknown, calls a subroutine with a full return
+	/// stack, returns from a subroutine without calling one or accesses
+	/// memory out of bounds. The program counter is left unchanged.
 	#[allow(clippy::too_many_lines)]
 	#[cfg_attr(
 		feature = "tracing",
@@ -53,20 +627,18 @@ impl Cpu {
 			skip_all,
 		),
 	)]
-	pub fn run_instruction(
+	pub fn step(
 		&mut self,
 		ram: &mut crate::ram::Ram,
 		timer: &mut crate::timer::Timer,
 		screen: &mut crate::screen::Screen,
-		keyboard: &crate::keyboard::Keyboard,
-	) {
+		keyboard: &mut crate::keyboard::Keyboard,
+	) -> Result<(), CpuError> {
 		use rand::Rng;
 
-		debug_assert!(self.can_run_instruction());
-		self.last_instruction_time = std::time::Instant::now();
-
-		let first_byte = u16::from(ram.read(self.pc));
-		let second_byte = u16::from(ram.read(self.pc + 1));
+		let first_byte = u16::from(self.try_read(ram, self.pc)?);
+		let second_byte =
+			u16::from(self.try_read(ram, self.pc.wrapping_add(1))?);
 		let instruction = (first_byte << 8) | second_byte;
 		#[cfg(feature = "tracing")]
 		tracing::trace!("Read instruction {:#X}:{:#X}", self.pc, instruction);
@@ -84,74 +656,154 @@ impl Cpu {
 		let xu = x as usize;
 		let yu = y as usize;
 
+		// Retry the instruction on the next cycle until a new frame starts,
+		// see `self.set_display_wait_quirk`
+		if self.waits_for_vblank(instruction) && !self.vblank_passed() {
+			return Ok(());
+		}
+		// Batched draws land before the screen changes in any other way, see
+		// `DrawBatchQuirk::Frame`
+		if Self::moves_pixels(instruction) {
+			self.commit_draws(screen);
+		}
+		#[cfg(feature = "profile")]
+		{
+			*self
+				.profile
+				.entry(crate::disasm::mnemonic(instruction))
+				.or_default() += 1;
+		}
+
 		match ((instruction & 0xF000) >> 12, nn, n) {
 			// Clear the screen
 			(0x0, 0xE0, _) => {
 				screen.clear();
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Scroll the screen down by `n` lines (SUPER-CHIP)
+			(0x0, _, _) if instruction & 0xFFF0 == 0x00C0 => {
+				screen.scroll_down(n as usize);
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Scroll the screen right by 4 pixels (SUPER-CHIP)
+			(0x0, 0xFB, _) if x == 0 => {
+				screen.scroll_right();
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Scroll the screen left by 4 pixels (SUPER-CHIP)
+			(0x0, 0xFC, _) if x == 0 => {
+				screen.scroll_left();
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Switch to the 64x32 resolution (SUPER-CHIP)
+			(0x0, 0xFE, _) if x == 0 => {
+				screen.set_hires(false);
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Switch to the 128x64 resolution (SUPER-CHIP)
+			(0x0, 0xFF, _) if x == 0 => {
+				screen.set_hires(true);
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Return from a subroutine
-			(0x0, 0xEE, _) => self.pc = self.return_stack.pop().unwrap(),
+			(0x0, 0xEE, _) => {
+				self.pc = self
+					.return_stack
+					.pop()
+					.ok_or(CpuError::StackUnderflow { pc: self.pc })?;
+			}
+			// Call a machine code routine at nnn (SYS). Only the original
+			// COSMAC VIP could run it, so it is ignored like on every modern
+			// interpreter.
+			(0x0, _, _) => {
+				#[cfg(feature = "tracing")]
+				tracing::debug!("Ignored SYS {:#05X} at {:#X}", nnn, self.pc);
+				self.pc = self.pc.wrapping_add(2);
+			}
 			// Jump to location `nnn`
-			(0x1, _, _) => self.pc = nnn,
+			(0x1, _, _) => self.jump(nnn),
 			// Call subroutine at nnn
 			(0x2, _, _) => {
-				self.return_stack.push(self.pc + 2);
+				if self.return_stack.len() >= self.max_stack_depth {
+					return Err(CpuError::StackOverflow { pc: self.pc });
+				}
+				self.return_stack.push(self.pc.wrapping_add(2));
 				self.pc = nnn;
 			}
 			// Skip next instruction if vx == nn
 			(0x3, _, _) => {
 				if self.v[xu] == nn {
-					self.pc += 4;
+					self.skip_next_instruction(ram);
 				} else {
-					self.pc += 2;
+					self.pc = self.pc.wrapping_add(2);
 				}
 			}
 			// Skip next instruction if vx != nn
 			(0x4, _, _) => {
 				if self.v[xu] == nn {
-					self.pc += 2;
+					self.pc = self.pc.wrapping_add(2);
 				} else {
-					self.pc += 4;
+					self.skip_next_instruction(ram);
 				}
 			}
 			// Skip next instruction if vx == vy
 			(0x5, _, 0x0) => {
 				if self.v[xu] == self.v[yu] {
-					self.pc += 4;
+					self.skip_next_instruction(ram);
 				} else {
-					self.pc += 2;
+					self.pc = self.pc.wrapping_add(2);
+				}
+			}
+			// Store registers vx through vy in memory starting at location i,
+			// in descending order if x > y. I is unchanged (XO-CHIP)
+			(0x5, _, 0x2) if self.xochip => {
+				for (offset, register) in Self::register_range(xu, yu) {
+					let address = self.i.wrapping_add(offset);
+					self.try_write(ram, address, self.v[register])?;
 				}
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Read registers vx through vy from memory starting at location
+			// i, in descending order if x > y. I is unchanged (XO-CHIP)
+			(0x5, _, 0x3) if self.xochip => {
+				for (offset, register) in Self::register_range(xu, yu) {
+					let address = self.i.wrapping_add(offset);
+					self.v[register] = self.try_read(ram, address)?;
+				}
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = nn
 			(0x6, _, _) => {
 				self.v[xu] = nn;
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = vx + nn
 			(0x7, _, _) => {
 				self.v[xu] = self.v[xu].wrapping_add(nn);
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = vy
 			(0x8, _, 0x0) => {
 				self.v[xu] = self.v[yu];
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = vx OR vy
 			(0x8, _, 0x1) => {
 				self.v[xu] |= self.v[yu];
-				self.pc += 2;
+				self.apply_logic_quirk();
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = vx AND vy
 			(0x8, _, 0x2) => {
 				self.v[xu] &= self.v[yu];
-				self.pc += 2;
+				self.apply_logic_quirk();
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = vx XOR vy
 			(0x8, _, 0x3) => {
 				self.v[xu] ^= self.v[yu];
-				self.pc += 2;
+				self.apply_logic_quirk();
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set vx = vx + vy. If overflowing, vf = 1, otherwise vf = 0
 			(0x8, _, 0x4) => {
@@ -161,7 +813,7 @@ impl Cpu {
 				self.v[xu] = sum;
 				self.v[0xF] = u8::from(is_overflow);
 
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// If vx => vy, vf = 1, otherwise vf = 0. Set vx = vx - vy
 			(0x8, _, 0x5) => {
@@ -171,15 +823,17 @@ impl Cpu {
 				self.v[xu] = diff;
 				self.v[0xF] = u8::from(!is_overflow);
 
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
-			// If the least-significant bit of vx is 1, vf = 1, otherwise vf =
-			// 0. Set vx = vx SHR 1
+			// Set vx = source SHR 1. If the least-significant bit of source
+			// is 1, vf = 1, otherwise vf = 0. Source is vx or vy depending on
+			// `self.shift_quirk`
 			(0x8, _, 0x6) => {
-				self.v[0xF] = self.v[xu] & 0x1;
-				self.v[xu] >>= 1;
+				let source = self.shift_source(xu, yu);
+				self.v[xu] = source >> 1;
+				self.v[0xF] = source & 0x1;
 
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// if vx <= vy, vf = 1, otherwise vf = 0. Set vx = vy - vx
 			(0x8, _, 0x7) => {
@@ -189,131 +843,418 @@ impl Cpu {
 				self.v[xu] = diff;
 				self.v[0xF] = u8::from(!is_overflow);
 
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
-			// If the most-significant bit of vx is 1, vf = 1, otherwise vf =
-			// 0. Set vx = vx SHL 1
+			// Set vx = source SHL 1. If the most-significant bit of source
+			// is 1, vf = 1, otherwise vf = 0. Source is vx or vy depending on
+			// `self.shift_quirk`
 			(0x8, _, 0xE) => {
-				self.v[0xF] = (self.v[xu] & 0x80) >> 7;
-				self.v[xu] <<= 1;
+				let source = self.shift_source(xu, yu);
+				self.v[xu] = source << 1;
+				self.v[0xF] = (source & 0x80) >> 7;
 
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Skip next instruction, if vx != vy
 			(0x9, _, 0x0) => {
 				if self.v[xu] == self.v[yu] {
-					self.pc += 2;
+					self.pc = self.pc.wrapping_add(2);
 				} else {
-					self.pc += 4;
+					self.skip_next_instruction(ram);
 				}
 			}
 			// Set i = nnn
 			(0xA, _, _) => {
 				self.i = nnn;
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Jump to location nnn + v0, or nnn + vx depending on
+			// `self.jump_quirk`. The address wraps to stay within 12 bits
+			(0xB, _, _) => {
+				let offset = match self.jump_quirk {
+					crate::quirks::JumpQuirk::V0 => self.v[0x0],
+					crate::quirks::JumpQuirk::Vx => self.v[xu],
+				};
+				self.jump((nnn + u16::from(offset)) & 0x0FFF);
 			}
-			// Jump to location nnn + v0
-			(0xB, _, _) => self.pc = nnn + u16::from(self.v[0x0]),
 			// Set vx = random byte AND nn
 			(0xC, _, _) => {
 				self.v[xu] = self.rng.gen::<u8>() & nn;
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Draws n-byte sprite starting at memory location i at (vx, vy)
 			// Sprites are XORed onto the existing screen. If this causes any
 			// pixels to be erased, vf = 1, otherwise vf = 0
+			// In the high resolution `Dxy0` draws a 16x16 sprite (SUPER-CHIP)
+			(0xD, _, 0x0) if screen.is_hires() => {
+				self.draw_wide_sprite(self.v[xu], self.v[yu], ram, screen)?;
+				self.pc = self.pc.wrapping_add(2);
+			}
 			(0xD, _, _) => {
-				self.draw_sprite(self.v[xu], self.v[yu], n, ram, screen);
-				self.pc += 2;
+				self.draw_sprite(self.v[xu], self.v[yu], n, ram, screen)?;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Skip next instruction, if vx key is pressed
 			(0xE, 0x9E, _) => {
 				if keyboard.is_key_pressed(self.v[xu]) {
-					self.pc += 4;
+					self.skip_next_instruction(ram);
 				} else {
-					self.pc += 2;
+					self.pc = self.pc.wrapping_add(2);
 				}
 			}
 			// Skip next instruction, if vx key is not pressed
 			(0xE, 0xA1, _) => {
 				if keyboard.is_key_pressed(self.v[xu]) {
-					self.pc += 2;
+					self.pc = self.pc.wrapping_add(2);
 				} else {
-					self.pc += 4;
+					self.skip_next_instruction(ram);
 				}
 			}
+			// Set i = the 16-bit address in the next two bytes, skipping them
+			// (XO-CHIP)
+			(0xF, 0x00, _) if self.xochip && x == 0 => {
+				let high = self.try_read(ram, self.pc.wrapping_add(2))?;
+				let low = self.try_read(ram, self.pc.wrapping_add(3))?;
+				self.i = u16::from_be_bytes([high, low]);
+				self.pc = self.pc.wrapping_add(4);
+			}
+			// Load the 16 bytes starting at i into the audio pattern
+			// (XO-CHIP)
+			(0xF, 0x02, _) if self.xochip && x == 0 => {
+				let mut pattern = [0; 16];
+				for (offset, sample) in (0..).zip(&mut pattern) {
+					*sample =
+						self.try_read(ram, self.i.wrapping_add(offset))?;
+				}
+				self.audio_pattern = pattern;
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Set the audio pitch = vx (XO-CHIP)
+			(0xF, 0x3A, _) if self.xochip => {
+				self.pitch = self.v[xu];
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Select the drawing planes `x` (XO-CHIP)
+			(0xF, 0x01, _) if self.xochip => {
+				screen.select_planes(x);
+				self.pc = self.pc.wrapping_add(2);
+			}
 			// Set vx = delay timer value
 			(0xF, 0x07, _) => {
 				self.v[xu] = timer.get_delay();
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
-			// If any key is pressed, place it code in vx
-			(0xF, 0x0A, _) => {
-				if let Some(c) = keyboard.pressed_key_code {
-					self.v[xu] = c;
-					self.pc += 2;
+			// Wait for a key to be pressed and released, then place its code
+			// in vx. Until then pc stays here, so this runs again on every
+			// cycle. If several keys are pressed, the lowest code is used (see
+			// `Keyboard::press_key`)
+			(0xF, 0x0A, _) => match self.key_wait {
+				KeyWait::Idle => {
+					// Presses from before the wait must not complete it
+					let _ = keyboard.take_pressed_key();
+					self.key_wait = KeyWait::Press;
 				}
-			}
+				KeyWait::Press => {
+					if let Some(c) = keyboard.take_pressed_key() {
+						self.key_wait = KeyWait::Release(c);
+					}
+				}
+				KeyWait::Release(c) => {
+					if !keyboard.is_key_pressed(c) {
+						self.v[xu] = c;
+						self.key_wait = KeyWait::Idle;
+						self.pc = self.pc.wrapping_add(2);
+					}
+				}
+			},
 			// Set delay timer = vx
 			(0xF, 0x15, _) => {
 				timer.set_delay(self.v[xu]);
-				self.pc += 2;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set sound timer = vx
-			(0xF, 0x18, _) => self.pc += 2, // No sound
-			// Set i = i + vx
+			(0xF, 0x18, _) => {
+				timer.set_sound(self.v[xu]);
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Set i = i + vx, wrapped to the address space
 			(0xF, 0x1E, _) => {
-				self.i += u16::from(self.v[xu]);
-				self.pc += 2;
+				let mask = self.index_mask();
+				let (sum, carry) =
+					self.i.overflowing_add(u16::from(self.v[xu]));
+				if self.index_overflow_quirk
+					== crate::quirks::IndexOverflowQuirk::SetVf
+				{
+					self.v[0xF] = u8::from(carry || sum > mask);
+				}
+				self.i = sum & mask;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Set i = location of sprite for digit vx
 			(0xF, 0x29, _) => {
 				// Multiply by 5 because each sprite has 5 lines, each line is
 				// 1 byte.
-				self.i = u16::from(self.v[xu]) * 5;
-				self.pc += 2;
+				self.i = crate::consts::RAM_DIGIT_SPRITES_ADDRESS
+					+ u16::from(self.v[xu]) * 5;
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Set i = location of the 8x10 sprite for digit vx (SUPER-CHIP)
+			(0xF, 0x30, _) => {
+				// Multiply by 10 because each sprite has 10 lines, each line
+				// is 1 byte.
+				self.i = crate::consts::RAM_BIG_DIGIT_SPRITES_ADDRESS
+					+ u16::from(self.v[xu]) * 10;
+				self.pc = self.pc.wrapping_add(2);
 			}
 			// Takes hundreds, tens and ones of vx and writes them one after
-			// another starting with i
+			// another starting with i. The ones are written first, so an i
+			// too close to the end of memory fails before anything changes.
 			(0xF, 0x33, _) => {
 				let vx = self.v[xu];
-				ram.write(self.i, vx / 100);
-				ram.write(self.i + 1, (vx % 100) / 10);
-				ram.write(self.i + 2, vx % 10);
-				self.pc += 2;
+				self.try_write(ram, self.i.wrapping_add(2), vx % 10)?;
+				self.try_write(ram, self.i.wrapping_add(1), (vx % 100) / 10)?;
+				self.try_write(ram, self.i, vx / 100)?;
+				self.pc = self.pc.wrapping_add(2);
 			}
-			// Store registers v0 through vx im memory starting at location i
+			// Store registers v0 through vx im memory starting at location i.
+			// Then advance i if `self.memory_quirk` says so
 			(0xF, 0x55, _) => {
 				for i in 0..=x {
-					ram.write(self.i + u16::from(i), self.v[i as usize]);
+					let address = self.i.wrapping_add(u16::from(i));
+					self.try_write(ram, address, self.v[i as usize])?;
 				}
-				self.pc += 2;
+				self.apply_memory_quirk(x);
+				self.pc = self.pc.wrapping_add(2);
 			}
-			// Read register v0 through vx from memory starting at location i
+			// Read register v0 through vx from memory starting at location i.
+			// Then advance i if `self.memory_quirk` says so
 			(0xF, 0x65, _) => {
 				for i in 0..=x {
-					self.v[i as usize] = ram.read(self.i + u16::from(i));
+					self.v[i as usize] =
+						self.try_read(ram, self.i.wrapping_add(u16::from(i)))?;
 				}
-				self.pc += 2;
+				self.apply_memory_quirk(x);
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Save v0 through vx to the RPL user flags (SUPER-CHIP)
+			(0xF, 0x75, _) if xu < crate::consts::CPU_RPL_FLAGS => {
+				self.rpl_flags[..=xu].copy_from_slice(&self.v[..=xu]);
+				self.pc = self.pc.wrapping_add(2);
+			}
+			// Restore v0 through vx from the RPL user flags (SUPER-CHIP)
+			(0xF, 0x85, _) if xu < crate::consts::CPU_RPL_FLAGS => {
+				self.v[..=xu].copy_from_slice(&self.rpl_flags[..=xu]);
+				self.pc = self.pc.wrapping_add(2);
+			}
+			_ => {
+				return Err(CpuError::UnknownInstruction {
+					pc: self.pc,
+					opcode: instruction,
+				})
 			}
-			_ => unreachable!(
-				"Invalid instruction: {:#X}:{:#X}",
-				self.pc, instruction
-			),
 		}
+		Ok(())
 	}
 
 	/// Determines whether enough time has elapsed for us to run a new
-	/// instruction
+	/// instruction, that is, one period of `self.clock_hz` at the speed of
+	/// `self.speed_multiplier` and `self.speed_divisor`.
 	#[inline]
 	#[must_use]
 	pub fn can_run_instruction(&self) -> bool {
-		self.last_instruction_time.elapsed()
-			> std::time::Duration::from_millis(2)
+		self.last_instruction_time.elapsed() > self.instruction_period()
+	}
+
+	/// Whether `Fx0A` is waiting for a key to be pressed and released.
+	#[inline]
+	#[must_use]
+	pub fn is_waiting_for_key(&self) -> bool {
+		self.key_wait != KeyWait::Idle
+	}
+
+	/// Whether the ROM has ended: the last instruction was a `1nnn` or `Bnnn`
+	/// jump to its own address. Nothing but the timers can change after it,
+	/// so there is no point in running it again until a reset.
+	#[inline]
+	#[must_use]
+	pub const fn has_ended(&self) -> bool {
+		self.ended
+	}
+
+	/// Jumps to `address`, noting whether it is the address of the jump, see
+	/// `self.has_ended`.
+	#[inline]
+	fn jump(&mut self, address: u16) {
+		self.ended = address == self.pc;
+		self.pc = address;
+	}
+
+	/// Whether the last instruction is waiting for the vertical blank because
+	/// of `self.display_wait_quirk`, so running it again does nothing until
+	/// the next 60Hz frame.
+	#[inline]
+	#[must_use]
+	pub const fn is_waiting_for_vblank(&self) -> bool {
+		self.vblank_wait_frame.is_some()
+	}
+
+	/// Returns how long until `self.can_run_instruction` becomes `true`, zero
+	/// if it already is.
+	#[inline]
+	#[must_use]
+	pub fn time_until_next_instruction(&self) -> std::time::Duration {
+		self.instruction_period()
+			.saturating_sub(self.last_instruction_time.elapsed())
+	}
+
+	#[inline]
+	fn instruction_period(&self) -> std::time::Duration {
+		self.wall_clock_duration(
+			std::time::Duration::from_secs(1) / self.clock_hz.get(),
+		)
+	}
+
+	/// Returns the value shifted by `8xy6` and `8xyE` according to
+	/// `self.shift_quirk`.
+	#[inline]
+	fn shift_source(&self, xu: usize, yu: usize) -> u8 {
+		match self.shift_quirk {
+			crate::quirks::ShiftQuirk::ShiftVx => self.v[xu],
+			crate::quirks::ShiftQuirk::ShiftVy => self.v[yu],
+		}
+	}
+
+	/// Whether `self.display_wait_quirk` makes the `instruction` wait for the
+	/// vertical blank.
+	#[inline]
+	const fn waits_for_vblank(&self, instruction: u16) -> bool {
+		use crate::quirks::DisplayWaitQuirk;
+
+		let is_draw = instruction & 0xF000 == 0xD000;
+		// Like the decoder, `01E0` to `0FE0` clear the screen too
+		let is_clear = instruction & 0xF0FF == 0x00E0;
+		match self.display_wait_quirk {
+			DisplayWaitQuirk::Off => false,
+			DisplayWaitQuirk::Draw => {
+				is_draw || (is_clear && self.clear_waits)
+			}
+		}
+	}
+
+	/// Whether the `instruction` clears, scrolls or switches the resolution
+	/// of the screen.
+	#[inline]
+	const fn moves_pixels(instruction: u16) -> bool {
+		matches!(instruction, 0x00FB | 0x00FC | 0x00FE | 0x00FF)
+			|| instruction & 0xF0FF == 0x00E0
+			|| instruction & 0xFFF0 == 0x00C0
+	}
+
+	/// Shows the draws batched by `DrawBatchQuirk::Frame`, see
+	/// `Screen::commit_draws`, and sets vf if they erased any pixel.
+	#[inline]
+	pub fn commit_draws(&mut self, screen: &mut crate::screen::Screen) {
+		if let Some(erased) = screen.commit_draws() {
+			self.v[0xF] = u8::from(erased);
+		}
+	}
+
+	/// Ends a 60Hz frame: commits the batched draws with
+	/// `self.commit_draws` and starts the next vertical blank if it follows
+	/// the frames, see `self.set_vblank_follows_frames`. The `Emulator`
+	/// calls it whenever it ticks the `Timer`.
+	#[inline]
+	pub fn end_frame(&mut self, screen: &mut crate::screen::Screen) {
+		self.commit_draws(screen);
+		if let Some(frames) = &mut self.ended_frames {
+			*frames += 1;
+		}
+	}
+
+	/// Starts waiting for the next 60Hz frame on the first call and returns
+	/// `true` once it has begun, ending the wait.
+	#[inline]
+	fn vblank_passed(&mut self) -> bool {
+		use std::convert::TryFrom as _;
+
+		let frame = self.ended_frames.unwrap_or_else(|| {
+			let elapsed = self
+				.emulated_duration(self.vblank_epoch.elapsed())
+				.as_micros();
+			u64::try_from(elapsed * 60 / 1_000_000).unwrap_or(u64::MAX)
+		});
+		match self.vblank_wait_frame {
+			Some(wait_frame) if frame > wait_frame => {
+				self.vblank_wait_frame = None;
+				true
+			}
+			Some(_) => false,
+			None => {
+				self.vblank_wait_frame = Some(frame);
+				false
+			}
+		}
+	}
+
+	/// Resets vf after `8xy1`, `8xy2` and `8xy3`, if `self.logic_quirk`
+	/// requires it.
+	#[inline]
+	fn apply_logic_quirk(&mut self) {
+		if self.logic_quirk == crate::quirks::LogicQuirk::ResetVf {
+			self.v[0xF] = 0;
+		}
+	}
+
+	/// Advances `self.i` past the `x + 1` registers stored or loaded by
+	/// `Fx55` and `Fx65`, if `self.memory_quirk` requires it.
+	#[inline]
+	fn apply_memory_quirk(&mut self, x: u8) {
+		if self.memory_quirk == crate::quirks::MemoryQuirk::IncrementIndex {
+			self.i = self.i.wrapping_add(u16::from(x) + 1) & self.index_mask();
+		}
+	}
+
+	/// Returns the mask that keeps `self.i` within the address space: 12
+	/// bits, or all 16 with XO-CHIP.
+	#[inline]
+	const fn index_mask(&self) -> u16 {
+		if self.xochip {
+			u16::MAX
+		} else {
+			crate::consts::CPU_INDEX_MASK
+		}
+	}
+
+	/// Returns the offsets from `self.i` and the registers `x` through `y`
+	/// of the XO-CHIP `5xy2` and `5xy3`, walking down if `x > y`.
+	#[inline]
+	fn register_range(
+		x: usize,
+		y: usize,
+	) -> impl Iterator<Item = (u16, usize)> {
+		(0..=x.abs_diff(y)).map(move |offset| {
+			let register = if x <= y { x + offset } else { x - offset };
+			// At most 16 registers
+			#[allow(clippy::cast_possible_truncation)]
+			(offset as u16, register)
+		})
+	}
+
+	/// Moves `self.pc` past the next instruction, which is 4 bytes long if
+	/// it is the XO-CHIP `F000 nnnn`. Like every advance of `self.pc`, it
+	/// wraps around at the end of the 16-bit address space.
+	#[inline]
+	fn skip_next_instruction(&mut self, ram: &crate::ram::Ram) {
+		let next = self.pc.wrapping_add(2);
+		let is_long = self.xochip
+			&& ram.try_read(next) == Ok(0xF0)
+			&& ram.try_read(next.wrapping_add(1)) == Ok(0x00);
+		self.pc = self.pc.wrapping_add(if is_long { 6 } else { 4 });
 	}
 
 	/// Draws `length`-byte sprite starting at memory location `self.i` at `x`,
-	/// `y` using [`crate::.draw_byte`].
+	/// `y` using `Screen::draw_byte`. With both XO-CHIP planes selected, the
+	/// sprite of the second plane follows the one of the first.
 	#[cfg_attr(
 		feature = "tracing",
 		tracing::instrument(
@@ -333,19 +1274,771 @@ impl Cpu {
 		length: u8,
 		ram: &crate::ram::Ram,
 		screen: &mut crate::screen::Screen,
-	) {
+	) -> Result<(), CpuError> {
 		let mut should_set_vf = false;
+		// The starting coordinates always wrap, the rows past the bottom
+		// edge wrap or are clipped one by one
+		let x = usize::from(x) % screen.width();
+		let y = usize::from(y) % screen.height();
 
-		for sprite_i in 0..length {
-			let byte = ram.read(self.i + u16::from(sprite_i));
-			let is_erased =
-				screen.draw_byte(byte, x as usize, (y + sprite_i) as usize);
+		let mut address = self.i;
+		for plane in screen.selected_planes() {
+			for sprite_i in 0..length {
+				let byte = self.try_read(ram, address)?;
+				address = address.wrapping_add(1);
+				let Some(row) = screen.sprite_row(y + usize::from(sprite_i))
+				else {
+					continue;
+				};
+				let is_erased = screen.draw_byte(plane, byte, x, row);
 
-			if is_erased {
-				should_set_vf = true;
+				if is_erased {
+					should_set_vf = true;
+				}
 			}
 		}
 
-		self.v[0xF] = u8::from(should_set_vf);
+		self.set_draw_vf(screen, should_set_vf);
+		Ok(())
+	}
+
+	/// Draws the 16x16 sprite of `Dxy0`, 32 bytes starting at memory
+	/// location `self.i`, at `x`, `y` using `Screen::draw_wide_byte`. Like in
+	/// `self.draw_sprite`, every selected plane has its own 32 bytes.
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(
+			fields(
+				i = self.i,
+				pc = self.pc,
+			),
+			level = tracing::Level::TRACE,
+			skip(self, ram, screen),
+		),
+	)]
+	#[inline]
+	fn draw_wide_sprite(
+		&mut self,
+		x: u8,
+		y: u8,
+		ram: &crate::ram::Ram,
+		screen: &mut crate::screen::Screen,
+	) -> Result<(), CpuError> {
+		let mut should_set_vf = false;
+		let x = usize::from(x) % screen.width();
+		let y = usize::from(y) % screen.height();
+
+		let mut address = self.i;
+		for plane in screen.selected_planes() {
+			for row in 0..16 {
+				let high = self.try_read(ram, address)?;
+				let low = self.try_read(ram, address.wrapping_add(1))?;
+				address = address.wrapping_add(2);
+				let Some(row) = screen.sprite_row(y + row) else {
+					continue;
+				};
+				let is_erased = screen.draw_wide_byte(
+					plane,
+					u16::from_be_bytes([high, low]),
+					x,
+					row,
+				);
+
+				if is_erased {
+					should_set_vf = true;
+				}
+			}
+		}
+
+		self.set_draw_vf(screen, should_set_vf);
+		Ok(())
+	}
+
+	/// Sets vf to whether a sprite erased any pixel, unless the draws are
+	/// batched and `self.commit_draws` sets it later.
+	#[inline]
+	fn set_draw_vf(&mut self, screen: &crate::screen::Screen, erased: bool) {
+		if screen.draw_batch_quirk()
+			== crate::quirks::DrawBatchQuirk::Immediate
+		{
+			self.v[0xF] = u8::from(erased);
+		}
+	}
+
+	/// `Ram::try_read` that reports the error at `self.pc`.
+	#[inline]
+	fn try_read(
+		&self,
+		ram: &crate::ram::Ram,
+		address: u16,
+	) -> Result<u8, CpuError> {
+		ram.try_read(address)
+			.map_err(|source| CpuError::Ram { pc: self.pc, source })
+	}
+
+	/// `Ram::try_write` that reports the error at `self.pc`.
+	#[inline]
+	fn try_write(
+		&self,
+		ram: &mut crate::ram::Ram,
+		address: u16,
+		value: u8,
+	) -> Result<(), CpuError> {
+		ram.try_write(address, value)
+			.map_err(|source| CpuError::Ram { pc: self.pc, source })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{Cpu, CpuError, KeyWait},
+		crate::quirks::{MemoryQuirk, ShiftQuirk},
+	};
+
+	/// A `Cpu` with everything `Cpu::step` needs.
+	struct Machine {
+		cpu: Cpu,
+		ram: crate::ram::Ram,
+		timer: crate::timer::Timer,
+		screen: crate::screen::Screen,
+		keyboard: crate::keyboard::Keyboard,
+	}
+
+	impl Machine {
+		fn new() -> Self {
+			Self {
+				cpu: Cpu::new(),
+				ram: crate::ram::Ram::new(),
+				timer: crate::timer::Timer::new(),
+				screen: crate::screen::Screen::new(),
+				keyboard: crate::keyboard::Keyboard::new(),
+			}
+		}
+
+		/// Writes `opcode` at the program counter and runs it, ignoring the
+		/// clock.
+		fn step_once(&mut self, opcode: u16) -> Result<(), CpuError> {
+			let [high, low] = opcode.to_be_bytes();
+			self.ram.write(self.cpu.pc, high);
+			self.ram.write(self.cpu.pc + 1, low);
+			self.cpu.step(
+				&mut self.ram,
+				&mut self.timer,
+				&mut self.screen,
+				&mut self.keyboard,
+			)
+		}
+
+		/// Runs `opcode` with `vx` and `vy` in v1 and v2, returning v1 and
+		/// vf.
+		fn alu(&mut self, opcode: u16, vx: u8, vy: u8) -> (u8, u8) {
+			self.cpu.v[1] = vx;
+			self.cpu.v[2] = vy;
+			self.step_once(opcode).unwrap();
+			(self.cpu.v[1], self.cpu.v[0xF])
+		}
+	}
+
+	#[test]
+	fn add_sets_vf_on_carry() {
+		let mut machine = Machine::new();
+		assert_eq!(machine.alu(0x8124, 200, 100), (44, 1));
+		assert_eq!(machine.alu(0x8124, 10, 20), (30, 0));
+		assert_eq!(machine.alu(0x8124, 255, 1), (0, 1));
+	}
+
+	#[test]
+	fn sub_sets_vf_without_borrow() {
+		let mut machine = Machine::new();
+		assert_eq!(machine.alu(0x8125, 20, 10), (10, 1));
+		assert_eq!(machine.alu(0x8125, 10, 20), (246, 0));
+		// Equal operands do not borrow
+		assert_eq!(machine.alu(0x8125, 5, 5), (0, 1));
+	}
+
+	#[test]
+	fn subn_sets_vf_without_borrow() {
+		let mut machine = Machine::new();
+		assert_eq!(machine.alu(0x8127, 10, 20), (10, 1));
+		assert_eq!(machine.alu(0x8127, 20, 10), (246, 0));
+		assert_eq!(machine.alu(0x8127, 5, 5), (0, 1));
+	}
+
+	#[test]
+	fn flag_wins_over_result_in_vf() {
+		let mut machine = Machine::new();
+		machine.cpu.v[0xF] = 200;
+		machine.cpu.v[1] = 100;
+		machine.step_once(0x8F14).unwrap();
+		assert_eq!(machine.cpu.v[0xF], 1);
+
+		machine.cpu.v[0xF] = 0x81;
+		machine.step_once(0x8FF6).unwrap();
+		assert_eq!(machine.cpu.v[0xF], 1);
+	}
+
+	#[test]
+	fn logic_quirk_resets_vf_or_keeps_it() {
+		use crate::quirks::LogicQuirk;
+
+		for (quirk, vf) in [(LogicQuirk::KeepVf, 7), (LogicQuirk::ResetVf, 0)]
+		{
+			let mut machine = Machine::new();
+			machine.cpu.set_logic_quirk(quirk);
+			for opcode in [0x8121, 0x8122, 0x8123] {
+				machine.cpu.v[1] = 0b1100;
+				machine.cpu.v[2] = 0b1010;
+				machine.cpu.v[0xF] = 7;
+				machine.step_once(opcode).unwrap();
+				assert_eq!(machine.cpu.v[0xF], vf, "{quirk:?} {opcode:#06X}");
+			}
+			assert_eq!(machine.cpu.v[1], 0b0110);
+		}
+	}
+
+	#[test]
+	fn index_overflow_quirk_sets_vf_or_ignores_it() {
+		use crate::quirks::IndexOverflowQuirk;
+
+		for (quirk, vf) in
+			[(IndexOverflowQuirk::Ignore, 7), (IndexOverflowQuirk::SetVf, 1)]
+		{
+			let mut machine = Machine::new();
+			machine.cpu.set_index_overflow_quirk(quirk);
+			machine.cpu.i = 0xFFE;
+			machine.cpu.v[1] = 3;
+			machine.cpu.v[0xF] = 7;
+			machine.step_once(0xF11E).unwrap();
+			assert_eq!((machine.cpu.i, machine.cpu.v[0xF]), (0x001, vf));
+		}
+	}
+
+	#[test]
+	fn shifts_move_the_lost_bit_to_vf() {
+		let mut machine = Machine::new();
+		machine.cpu.set_shift_quirk(ShiftQuirk::ShiftVy);
+		assert_eq!(machine.alu(0x8126, 0, 0x81), (0x40, 1));
+		assert_eq!(machine.alu(0x8126, 0, 0x80), (0x40, 0));
+		assert_eq!(machine.alu(0x812E, 0, 0x81), (0x02, 1));
+		assert_eq!(machine.alu(0x812E, 0, 0x41), (0x82, 0));
+
+		machine.cpu.set_shift_quirk(ShiftQuirk::ShiftVx);
+		assert_eq!(machine.alu(0x8126, 0x03, 0xFF), (0x01, 1));
+		assert_eq!(machine.alu(0x812E, 0x03, 0xFF), (0x06, 0));
+	}
+
+	#[test]
+	fn bcd_writes_hundreds_tens_and_ones() {
+		let mut machine = Machine::new();
+		machine.cpu.i = 0x300;
+		for (value, digits) in
+			[(254, [2, 5, 4]), (7, [0, 0, 7]), (60, [0, 6, 0])]
+		{
+			machine.cpu.v[3] = value;
+			machine.step_once(0xF333).unwrap();
+			let written: Vec<_> =
+				(0x300..0x303).map(|a| machine.ram.read(a)).collect();
+			assert_eq!(written, digits);
+		}
+		assert_eq!(machine.cpu.i, 0x300);
+	}
+
+	#[test]
+	fn watchpoint_matches_address_access_and_value() {
+		use crate::ram::{Access, Watchpoint, WatchpointHit};
+
+		let mut machine = Machine::new();
+		machine.ram.toggle_watchpoint(Watchpoint {
+			address: 0x301,
+			access: Access::Write,
+			value: Some(5),
+		});
+		machine.cpu.i = 0x300;
+		machine.cpu.v[3] = 7;
+		machine.step_once(0xF333).unwrap();
+		assert_eq!(machine.ram.take_watchpoint_hit(), None);
+
+		machine.cpu.v[3] = 254;
+		machine.step_once(0xF333).unwrap();
+		assert_eq!(
+			machine.ram.take_watchpoint_hit(),
+			Some(WatchpointHit {
+				address: 0x301,
+				access: Access::Write,
+				value: 5,
+			})
+		);
+	}
+
+	#[test]
+	fn uninit_reads_are_recorded_once() {
+		let mut machine = Machine::new();
+		machine.ram.set_warn_uninit_reads(true);
+		for _ in 0..2 {
+			machine.cpu.i = 0x300;
+			machine.step_once(0xF065).unwrap();
+		}
+		assert_eq!(machine.ram.take_uninit_reads(), [0x300]);
+
+		machine.cpu.i = 0x302;
+		machine.step_once(0xF055).unwrap();
+		machine.cpu.i = 0x302;
+		machine.step_once(0xF065).unwrap();
+		assert_eq!(machine.ram.take_uninit_reads(), []);
+	}
+
+	#[test]
+	fn seventeen_nested_calls_overflow_the_stack() {
+		let mut machine = Machine::new();
+		for _ in 0..16 {
+			machine.step_once(0x2200).unwrap();
+		}
+		assert_eq!(
+			machine.step_once(0x2200),
+			Err(CpuError::StackOverflow { pc: 0x200 })
+		);
+		assert_eq!(machine.cpu.stack().len(), 16);
+	}
+
+	#[test]
+	fn return_without_a_call_underflows_the_stack() {
+		let mut machine = Machine::new();
+		assert_eq!(
+			machine.step_once(0x00EE),
+			Err(CpuError::StackUnderflow { pc: 0x200 })
+		);
+		assert_eq!(machine.cpu.pc, 0x200);
+	}
+
+	#[test]
+	fn full_size_rom_runs_at_the_last_address() {
+		let size = crate::consts::RAM_XOCHIP_SIZE;
+		let mut rom = vec![0; size - 0x200];
+		rom[size - 0x202..].copy_from_slice(&[0x30, 0x00]); // SE V0, #00
+		let mut machine = Machine::new();
+		machine.ram.set_size(size);
+		machine.ram.load_rom(&rom).unwrap();
+
+		// The address wraps around after 0xFFFF
+		machine.cpu.pc = 0xFFFE;
+		machine
+			.cpu
+			.step(
+				&mut machine.ram,
+				&mut machine.timer,
+				&mut machine.screen,
+				&mut machine.keyboard,
+			)
+			.unwrap();
+		assert_eq!(machine.cpu.pc, 0x0002);
+
+		machine.cpu.pc = 0xFFFE;
+		machine.step_once(0x6005).unwrap();
+		assert_eq!(machine.cpu.pc, 0x0000);
+
+		machine.cpu.pc = 0xFFFE;
+		machine.step_once(0x2300).unwrap();
+		assert_eq!((machine.cpu.pc, machine.cpu.stack()), (0x300, &[0][..]));
+	}
+
+	#[test]
+	fn bcd_near_end_of_memory_fails_cleanly() {
+		let mut machine = Machine::new();
+		machine.cpu.i = 0xFFF;
+		machine.cpu.v[3] = 254;
+		assert_eq!(
+			machine.step_once(0xF333),
+			Err(CpuError::Ram {
+				pc: 0x200,
+				source: crate::ram::RamError::OutOfBounds { address: 0x1001 },
+			})
+		);
+		assert_eq!(machine.ram.read(0xFFF), 0);
+		assert_eq!(machine.cpu.pc, 0x200);
+	}
+
+	#[test]
+	fn store_and_load_round_trip() {
+		for (quirk, i_after) in [
+			(MemoryQuirk::IncrementIndex, 0x304),
+			(MemoryQuirk::KeepIndex, 0x300),
+		] {
+			let mut machine = Machine::new();
+			machine.cpu.set_memory_quirk(quirk);
+			machine.cpu.v[..4].copy_from_slice(&[1, 2, 3, 4]);
+			machine.cpu.v[4] = 5;
+			machine.cpu.i = 0x300;
+			machine.step_once(0xF355).unwrap();
+			assert_eq!(machine.cpu.i, i_after, "{quirk:?}");
+			// Only v0 through vx are stored
+			assert_eq!(machine.ram.read(0x304), 0);
+
+			machine.cpu.v = [0; 16];
+			machine.cpu.i = 0x300;
+			machine.step_once(0xF365).unwrap();
+			assert_eq!(machine.cpu.v[..5], [1, 2, 3, 4, 0], "{quirk:?}");
+			assert_eq!(machine.cpu.i, i_after, "{quirk:?}");
+		}
+	}
+
+	#[test]
+	fn skips_compare_registers_and_keys() {
+		let mut machine = Machine::new();
+		machine.cpu.v[1] = 5;
+		machine.cpu.v[2] = 5;
+		machine.cpu.v[3] = 6;
+		machine.keyboard.press_key(5);
+		for (opcode, skips) in [
+			(0x3105, true),
+			(0x3106, false),
+			(0x4105, false),
+			(0x4106, true),
+			(0x5120, true),
+			(0x5130, false),
+			(0x9120, false),
+			(0x9130, true),
+			(0xE19E, true),
+			(0xE39E, false),
+			(0xE1A1, false),
+			(0xE3A1, true),
+		] {
+			machine.cpu.pc = 0x200;
+			machine.step_once(opcode).unwrap();
+			let expected = if skips { 0x204 } else { 0x202 };
+			assert_eq!(machine.cpu.pc, expected, "{opcode:#06X}");
+		}
+	}
+
+	#[test]
+	fn xochip_skips_over_long_index_load() {
+		let mut machine = Machine::new();
+		machine.cpu.set_xochip(true);
+		machine.ram.write(0x202, 0xF0);
+		machine.ram.write(0x203, 0x00);
+		machine.step_once(0x3000).unwrap();
+		assert_eq!(machine.cpu.pc, 0x206);
+	}
+
+	#[test]
+	fn xochip_saves_and_loads_register_ranges() {
+		let mut machine = Machine::new();
+		machine.cpu.set_xochip(true);
+		machine.cpu.i = 0x300;
+		machine.cpu.v[1..4].copy_from_slice(&[0x11, 0x22, 0x33]);
+		machine.step_once(0x5312).unwrap();
+		assert_eq!(
+			machine.ram.snapshot(0x300..0x303),
+			Ok(&[0x33, 0x22, 0x11][..])
+		);
+		machine.step_once(0x5573).unwrap();
+		assert_eq!(machine.cpu.v[5..8], [0x33, 0x22, 0x11]);
+		assert_eq!((machine.cpu.i, machine.cpu.pc), (0x300, 0x204));
+
+		machine.cpu.set_xochip(false);
+		assert!(machine.step_once(0x5012).is_err());
+	}
+
+	#[test]
+	fn sprite_rows_wrap_or_clip_at_the_bottom_edge() {
+		use crate::quirks::ClipQuirk;
+
+		let mut machine = Machine::new();
+		machine.ram.write(0x300, 0x80);
+		machine.ram.write(0x301, 0xC0);
+		machine.ram.write(0x302, 0xE0);
+		machine.cpu.i = 0x300;
+		machine.cpu.v[2] = 31;
+		let lit = |screen: &crate::screen::Screen, y: usize| {
+			screen.buffer()[y * 64..y * 64 + 4].to_vec()
+		};
+
+		machine.screen.set_clip_quirk(ClipQuirk::Clip);
+		machine.step_once(0xD123).unwrap();
+		assert_eq!(lit(&machine.screen, 31), [1, 0, 0, 0]);
+		assert_eq!(lit(&machine.screen, 0), [0; 4]);
+		assert_eq!(machine.cpu.v[0xF], 0);
+
+		// The second row lands on the top row and erases its pixel
+		machine.screen.clear();
+		machine.screen.set_clip_quirk(ClipQuirk::Wrap);
+		machine.screen.draw_byte(1, 0x40, 0, 0);
+		machine.step_once(0xD123).unwrap();
+		assert_eq!(lit(&machine.screen, 31), [1, 0, 0, 0]);
+		assert_eq!(lit(&machine.screen, 0), [1, 0, 0, 0]);
+		assert_eq!(lit(&machine.screen, 1), [1, 1, 1, 0]);
+		assert_eq!(machine.cpu.v[0xF], 1);
+	}
+
+	#[test]
+	fn sprite_columns_wrap_or_clip_at_the_right_edge() {
+		use crate::quirks::ClipQuirk;
+
+		let row = |screen: &crate::screen::Screen| {
+			let buffer = screen.buffer();
+			[&buffer[..4], &buffer[60..64]].concat()
+		};
+		for (quirk, lit, vf) in [
+			(ClipQuirk::Clip, [0, 1, 0, 0, 1, 1, 1, 1], 0),
+			(ClipQuirk::Wrap, [1, 0, 1, 1, 1, 1, 1, 1], 1),
+		] {
+			let mut machine = Machine::new();
+			machine.ram.write(0x300, 0xFF);
+			machine.cpu.i = 0x300;
+			machine.cpu.v[1] = 60;
+			machine.screen.set_clip_quirk(quirk);
+			// Only the wrapped columns reach this pixel and erase it
+			machine.screen.draw_byte(1, 0x40, 0, 0);
+			machine.step_once(0xD121).unwrap();
+			assert_eq!(row(&machine.screen), lit, "{quirk:?}");
+			assert_eq!(machine.cpu.v[0xF], vf, "{quirk:?}");
+		}
+	}
+
+	#[test]
+	fn batched_draws_collide_at_the_end_of_the_frame() {
+		use crate::quirks::DrawBatchQuirk;
+
+		let mut machine = Machine::new();
+		machine.screen.set_draw_batch_quirk(DrawBatchQuirk::Frame);
+		machine.ram.write(0x300, 0xF0);
+		machine.cpu.i = 0x300;
+		let lit =
+			|screen: &crate::screen::Screen| screen.buffer()[..4].to_vec();
+
+		// Drawn and erased within the frame, so it never shows or collides
+		machine.step_once(0xD001).unwrap();
+		machine.step_once(0xD001).unwrap();
+		machine.cpu.v[0xF] = 7;
+		machine.cpu.commit_draws(&mut machine.screen);
+		assert_eq!(lit(&machine.screen), [0; 4]);
+		assert_eq!(machine.cpu.v[0xF], 0);
+
+		machine.step_once(0xD001).unwrap();
+		assert_eq!(lit(&machine.screen), [0; 4]);
+		machine.cpu.commit_draws(&mut machine.screen);
+		assert_eq!(lit(&machine.screen), [1; 4]);
+		assert_eq!(machine.cpu.v[0xF], 0);
+
+		// Erasing the shown sprite collides, and a clear lands after it
+		machine.step_once(0xD001).unwrap();
+		machine.step_once(0x00E0).unwrap();
+		assert_eq!(machine.cpu.v[0xF], 1);
+		machine.step_once(0xD001).unwrap();
+		machine.cpu.commit_draws(&mut machine.screen);
+		assert_eq!(lit(&machine.screen), [1; 4]);
+		assert_eq!(machine.cpu.v[0xF], 0);
+
+		// `01E0` is a clear as well
+		machine.step_once(0xD001).unwrap();
+		machine.step_once(0x01E0).unwrap();
+		assert_eq!(machine.cpu.v[0xF], 1);
+		assert_eq!(lit(&machine.screen), [0; 4]);
+	}
+
+	#[test]
+	fn initial_registers_are_zero_or_seeded() {
+		use super::InitialRegisters;
+
+		let mut cpu = Cpu::new();
+		cpu.v[3] = 7;
+		cpu.set_initial_registers(InitialRegisters::Zero);
+		assert_eq!(cpu.v, [0; 16]);
+
+		cpu.set_initial_registers(InitialRegisters::Random(42));
+		let seeded = cpu.v;
+		assert_eq!(seeded, [
+			0xA2, 0x24, 0x27, 0x22, 0x63, 0x77, 0xCC, 0x86, 0x7D, 0x51, 0xAD,
+			0x3F, 0x13, 0x0A, 0xF0, 0x8A
+		]);
+		cpu.v[3] = seeded[3].wrapping_add(1);
+		cpu.reset();
+		assert_eq!(cpu.v, seeded);
+
+		let mut other = Cpu::new();
+		other.set_initial_registers(InitialRegisters::Random(42));
+		assert_eq!(other.v, seeded);
+		other.set_initial_registers(InitialRegisters::Random(43));
+		assert_ne!(other.v, seeded);
+	}
+
+	#[test]
+	fn key_wait_blocks_until_a_fresh_press_is_released() {
+		let mut machine = Machine::new();
+		machine.keyboard.press_key(0x7);
+		assert_eq!(machine.cpu.key_wait, KeyWait::Idle);
+		for _ in 0..3 {
+			machine.step_once(0xF20A).unwrap();
+			assert_eq!(machine.cpu.pc, 0x200);
+			assert_eq!(machine.cpu.key_wait, KeyWait::Press);
+		}
+
+		machine.keyboard.press_key(0xA);
+		machine.step_once(0xF20A).unwrap();
+		assert_eq!(machine.cpu.pc, 0x200);
+		assert_eq!(machine.cpu.key_wait, KeyWait::Release(0xA));
+
+		machine.keyboard.release_key(0xA);
+		machine.step_once(0xF20A).unwrap();
+		assert_eq!((machine.cpu.pc, machine.cpu.v[2]), (0x202, 0xA));
+		assert_eq!(machine.cpu.key_wait, KeyWait::Idle);
+	}
+
+	#[test]
+	fn key_wait_takes_the_lowest_of_simultaneous_keys() {
+		let mut machine = Machine::new();
+		#[cfg(feature = "window")]
+		assert_eq!(
+			machine
+				.keyboard
+				.select_key_code(&[minifb::Key::W, minifb::Key::Key3]),
+			Some(0x3)
+		);
+
+		machine.step_once(0xF10A).unwrap();
+		machine.keyboard.press_key(0x5);
+		machine.keyboard.press_key(0x3);
+		machine.step_once(0xF10A).unwrap();
+		machine.keyboard.set_state(0);
+		machine.step_once(0xF10A).unwrap();
+		assert_eq!((machine.cpu.pc, machine.cpu.v[1]), (0x202, 0x3));
+	}
+
+	#[test]
+	fn clear_waits_for_the_next_frame() {
+		use crate::quirks::DisplayWaitQuirk;
+
+		let mut machine = Machine::new();
+		machine.cpu.set_display_wait_quirk(DisplayWaitQuirk::Draw);
+		machine.cpu.set_vblank_follows_frames(true);
+		machine.step_once(0x00E0).unwrap();
+		assert_eq!(machine.cpu.pc, 0x202);
+
+		machine.cpu.set_clear_waits(true);
+		machine.screen.draw_byte(1, 0x80, 0, 0);
+		for _ in 0..3 {
+			machine.step_once(0x00E0).unwrap();
+			assert_eq!(machine.cpu.pc, 0x202);
+			assert!(machine.cpu.is_waiting_for_vblank());
+		}
+		assert_eq!(machine.screen.buffer()[0], 1);
+
+		machine.cpu.end_frame(&mut machine.screen);
+		machine.step_once(0x00E0).unwrap();
+		assert_eq!(machine.cpu.pc, 0x204);
+		assert_eq!(machine.screen.buffer()[0], 0);
+	}
+
+	#[test]
+	fn jump_to_itself_ends_the_rom() {
+		let mut machine = Machine::new();
+		machine.step_once(0x1300).unwrap();
+		assert!(!machine.cpu.has_ended());
+
+		machine.cpu.pc = 0x200;
+		machine.step_once(0x1200).unwrap();
+		assert!(machine.cpu.has_ended());
+		machine.cpu.reset();
+		assert!(!machine.cpu.has_ended());
+
+		// A jump anywhere else, e.g. back to a key check, keeps running
+		machine.step_once(0x1202).unwrap();
+		assert!(!machine.cpu.has_ended());
+
+		machine.cpu.pc = 0x200;
+		machine.cpu.v[0] = 2;
+		machine.step_once(0xB1FE).unwrap();
+		assert!(machine.cpu.has_ended());
+	}
+
+	#[test]
+	fn resolution_switches_match_the_whole_word() {
+		let mut machine = Machine::new();
+		machine.step_once(0x02FF).unwrap();
+		assert!(!machine.screen.is_hires());
+		machine.step_once(0x00FF).unwrap();
+		assert!(machine.screen.is_hires());
+		machine.step_once(0x01FE).unwrap();
+		assert!(machine.screen.is_hires());
+		machine.step_once(0x00FE).unwrap();
+		assert!(!machine.screen.is_hires());
+		assert_eq!(machine.cpu.pc, 0x208);
+	}
+
+	#[test]
+	fn scrolls_match_the_whole_word() {
+		let lit = |machine: &Machine| {
+			let buffer = machine.screen.buffer();
+			(0..buffer.len()).filter(|&i| buffer[i] != 0).collect::<Vec<_>>()
+		};
+		let mut machine = Machine::new();
+		machine.step_once(0x00FF).unwrap();
+		machine.ram.write(0x300, 0x80);
+		machine.cpu.i = 0x300;
+		machine.step_once(0xD011).unwrap();
+		assert_eq!(lit(&machine), [0]);
+
+		machine.step_once(0x00C2).unwrap();
+		assert_eq!(lit(&machine), [2 * 128]);
+		machine.step_once(0x00FB).unwrap();
+		assert_eq!(lit(&machine), [2 * 128 + 4]);
+		machine.step_once(0x00FC).unwrap();
+		assert_eq!(lit(&machine), [2 * 128]);
+
+		// SYS, ignored
+		for opcode in [0x01C5, 0x03FB, 0x02FC] {
+			machine.step_once(opcode).unwrap();
+			assert_eq!(lit(&machine), [2 * 128]);
+		}
+		assert_eq!(machine.cpu.pc, 0x210);
+	}
+
+	#[test]
+	fn digit_addresses_resolve_to_their_glyphs() {
+		let mut machine = Machine::new();
+		machine.ram.load_digit_sprites();
+		for digit in 0..16 {
+			machine.cpu.v[1] = digit;
+			machine.step_once(0xF129).unwrap();
+			let i = machine.cpu.i;
+			assert_eq!(
+				machine.ram.snapshot(i..i + 5).unwrap(),
+				crate::consts::RAM_DIGIT_SPRITES[usize::from(digit)]
+			);
+			machine.step_once(0xF130).unwrap();
+			let i = machine.cpu.i;
+			assert_eq!(
+				machine.ram.snapshot(i..i + 10).unwrap(),
+				crate::consts::RAM_BIG_DIGIT_SPRITES[usize::from(digit)]
+			);
+		}
+	}
+
+	#[test]
+	fn rpl_flags_save_and_restore_round_trip() {
+		let mut machine = Machine::new();
+		machine.cpu.v[..4].copy_from_slice(&[1, 2, 3, 4]);
+		machine.step_once(0xF275).unwrap();
+		assert_eq!(machine.cpu.rpl_flags(), [1, 2, 3, 0, 0, 0, 0, 0]);
+
+		machine.cpu.v = [0xAA; 16];
+		machine.step_once(0xF785).unwrap();
+		assert_eq!(machine.cpu.v[..9], [1, 2, 3, 0, 0, 0, 0, 0, 0xAA]);
+
+		// There are only 8 flags
+		assert_eq!(
+			machine.step_once(0xF875),
+			Err(CpuError::UnknownInstruction { pc: 0x204, opcode: 0xF875 })
+		);
+	}
+
+	#[test]
+	fn unknown_instruction_keeps_pc() {
+		let mut machine = Machine::new();
+		assert_eq!(
+			machine.step_once(0x5121),
+			Err(CpuError::UnknownInstruction { pc: 0x200, opcode: 0x5121 })
+		);
+		assert_eq!(machine.cpu.pc, 0x200);
 	}
 }
@@ -0,0 +1,273 @@
+//! A small gdb-like debugger driven by text commands, for frontends that
+//! read them from stdin. Addresses and lengths are hex, like in the
+//! disassembly:
+//!
+//! * `s` - Run one instruction
+//! * `c` - Continue until a breakpoint or a watchpoint
+//! * `b <addr>` - Set or remove a breakpoint
+//! * `w <addr> [r|w] [value]` - Set or remove a watchpoint on writes (the
+//!   default) or reads of the address, optionally only of the value
+//! * `r` - Dump the registers, the top of the stack and the timers
+//! * `m <addr> <len>` - Dump memory
+//! * `d` - Disassemble around the program counter
+
+/// Instructions `Command::Disassemble` shows before and after the program
+/// counter.
+const DISASSEMBLY_CONTEXT: u16 = 4;
+/// Bytes per line of `Command::Memory`.
+const MEMORY_DUMP_WIDTH: usize = 16;
+
+/// A parsed debugger command, see the module documentation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+	Step,
+	Continue,
+	Break(u16),
+	Watch(crate::ram::Watchpoint),
+	Registers,
+	Memory { address: u16, length: u16 },
+	Disassemble,
+}
+
+impl std::str::FromStr for Command {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		use std::convert::TryFrom as _;
+
+		let mut words = s.split_whitespace();
+		let name = words.next().unwrap_or_default();
+
+		let command = match name {
+			"s" => Self::Step,
+			"c" => Self::Continue,
+			"b" => Self::Break(parse_hex(words.next(), "an address")?),
+			"w" => {
+				let address = parse_hex(words.next(), "an address")?;
+				let access = match words.next() {
+					None | Some("w") => crate::ram::Access::Write,
+					Some("r") => crate::ram::Access::Read,
+					Some(word) => anyhow::bail!(
+						"Invalid access: {word}. Expected r or w."
+					),
+				};
+				let value = match words.next() {
+					Some(word) => {
+						let value = parse_hex(Some(word), "a value")?;
+						Some(u8::try_from(value).map_err(|_| {
+							anyhow::anyhow!("Invalid value: {word}.")
+						})?)
+					}
+					None => None,
+				};
+				Self::Watch(crate::ram::Watchpoint { address, access, value })
+			}
+			"r" => Self::Registers,
+			"m" => Self::Memory {
+				address: parse_hex(words.next(), "an address")?,
+				length: parse_hex(words.next(), "a length")?,
+			},
+			"d" => Self::Disassemble,
+			_ => anyhow::bail!(
+				"Unknown command: {s:?}. Expected s, c, b <addr>, \
+				 w <addr> [r|w] [value], r, m <addr> <len> or d."
+			),
+		};
+		anyhow::ensure!(words.next().is_none(), "Too many arguments: {s:?}.");
+		Ok(command)
+	}
+}
+
+/// Parses a hex number with an optional `0x` or `#` prefix. `what` names it
+/// in the errors.
+fn parse_hex(word: Option<&str>, what: &str) -> anyhow::Result<u16> {
+	let word = word.ok_or_else(|| anyhow::anyhow!("Expected {what}."))?;
+	let hex = word
+		.strip_prefix("0x")
+		.or_else(|| word.strip_prefix('#'))
+		.unwrap_or(word);
+	u16::from_str_radix(hex, 16)
+		.map_err(|_| anyhow::anyhow!("Invalid {what}: {word}."))
+}
+
+/// Breakpoints plus the commands that work with them. Execution itself is
+/// paused and resumed with `Emulator::set_paused`, so the frontend keeps
+/// running the `Emulator` as usual while it is not paused.
+#[derive(Debug, Default)]
+pub struct Debugger {
+	breakpoints: std::collections::BTreeSet<u16>,
+}
+
+impl Debugger {
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the breakpoint addresses in ascending order.
+	#[inline]
+	pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+		self.breakpoints.iter().copied()
+	}
+
+	/// Runs the `command` on the `emulator` and returns the text to show.
+	///
+	/// # Errors
+	///
+	/// If an instruction run by `Command::Step` or `Command::Continue`
+	/// fails.
+	pub fn execute(
+		&mut self,
+		emulator: &mut crate::emulator::Emulator,
+		command: Command,
+	) -> Result<String, crate::cpu::CpuError> {
+		Ok(match command {
+			Command::Step => {
+				emulator.set_paused(true);
+				emulator.step()?;
+				let disassembly = self.disassemble(emulator, 0);
+				match emulator.take_watchpoint_hit() {
+					Some(hit) => {
+						format!("{}\n{disassembly}", describe_hit(hit))
+					}
+					None => disassembly,
+				}
+			}
+			Command::Continue => {
+				// Leave the breakpoint we are stopped at first
+				if emulator.is_paused() {
+					emulator.step()?;
+				}
+				emulator.set_paused(false);
+				"Continuing.".to_owned()
+			}
+			Command::Break(address) => {
+				if self.breakpoints.insert(address) {
+					format!("Breakpoint at #{address:04X}.")
+				} else {
+					self.breakpoints.remove(&address);
+					format!("Removed the breakpoint at #{address:04X}.")
+				}
+			}
+			Command::Watch(watchpoint) => {
+				let description = describe_watchpoint(watchpoint);
+				if emulator.toggle_watchpoint(watchpoint) {
+					format!("Watchpoint on {description}.")
+				} else {
+					format!("Removed the watchpoint on {description}.")
+				}
+			}
+			Command::Registers => emulator.overlay_text(),
+			Command::Memory { address, length } => {
+				Self::dump_memory(emulator.ram(), address, length)
+			}
+			Command::Disassemble => {
+				self.disassemble(emulator, DISASSEMBLY_CONTEXT)
+			}
+		})
+	}
+
+	/// Pauses the `emulator` if its program counter is at a breakpoint or the
+	/// previous instruction hit a watchpoint, so call it before running every
+	/// instruction. Returns the text to show then.
+	#[inline]
+	pub fn check_breakpoint(
+		&self,
+		emulator: &mut crate::emulator::Emulator,
+	) -> Option<String> {
+		if let Some(hit) = emulator.take_watchpoint_hit() {
+			emulator.set_paused(true);
+			return Some(format!(
+				"Stopped at the watchpoint. {}\n{}",
+				describe_hit(hit),
+				self.disassemble(emulator, 0)
+			));
+		}
+
+		let pc = emulator.cpu().pc();
+		if emulator.is_paused() || !self.breakpoints.contains(&pc) {
+			return None;
+		}
+		emulator.set_paused(true);
+		Some(format!(
+			"Stopped at the breakpoint at #{pc:04X}.\n{}",
+			self.disassemble(emulator, 0)
+		))
+	}
+
+	/// Disassembles `context` instructions before and after the program
+	/// counter, marking the breakpoints with `*` and the program counter
+	/// with `>`.
+	fn disassemble(
+		&self,
+		emulator: &crate::emulator::Emulator,
+		context: u16,
+	) -> String {
+		let pc = emulator.cpu().pc();
+		let start = pc.saturating_sub(context * 2);
+		let end = pc.saturating_add(context * 2 + 2);
+
+		let mut lines = vec![];
+		for address in (start..end).step_by(2) {
+			let Ok(&[high, low]) =
+				emulator.ram().snapshot(address..address.saturating_add(2))
+			else {
+				break;
+			};
+			let opcode = u16::from_be_bytes([high, low]);
+			let marker = if address == pc { '>' } else { ' ' };
+			let breakpoint =
+				if self.breakpoints.contains(&address) { '*' } else { ' ' };
+			lines.push(format!(
+				"{marker}{breakpoint} {address:04X}: {opcode:04X}  {}",
+				crate::disasm::disassemble_instruction(opcode)
+			));
+		}
+		lines.join("\n")
+	}
+
+	/// Dumps `length` bytes of memory from `address`, `MEMORY_DUMP_WIDTH`
+	/// per line.
+	fn dump_memory(
+		ram: &crate::ram::Ram,
+		address: u16,
+		length: u16,
+	) -> String {
+		let bytes = match ram.snapshot(address..address.saturating_add(length))
+		{
+			Ok(bytes) => bytes,
+			Err(e) => return e.to_string(),
+		};
+		let lines: Vec<_> = (usize::from(address)..)
+			.step_by(MEMORY_DUMP_WIDTH)
+			.zip(bytes.chunks(MEMORY_DUMP_WIDTH))
+			.map(|(line_address, line)| {
+				let hex: Vec<_> =
+					line.iter().map(|byte| format!("{byte:02X}")).collect();
+				format!("{line_address:04X}: {}", hex.join(" "))
+			})
+			.collect();
+		lines.join("\n")
+	}
+}
+
+/// Describes the `watchpoint` like `writes of #05 to #0300`.
+fn describe_watchpoint(watchpoint: crate::ram::Watchpoint) -> String {
+	let crate::ram::Watchpoint { address, access, value } = watchpoint;
+	let value = value.map(|value| format!(" of #{value:02X}"));
+	let preposition = match access {
+		crate::ram::Access::Read => "from",
+		crate::ram::Access::Write => "to",
+	};
+	format!(
+		"{access}s{} {preposition} #{address:04X}",
+		value.unwrap_or_default()
+	)
+}
+
+/// Describes the watchpoint hit by the instruction at `pc`.
+fn describe_hit((pc, hit): (u16, crate::ram::WatchpointHit)) -> String {
+	let crate::ram::WatchpointHit { address, access, value } = hit;
+	format!("#{pc:04X}: {access} of #{value:02X} at #{address:04X}.")
+}
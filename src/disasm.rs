@@ -0,0 +1,148 @@
+//! Turns opcodes into mnemonics in the style of Cowgod's Chip-8 technical
+//! reference. The decoding mirrors `Cpu::step`, so everything it rejects as
+//! unknown is rendered as a `DW` data word.
+
+/// Disassembles a single `opcode`, e.g. `0xD015` into `DRW V0, V1, 5`.
+#[must_use]
+pub fn disassemble_instruction(opcode: u16) -> String {
+	let nnn = opcode & 0x0FFF;
+	let nn = (opcode & 0x00FF) as u8;
+	let n = (opcode & 0x000F) as u8;
+	let x = (opcode & 0x0F00) >> 8;
+	let y = (opcode & 0x00F0) >> 4;
+
+	match ((opcode & 0xF000) >> 12, nn, n) {
+		(0x0, 0xE0, _) => "CLS".to_owned(),
+		(0x0, 0xEE, _) => "RET".to_owned(),
+		(0x0, _, _) if opcode & 0xFFF0 == 0x00C0 => format!("SCD {n}"),
+		(0x0, 0xFB, _) if x == 0 => "SCR".to_owned(),
+		(0x0, 0xFC, _) if x == 0 => "SCL".to_owned(),
+		(0x0, 0xFE, _) if x == 0 => "LOW".to_owned(),
+		(0x0, 0xFF, _) if x == 0 => "HIGH".to_owned(),
+		(0x0, _, _) => format!("SYS #{nnn:03X}"),
+		(0x1, _, _) => format!("JP #{nnn:03X}"),
+		(0x2, _, _) => format!("CALL #{nnn:03X}"),
+		(0x3, _, _) => format!("SE V{x:X}, #{nn:02X}"),
+		(0x4, _, _) => format!("SNE V{x:X}, #{nn:02X}"),
+		(0x5, _, 0x0) => format!("SE V{x:X}, V{y:X}"),
+		(0x5, _, 0x2) => format!("SAVE V{x:X}, V{y:X}"),
+		(0x5, _, 0x3) => format!("LOAD V{x:X}, V{y:X}"),
+		(0x6, _, _) => format!("LD V{x:X}, #{nn:02X}"),
+		(0x7, _, _) => format!("ADD V{x:X}, #{nn:02X}"),
+		(0x8, _, 0x0) => format!("LD V{x:X}, V{y:X}"),
+		(0x8, _, 0x1) => format!("OR V{x:X}, V{y:X}"),
+		(0x8, _, 0x2) => format!("AND V{x:X}, V{y:X}"),
+		(0x8, _, 0x3) => format!("XOR V{x:X}, V{y:X}"),
+		(0x8, _, 0x4) => format!("ADD V{x:X}, V{y:X}"),
+		(0x8, _, 0x5) => format!("SUB V{x:X}, V{y:X}"),
+		(0x8, _, 0x6) => format!("SHR V{x:X}, V{y:X}"),
+		(0x8, _, 0x7) => format!("SUBN V{x:X}, V{y:X}"),
+		(0x8, _, 0xE) => format!("SHL V{x:X}, V{y:X}"),
+		(0x9, _, 0x0) => format!("SNE V{x:X}, V{y:X}"),
+		(0xA, _, _) => format!("LD I, #{nnn:03X}"),
+		(0xB, _, _) => format!("JP V0, #{nnn:03X}"),
+		(0xC, _, _) => format!("RND V{x:X}, #{nn:02X}"),
+		(0xD, _, _) => format!("DRW V{x:X}, V{y:X}, {n}"),
+		(0xE, 0x9E, _) => format!("SKP V{x:X}"),
+		(0xE, 0xA1, _) => format!("SKNP V{x:X}"),
+		(0xF, 0x00, _) if x == 0 => "LD I, LONG".to_owned(),
+		(0xF, 0x01, _) => format!("PLANE {x}"),
+		(0xF, 0x02, _) if x == 0 => "AUDIO".to_owned(),
+		(0xF, 0x07, _) => format!("LD V{x:X}, DT"),
+		(0xF, 0x0A, _) => format!("LD V{x:X}, K"),
+		(0xF, 0x15, _) => format!("LD DT, V{x:X}"),
+		(0xF, 0x18, _) => format!("LD ST, V{x:X}"),
+		(0xF, 0x1E, _) => format!("ADD I, V{x:X}"),
+		(0xF, 0x29, _) => format!("LD F, V{x:X}"),
+		(0xF, 0x30, _) => format!("LD HF, V{x:X}"),
+		(0xF, 0x33, _) => format!("LD B, V{x:X}"),
+		(0xF, 0x3A, _) => format!("PITCH V{x:X}"),
+		(0xF, 0x55, _) => format!("LD [I], V{x:X}"),
+		(0xF, 0x65, _) => format!("LD V{x:X}, [I]"),
+		(0xF, 0x75, _) if x < 8 => format!("LD R, V{x:X}"),
+		(0xF, 0x85, _) if x < 8 => format!("LD V{x:X}, R"),
+		_ => format!("DW #{opcode:04X}"),
+	}
+}
+
+/// Returns just the mnemonic of `opcode`, e.g. `DRW` for `0xD015`, without
+/// formatting the operands. Unknown opcodes are `DW`.
+#[must_use]
+pub const fn mnemonic(opcode: u16) -> &'static str {
+	let nn = (opcode & 0x00FF) as u8;
+	let n = (opcode & 0x000F) as u8;
+	let x = (opcode & 0x0F00) >> 8;
+
+	match ((opcode & 0xF000) >> 12, nn, n) {
+		(0x0, 0xE0, _) => "CLS",
+		(0x0, 0xEE, _) => "RET",
+		(0x0, _, _) if opcode & 0xFFF0 == 0x00C0 => "SCD",
+		(0x0, 0xFB, _) if x == 0 => "SCR",
+		(0x0, 0xFC, _) if x == 0 => "SCL",
+		(0x0, 0xFE, _) if x == 0 => "LOW",
+		(0x0, 0xFF, _) if x == 0 => "HIGH",
+		(0x0, _, _) => "SYS",
+		(0x1 | 0xB, _, _) => "JP",
+		(0x2, _, _) => "CALL",
+		(0x3, _, _) | (0x5, _, 0x0) => "SE",
+		(0x5, _, 0x2) => "SAVE",
+		(0x5, _, 0x3) => "LOAD",
+		(0x4, _, _) | (0x9, _, 0x0) => "SNE",
+		(0x6 | 0xA, _, _)
+		| (0x8, _, 0x0)
+		| (
+			0xF,
+			0x07 | 0x0A | 0x15 | 0x18 | 0x29 | 0x30 | 0x33 | 0x55 | 0x65,
+			_,
+		) => "LD",
+		(0xF, 0x75 | 0x85, _) if x < 8 => "LD",
+		(0xF, 0x00, _) if x == 0 => "LD",
+		(0x7, _, _) | (0x8, _, 0x4) | (0xF, 0x1E, _) => "ADD",
+		(0x8, _, 0x1) => "OR",
+		(0x8, _, 0x2) => "AND",
+		(0x8, _, 0x3) => "XOR",
+		(0x8, _, 0x5) => "SUB",
+		(0x8, _, 0x6) => "SHR",
+		(0x8, _, 0x7) => "SUBN",
+		(0x8, _, 0xE) => "SHL",
+		(0xC, _, _) => "RND",
+		(0xD, _, _) => "DRW",
+		(0xE, 0x9E, _) => "SKP",
+		(0xE, 0xA1, _) => "SKNP",
+		(0xF, 0x01, _) => "PLANE",
+		(0xF, 0x02, _) if x == 0 => "AUDIO",
+		(0xF, 0x3A, _) => "PITCH",
+		_ => "DW",
+	}
+}
+
+/// Disassembles the ROM `data` as if it were loaded at
+/// `consts::RAM_ROM_START_ADDRESS`, returning address, opcode and text of
+/// every word. The XO-CHIP `F000 nnnn` takes the following word as its
+/// address, rendered as `LD I, LONG #nnnn` like `asm` expects it. A trailing
+/// odd byte is rendered as a `DB` data byte.
+#[must_use]
+pub fn disassemble_rom(data: &[u8]) -> Vec<(u16, u16, String)> {
+	let mut address = crate::consts::RAM_ROM_START_ADDRESS;
+	let mut listing = Vec::with_capacity(data.len().div_ceil(2));
+
+	let mut words = data.chunks_exact(2);
+	while let Some(word) = words.next() {
+		let opcode = u16::from_be_bytes([word[0], word[1]]);
+		if opcode == 0xF000 {
+			if let Some(&[high, low]) = words.next() {
+				let nnnn = u16::from_be_bytes([high, low]);
+				let text = format!("LD I, LONG #{nnnn:04X}");
+				listing.push((address, opcode, text));
+				address = address.wrapping_add(4);
+				continue;
+			}
+		}
+		listing.push((address, opcode, disassemble_instruction(opcode)));
+		address = address.wrapping_add(2);
+	}
+	if let [byte] = words.remainder() {
+		listing.push((address, u16::from(*byte), format!("DB #{byte:02X}")));
+	}
+	listing
+}
@@ -1,4 +1,60 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+
+/// Snapshot of the whole machine, see `Emulator::save_state`.
+///
+/// With the `serde` feature it can be serialized, e.g. to save a game to a
+/// file. Timing, the loaded ROM and the configuration are not part of it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmulatorState {
+	cpu: crate::cpu::CpuState,
+	memory: Vec<u8>,
+	screen: crate::screen::ScreenState,
+	timer: crate::timer::TimerState,
+}
+
+/// What the machine looked like when an instruction failed, see
+/// `Emulator::crash_dump`. With the `serde` feature it can be written to a
+/// file for a bug report.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CrashDump {
+	/// The message of the `CpuError`.
+	pub error: String,
+	pub pc: u16,
+	/// The word at `pc`, if it is inside the memory.
+	pub opcode: Option<u16>,
+	pub registers: [u8; 16],
+	pub index: u16,
+	/// The return addresses, the innermost call last.
+	pub stack: Vec<u16>,
+	/// The address of the first byte of `memory`.
+	pub memory_address: u16,
+	/// Up to `consts::CRASH_DUMP_MEMORY_CONTEXT` bytes before and after the
+	/// failed instruction.
+	pub memory: Vec<u8>,
+}
+
+/// What `Emulator::tick` did.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TickResult {
+	/// Ran an instruction, or a frame in the `TimerMode::Frames` mode.
+	Ran,
+	/// A frame is due and was counted as displayed: draw the `Screen` now,
+	/// e.g. with `Emulator::draw`.
+	Displayed,
+	/// The `Cpu` failed and stays at the failed instruction.
+	Halted(crate::cpu::CpuError),
+	/// Ran, and the buzzer turned on (`true`) or off (`false`).
+	Beep(bool),
+	/// Ran, and `Fx0A` waits for a key.
+	WaitingForKey,
+	/// Ran, and the ROM ended by jumping to itself, see `Cpu::has_ended`.
+	/// Only the timers keep running until a reset.
+	Ended,
+	/// Nothing is due, see `Emulator::time_until_next_event`.
+	Idle,
+}
 
 /// The `Emulator` is an assembler and initializer for all important
 /// components: `Cpu`, `Ram`, `Timer`, `Screen`, `Keyboard`.
@@ -8,26 +64,606 @@ pub struct Emulator {
 	timer: crate::timer::Timer,
 	screen: crate::screen::Screen,
 	keyboard: crate::keyboard::Keyboard,
+	rom: Vec<u8>,
+	overlay_visible: bool,
+	font: Option<[u8; crate::consts::RAM_FONT_SIZE]>,
+	big_font: Option<[u8; crate::consts::RAM_BIG_FONT_SIZE]>,
+	paused: bool,
+	turbo: bool,
+	turbo_multiplier: std::num::NonZeroU32,
+	/// The `Config::clock_hz` that `self.reset_speed` returns to.
+	base_clock_hz: std::num::NonZeroU32,
+	timer_mode: crate::timer::TimerMode,
+	/// Instructions run since the `Timer` last ticked, in the
+	/// `TimerMode::Cycles` mode.
+	frame_cycles: u32,
+	/// Wall-clock time not yet ticked off the `Timer` and when it was last
+	/// added, in the `TimerMode::WallClock` mode.
+	timer_elapsed: std::time::Duration,
+	timer_updated_at: crate::time::Instant,
+	/// See `self.instructions_per_frame`.
+	instructions_per_frame: Option<std::num::NonZeroU32>,
+	/// When `self.run_frame` is due next, in the `TimerMode::Frames` mode.
+	next_frame_at: crate::time::Instant,
+	rewind: crate::rewind::RewindBuffer,
+	/// The last watchpoint hit with the address of the instruction that
+	/// caused it.
+	watchpoint_hit: Option<(u16, crate::ram::WatchpointHit)>,
+	exec_trace: Option<crate::exec_trace::ExecTrace>,
+	/// Why the `ExecTrace` stopped logging, see
+	/// `self.take_exec_trace_error`.
+	exec_trace_error: Option<std::io::Error>,
+	#[cfg(feature = "audio")]
+	audio: Option<crate::audio::Audio>,
+}
+
+impl Default for Emulator {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl Emulator {
 	#[must_use]
 	pub fn new() -> Self {
+		Self::with_config(&crate::config::Config::default())
+	}
+
+	/// Creates an emulator with the speed, quirks, window size, colors and
+	/// key bindings of the `config`.
+	///
+	/// # Panics
+	///
+	/// If `config.validate` fails or a key binding has a code outside of
+	/// `0x0..=0xF`.
+	#[must_use]
+	pub fn with_config(config: &crate::config::Config) -> Self {
 		let mut ram = crate::ram::Ram::new();
 		ram.load_digit_sprites();
 
-		Self {
+		let mut emulator = Self {
 			cpu: crate::cpu::Cpu::new(),
 			ram,
 			timer: crate::timer::Timer::new(),
 			screen: crate::screen::Screen::new(),
 			keyboard: crate::keyboard::Keyboard::new(),
+			rom: vec![],
+			overlay_visible: false,
+			font: None,
+			big_font: None,
+			paused: false,
+			turbo: false,
+			turbo_multiplier: crate::consts::CPU_TURBO_MULTIPLIER,
+			base_clock_hz: config.clock_hz,
+			timer_mode: crate::timer::TimerMode::default(),
+			frame_cycles: 0,
+			timer_elapsed: std::time::Duration::ZERO,
+			timer_updated_at: crate::time::Instant::now(),
+			instructions_per_frame: None,
+			next_frame_at: crate::time::Instant::now(),
+			rewind: crate::rewind::RewindBuffer::new(),
+			watchpoint_hit: None,
+			exec_trace: None,
+			exec_trace_error: None,
+			#[cfg(feature = "audio")]
+			audio: None,
+		};
+		emulator.set_clock_hz(config.clock_hz);
+		emulator.set_turbo_multiplier(config.turbo_multiplier);
+		emulator.set_shift_quirk(config.shift_quirk());
+		emulator.set_memory_quirk(config.memory_quirk());
+		emulator.set_jump_quirk(config.jump_quirk());
+		emulator.set_display_wait_quirk(config.display_wait_quirk());
+		emulator.set_clear_waits(config.clear_waits());
+		emulator.set_logic_quirk(config.logic_quirk());
+		emulator.set_clip_quirk(config.clip_quirk());
+		emulator.set_index_overflow_quirk(config.index_overflow_quirk());
+		emulator.set_draw_batch_quirk(config.draw_batch_quirk());
+		emulator.set_window_multiplier(config.window_multiplier);
+		emulator.set_xochip(config.xochip());
+		emulator.set_ram_size(config.ram_size());
+		emulator.set_palette(config.colors().to_array());
+		#[cfg(feature = "window")]
+		for &(key, code) in &config.key_map {
+			emulator.remap_key(key, code);
+		}
+		emulator
+	}
+
+	/// Loads the ROM into the `Ram` and remembers it for resets.
+	///
+	/// # Errors
+	///
+	/// If the ROM does not fit into the `Ram`.
+	#[inline]
+	pub fn load_rom(
+		&mut self,
+		data: &[u8],
+	) -> Result<(), crate::ram::RamError> {
+		self.ram.load_rom(data)?;
+		self.rom = data.to_vec();
+		Ok(())
+	}
+
+	/// Like `self.load_rom`, but also returns what `RomInfo::scan` finds in
+	/// the ROM, for embedders that want to check it in the same call.
+	///
+	/// # Errors
+	///
+	/// If the ROM is empty or does not fit into the `Ram`. Nothing is loaded
+	/// then.
+	pub fn load_rom_checked(
+		&mut self,
+		data: &[u8],
+	) -> Result<crate::rom::RomInfo, crate::rom::RomError> {
+		if data.is_empty() {
+			return Err(crate::rom::RomError::Empty);
+		}
+		self.load_rom(data)?;
+		Ok(crate::rom::RomInfo::scan(data))
+	}
+
+	/// Swaps the ROM for `data` and restarts with it, see `self.cold_reset`.
+	/// The rewind history of the previous ROM is dropped.
+	///
+	/// # Errors
+	///
+	/// If the ROM does not fit into the `Ram`. The current ROM keeps running
+	/// untouched then.
+	pub fn load_rom_reset(
+		&mut self,
+		data: &[u8],
+	) -> Result<(), crate::ram::RamError> {
+		self.ram.check_rom_size(data.len())?;
+		self.rom = data.to_vec();
+		self.rewind.clear();
+		self.cold_reset();
+		Ok(())
+	}
+
+	/// Replaces the digit sprites with a custom `font`, see `Ram::load_font`.
+	/// It survives resets.
+	#[inline]
+	pub fn set_font(&mut self, font: &[u8; crate::consts::RAM_FONT_SIZE]) {
+		self.ram.load_font(font);
+		self.font = Some(*font);
+	}
+
+	/// Replaces the SUPER-CHIP big digit sprites with a custom `font`, see
+	/// `Ram::load_big_font`. It survives resets.
+	#[inline]
+	pub fn set_big_font(
+		&mut self,
+		font: &[u8; crate::consts::RAM_BIG_FONT_SIZE],
+	) {
+		self.ram.load_big_font(font);
+		self.big_font = Some(*font);
+	}
+
+	/// Restarts the machine as if it was powered on again: every component
+	/// is reinitialized, `Ram` is cleared and the digit sprites and the
+	/// current ROM are loaded again. A seeded run (see `self.set_rng_seed`)
+	/// repeats exactly after it.
+	pub fn cold_reset(&mut self) {
+		self.ram.clear();
+		if let Some(font) = &self.font {
+			self.ram.load_font(font);
+		}
+		if let Some(font) = &self.big_font {
+			self.ram.load_big_font(font);
+		}
+		let loaded = self.ram.load_rom(&self.rom);
+		// The ROM already fit when it was loaded for the first time
+		debug_assert!(loaded.is_ok());
+		self.warm_reset();
+	}
+
+	/// Restarts the program but keeps the `Ram` contents, so ROMs that use
+	/// memory as persistent storage keep their data.
+	pub fn warm_reset(&mut self) {
+		self.cpu.reset();
+		self.timer = crate::timer::Timer::new();
+		self.restart_timer_clock();
+		self.watchpoint_hit = None;
+		self.screen.reset();
+		self.keyboard.release_all_keys();
+		self.update_audio();
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn clock_hz(&self) -> std::num::NonZeroU32 {
+		self.cpu.clock_hz()
+	}
+
+	#[inline]
+	pub fn set_clock_hz(&mut self, clock_hz: std::num::NonZeroU32) {
+		self.cpu.set_clock_hz(clock_hz);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn timer_mode(&self) -> crate::timer::TimerMode {
+		self.timer_mode
+	}
+
+	/// Switches how the `Timer` is ticked, keeping the remaining ticks.
+	/// With `TimerMode::Cycles`, it ticks every `self.clock_hz / 60`
+	/// instructions, so runs with `self.run_cycles` are reproducible. The
+	/// vertical blank of the display-wait quirk follows the ticks then too,
+	/// see `Cpu::set_vblank_follows_frames`.
+	pub fn set_timer_mode(&mut self, mode: crate::timer::TimerMode) {
+		self.timer_mode = mode;
+		self.cpu.set_vblank_follows_frames(
+			mode != crate::timer::TimerMode::WallClock,
+		);
+		self.restart_timer_clock();
+	}
+
+	/// Decrements the timers by one 60Hz tick, see `Timer::tick`, and ends
+	/// the frame with `self.end_frame`.
+	#[inline]
+	pub fn tick_timers(&mut self) {
+		self.end_frame();
+		self.update_audio();
+	}
+
+	/// Ticks the `Timer` at the end of a 60Hz frame and ends the frame of
+	/// the `Cpu`, see `Cpu::end_frame`.
+	#[inline]
+	fn end_frame(&mut self) {
+		self.timer.tick();
+		self.cpu.end_frame(&mut self.screen);
+	}
+
+	/// Returns how many instructions `self.run_frame` runs, by default the
+	/// `self.clock_hz` spread over 60 frames per second.
+	#[inline]
+	#[must_use]
+	pub fn instructions_per_frame(&self) -> std::num::NonZeroU32 {
+		self.instructions_per_frame.unwrap_or_else(|| {
+			std::num::NonZeroU32::new(self.cpu.clock_hz().get() / 60)
+				.unwrap_or(std::num::NonZeroU32::MIN)
+		})
+	}
+
+	#[inline]
+	pub fn set_instructions_per_frame(
+		&mut self,
+		instructions: std::num::NonZeroU32,
+	) {
+		self.instructions_per_frame = Some(instructions);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn is_turbo(&self) -> bool {
+		self.turbo
+	}
+
+	/// Fast-forwards: the `Cpu`, the vertical blank and the `Timer` run
+	/// `self.turbo_multiplier` times faster while it is on.
+	#[inline]
+	pub fn set_turbo(&mut self, turbo: bool) {
+		self.turbo = turbo;
+		let multiplier = if turbo {
+			self.turbo_multiplier
+		} else {
+			std::num::NonZeroU32::MIN
+		};
+		self.advance_timers();
+		self.cpu.set_speed_multiplier(multiplier);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn turbo_multiplier(&self) -> std::num::NonZeroU32 {
+		self.turbo_multiplier
+	}
+
+	#[inline]
+	pub fn set_turbo_multiplier(&mut self, multiplier: std::num::NonZeroU32) {
+		self.turbo_multiplier = multiplier;
+		if self.turbo {
+			self.set_turbo(true);
 		}
 	}
 
+	/// Raises (`faster`) or lowers `self.clock_hz` by
+	/// `consts::CPU_CLOCK_STEP_HZ`, but not below it. The `Timer` keeps
+	/// ticking at 60Hz, so the ROM just gets more or fewer instructions per
+	/// frame.
+	#[inline]
+	pub fn step_clock_hz(&mut self, faster: bool) {
+		let step = crate::consts::CPU_CLOCK_STEP_HZ;
+		let clock_hz = self.clock_hz();
+		let clock_hz = if faster {
+			clock_hz.saturating_add(step.get())
+		} else {
+			std::num::NonZeroU32::new(
+				clock_hz.get().saturating_sub(step.get()),
+			)
+			.map_or(clock_hz, |lower| lower.max(step))
+		};
+		self.set_clock_hz(clock_hz);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn is_slow_motion(&self) -> bool {
+		self.cpu.speed_divisor().get() > 1
+	}
+
+	/// Slows down the `Cpu`, the vertical blank and the `Timer` by
+	/// `consts::CPU_SLOW_MOTION_DIVISOR` while it is on, to watch an
+	/// animation frame by frame. Works together with `self.set_turbo`.
+	#[inline]
+	pub fn set_slow_motion(&mut self, slow_motion: bool) {
+		let divisor = if slow_motion {
+			crate::consts::CPU_SLOW_MOTION_DIVISOR
+		} else {
+			std::num::NonZeroU32::MIN
+		};
+		self.advance_timers();
+		self.cpu.set_speed_divisor(divisor);
+	}
+
+	/// Returns to the `Config::clock_hz` the emulator was created with and
+	/// turns off slow motion.
+	#[inline]
+	pub fn reset_speed(&mut self) {
+		self.set_clock_hz(self.base_clock_hz);
+		self.set_slow_motion(false);
+	}
+
+	#[inline]
+	pub fn set_max_stack_depth(&mut self, depth: usize) {
+		self.cpu.set_max_stack_depth(depth);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn rpl_flags(&self) -> [u8; crate::consts::CPU_RPL_FLAGS] {
+		self.cpu.rpl_flags()
+	}
+
 	#[inline]
-	pub fn load_rom(&mut self, data: &[u8]) {
-		self.ram.load_rom(data);
+	pub fn set_rpl_flags(
+		&mut self,
+		flags: [u8; crate::consts::CPU_RPL_FLAGS],
+	) {
+		self.cpu.set_rpl_flags(flags);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn shift_quirk(&self) -> crate::quirks::ShiftQuirk {
+		self.cpu.shift_quirk()
+	}
+
+	#[inline]
+	pub fn set_shift_quirk(&mut self, quirk: crate::quirks::ShiftQuirk) {
+		self.cpu.set_shift_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn memory_quirk(&self) -> crate::quirks::MemoryQuirk {
+		self.cpu.memory_quirk()
+	}
+
+	#[inline]
+	pub fn set_memory_quirk(&mut self, quirk: crate::quirks::MemoryQuirk) {
+		self.cpu.set_memory_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn jump_quirk(&self) -> crate::quirks::JumpQuirk {
+		self.cpu.jump_quirk()
+	}
+
+	#[inline]
+	pub fn set_jump_quirk(&mut self, quirk: crate::quirks::JumpQuirk) {
+		self.cpu.set_jump_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn logic_quirk(&self) -> crate::quirks::LogicQuirk {
+		self.cpu.logic_quirk()
+	}
+
+	#[inline]
+	pub fn set_logic_quirk(&mut self, quirk: crate::quirks::LogicQuirk) {
+		self.cpu.set_logic_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn clip_quirk(&self) -> crate::quirks::ClipQuirk {
+		self.screen.clip_quirk()
+	}
+
+	#[inline]
+	pub fn set_clip_quirk(&mut self, quirk: crate::quirks::ClipQuirk) {
+		self.screen.set_clip_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn index_overflow_quirk(
+		&self,
+	) -> crate::quirks::IndexOverflowQuirk {
+		self.cpu.index_overflow_quirk()
+	}
+
+	#[inline]
+	pub fn set_index_overflow_quirk(
+		&mut self,
+		quirk: crate::quirks::IndexOverflowQuirk,
+	) {
+		self.cpu.set_index_overflow_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn draw_batch_quirk(&self) -> crate::quirks::DrawBatchQuirk {
+		self.screen.draw_batch_quirk()
+	}
+
+	/// See `Screen::set_draw_batch_quirk`.
+	#[inline]
+	pub fn set_draw_batch_quirk(
+		&mut self,
+		quirk: crate::quirks::DrawBatchQuirk,
+	) {
+		self.screen.set_draw_batch_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn is_xochip(&self) -> bool {
+		self.cpu.is_xochip()
+	}
+
+	/// See `Cpu::set_xochip`.
+	#[inline]
+	pub fn set_xochip(&mut self, xochip: bool) {
+		self.cpu.set_xochip(xochip);
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn ram_size(&self) -> usize {
+		self.ram.size()
+	}
+
+	/// See `Ram::set_size`.
+	///
+	/// # Panics
+	///
+	/// If `size` is invalid or the loaded ROM would not fit anymore.
+	pub fn set_ram_size(&mut self, size: usize) {
+		let capacity =
+			size.saturating_sub(crate::consts::RAM_ROM_START_ADDRESS.into());
+		assert!(
+			self.rom.len() <= capacity,
+			"The ROM does not fit into {} bytes.",
+			size
+		);
+		self.ram.set_size(size);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn display_wait_quirk(&self) -> crate::quirks::DisplayWaitQuirk {
+		self.cpu.display_wait_quirk()
+	}
+
+	/// See `Cpu::set_display_wait_quirk`.
+	#[inline]
+	pub fn set_display_wait_quirk(
+		&mut self,
+		quirk: crate::quirks::DisplayWaitQuirk,
+	) {
+		self.cpu.set_display_wait_quirk(quirk);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn clear_waits(&self) -> bool {
+		self.cpu.clear_waits()
+	}
+
+	/// See `Cpu::set_clear_waits`.
+	#[inline]
+	pub fn set_clear_waits(&mut self, clear_waits: bool) {
+		self.cpu.set_clear_waits(clear_waits);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn initial_registers(&self) -> crate::cpu::InitialRegisters {
+		self.cpu.initial_registers()
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn foreground_color(&self) -> u32 {
+		self.screen.foreground_color()
+	}
+
+	/// See `Screen::set_window_multiplier`.
+	///
+	/// # Panics
+	///
+	/// If `multiplier` is not in
+	/// `consts::WINDOW_MIN_MULTIPLIER..=consts::WINDOW_MAX_MULTIPLIER`.
+	#[inline]
+	pub fn set_window_multiplier(&mut self, multiplier: usize) {
+		self.screen.set_window_multiplier(multiplier);
+	}
+
+	#[inline]
+	pub fn set_foreground_color(&mut self, color: u32) {
+		self.screen.set_foreground_color(color);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn background_color(&self) -> u32 {
+		self.screen.background_color()
+	}
+
+	#[inline]
+	pub fn set_background_color(&mut self, color: u32) {
+		self.screen.set_background_color(color);
+	}
+
+	/// See `Screen::palette`.
+	#[inline]
+	#[must_use]
+	pub const fn palette(&self) -> [u32; 4] {
+		self.screen.palette()
+	}
+
+	#[inline]
+	pub fn set_palette(&mut self, palette: [u32; 4]) {
+		self.screen.set_palette(palette);
+	}
+
+	#[inline]
+	pub fn set_initial_registers(
+		&mut self,
+		pattern: crate::cpu::InitialRegisters,
+	) {
+		self.cpu.set_initial_registers(pattern);
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn memory_fill(&self) -> &crate::ram::MemoryFill {
+		self.ram.fill()
+	}
+
+	/// See `Ram::set_fill`. The ROM is overwritten, so call it before
+	/// `self.load_rom`. The resets keep the fill.
+	#[inline]
+	pub fn set_memory_fill(&mut self, fill: crate::ram::MemoryFill) {
+		self.ram.set_fill(fill);
+	}
+
+	#[inline]
+	pub fn set_warn_uninit_reads(&mut self, enabled: bool) {
+		self.ram.set_warn_uninit_reads(enabled);
+	}
+
+	/// See `Ram::take_uninit_reads`.
+	#[inline]
+	#[must_use]
+	pub fn take_uninit_reads(&self) -> Vec<u16> {
+		self.ram.take_uninit_reads()
 	}
 
 	#[inline]
@@ -36,40 +672,803 @@ impl Emulator {
 	}
 
 	#[inline]
-	pub fn reset_pressed_key(&mut self) {
-		self.keyboard.reset_pressed_key();
+	pub fn release_key(&mut self, code: u8) {
+		self.keyboard.release_key(code);
+	}
+
+	/// See `Keyboard::state`.
+	#[inline]
+	#[must_use]
+	pub const fn keyboard_state(&self) -> u16 {
+		self.keyboard.state()
+	}
+
+	/// Holds exactly the keys of the `mask` at once, see
+	/// `Keyboard::set_state`. For frontends that know all the keys every
+	/// frame, instead of `self.press_key` and `self.release_key`.
+	#[inline]
+	pub fn set_keyboard_state(&mut self, mask: u16) {
+		self.keyboard.set_state(mask);
+	}
+
+	/// Holds the keys the `input` reports, see `Keyboard::set_state`.
+	/// Call it once per frame instead of `self.press_key` and
+	/// `self.release_key`.
+	#[inline]
+	pub fn poll_input(&mut self, input: &mut impl crate::input::InputSource) {
+		self.keyboard.set_state(input.poll());
+	}
+
+	/// See `Keyboard::key_map`.
+	#[cfg(feature = "window")]
+	#[inline]
+	#[must_use]
+	pub const fn key_map(
+		&self,
+	) -> &std::collections::HashMap<minifb::Key, u8> {
+		self.keyboard.key_map()
+	}
+
+	/// See `Keyboard::key_code`.
+	#[cfg(feature = "window")]
+	#[inline]
+	#[must_use]
+	pub fn key_code(&self, key: minifb::Key) -> Option<u8> {
+		self.keyboard.key_code(key)
 	}
 
+	/// See `Keyboard::remap`.
+	///
+	/// # Panics
+	///
+	/// If `code` is not in `0x0..=0xF`.
+	#[cfg(feature = "window")]
 	#[inline]
+	pub fn remap_key(&mut self, key: minifb::Key, code: u8) {
+		self.keyboard.remap(key, code);
+	}
+
+	/// Opens the audio output, so the buzzer sounds while the sound timer is
+	/// nonzero.
+	///
+	/// # Errors
+	///
+	/// If the audio output could not be opened.
+	#[cfg(feature = "audio")]
+	pub fn enable_audio(&mut self) -> Result<()> {
+		self.audio = Some(crate::audio::Audio::new()?);
+		self.update_audio();
+		Ok(())
+	}
+
+	/// Whether the buzzer is silent because the audio output is not open,
+	/// see `self.set_muted`.
+	#[cfg(feature = "audio")]
+	#[inline]
+	#[must_use]
+	pub const fn is_muted(&self) -> bool {
+		self.audio.is_none()
+	}
+
+	/// Closes the audio output, or opens it again with
+	/// `self.enable_audio`. The sound timer keeps running while muted, so
+	/// unmuting during a beep plays the rest of it.
+	///
+	/// # Errors
+	///
+	/// If the audio output could not be opened. It stays muted then.
+	#[cfg(feature = "audio")]
+	pub fn set_muted(&mut self, muted: bool) -> Result<()> {
+		if muted {
+			self.audio = None;
+		} else if self.audio.is_none() {
+			self.enable_audio()?;
+		}
+		Ok(())
+	}
+
+	/// Captures what is needed to understand the `error` the last
+	/// instruction returned: the `Cpu` registers, the failed instruction and
+	/// the memory around it.
+	#[must_use]
+	pub fn crash_dump(&self, error: &crate::cpu::CpuError) -> CrashDump {
+		let pc = error.pc();
+		let memory = self.ram.memory();
+		let opcode = match memory.get(usize::from(pc)..usize::from(pc) + 2) {
+			Some(&[high, low]) => Some(u16::from_be_bytes([high, low])),
+			_ => None,
+		};
+		let memory_address =
+			pc.saturating_sub(crate::consts::CRASH_DUMP_MEMORY_CONTEXT);
+		let end = usize::from(pc)
+			+ 2 + usize::from(crate::consts::CRASH_DUMP_MEMORY_CONTEXT);
+		CrashDump {
+			error: error.to_string(),
+			pc,
+			opcode,
+			registers: *self.cpu.registers(),
+			index: self.cpu.index(),
+			stack: self.cpu.stack().to_vec(),
+			memory_address,
+			memory: memory
+				.get(usize::from(memory_address)..end.min(memory.len()))
+				.unwrap_or_default()
+				.to_vec(),
+		}
+	}
+
+	/// Captures the `Cpu` registers, the `Ram`, the `Screen` and the
+	/// `Timer`.
 	#[must_use]
-	pub fn can_reset_pressed_key(&self) -> bool {
-		self.keyboard.can_reset_pressed_key()
+	pub fn save_state(&self) -> EmulatorState {
+		EmulatorState {
+			cpu: self.cpu.save_state(),
+			memory: self.ram.memory().to_vec(),
+			screen: self.screen.save_state(),
+			timer: self.timer.save_state(),
+		}
 	}
 
+	/// Restores the machine captured by `self.save_state`.
+	///
+	/// # Errors
+	///
+	/// If the memory or the screen of the `state` has the wrong size, which
+	/// may happen if it was deserialized from a broken file. Nothing is
+	/// restored then.
+	pub fn load_state(&mut self, state: &EmulatorState) -> Result<()> {
+		anyhow::ensure!(
+			state.memory.len() == self.ram.size(),
+			"Invalid memory size {}, expected {}.",
+			state.memory.len(),
+			self.ram.size()
+		);
+		self.screen.load_state(&state.screen).context("Invalid screen.")?;
+
+		self.cpu.load_state(&state.cpu);
+		self.ram.set_memory(&state.memory);
+		self.timer.load_state(state.timer);
+		self.update_audio();
+		Ok(())
+	}
+
+	/// Sets how many states `self.record_rewind_state` keeps. See
+	/// `RewindBuffer::set_capacity`.
+	#[inline]
+	pub fn set_rewind_capacity(&mut self, capacity: usize) {
+		self.rewind.set_capacity(capacity);
+	}
+
+	/// Remembers the current state for `self.rewind`. Frontends call it once
+	/// per displayed frame.
 	#[inline]
-	pub fn run_instruction(&mut self) {
+	pub fn record_rewind_state(&mut self) {
+		self.rewind.push(self.save_state());
+	}
+
+	/// Restores the most recently recorded state and forgets it, so repeated
+	/// calls step further back. Returns `false` if there is none left.
+	pub fn rewind(&mut self) -> bool {
+		let Some(state) = self.rewind.pop() else {
+			return false;
+		};
+		let restored = self.load_state(&state);
+		// The state was captured by `self.save_state`, so it is valid
+		debug_assert!(restored.is_ok());
+		true
+	}
+
+	/// Runs the following instruction. See `Cpu::run_instruction`.
+	///
+	/// # Errors
+	///
+	/// If the `Cpu` could not run the instruction.
+	#[inline]
+	pub fn run_instruction(&mut self) -> Result<(), crate::cpu::CpuError> {
+		let pc = self.cpu.pc();
+		let registers = self.exec_trace_registers();
 		self.cpu.run_instruction(
 			&mut self.ram,
 			&mut self.timer,
 			&mut self.screen,
-			&self.keyboard,
+			&mut self.keyboard,
+		)?;
+		self.record_exec_trace(pc, registers);
+		self.record_watchpoint_hit(pc);
+		self.count_timer_cycle();
+		self.update_audio();
+		Ok(())
+	}
+
+	/// Runs exactly one instruction right away, ignoring the
+	/// `can_run_instruction` gate. Used to step through a paused ROM.
+	///
+	/// # Errors
+	///
+	/// If the `Cpu` could not run the instruction.
+	#[inline]
+	pub fn step(&mut self) -> Result<(), crate::cpu::CpuError> {
+		let pc = self.cpu.pc();
+		let registers = self.exec_trace_registers();
+		self.cpu.step(
+			&mut self.ram,
+			&mut self.timer,
+			&mut self.screen,
+			&mut self.keyboard,
+		)?;
+		self.record_exec_trace(pc, registers);
+		self.record_watchpoint_hit(pc);
+		self.count_timer_cycle();
+		self.update_audio();
+		Ok(())
+	}
+
+	/// Adds the `watchpoint`, or removes it if it is already there. Returns
+	/// `true` if it was added. See `Ram::toggle_watchpoint`.
+	#[inline]
+	pub fn toggle_watchpoint(
+		&mut self,
+		watchpoint: crate::ram::Watchpoint,
+	) -> bool {
+		self.ram.toggle_watchpoint(watchpoint)
+	}
+
+	/// Returns and forgets the last watchpoint hit by `self.run_instruction`,
+	/// `self.step` or `self.run_cycles`, with the address of the instruction
+	/// that caused it. Frontends pause the emulator then.
+	#[inline]
+	pub fn take_watchpoint_hit(
+		&mut self,
+	) -> Option<(u16, crate::ram::WatchpointHit)> {
+		self.watchpoint_hit.take()
+	}
+
+	/// Logs every instruction run from now on to the `trace`, or stops
+	/// logging with `None`.
+	#[inline]
+	pub fn set_exec_trace(
+		&mut self,
+		trace: Option<crate::exec_trace::ExecTrace>,
+	) {
+		self.exec_trace = trace;
+	}
+
+	/// Returns and forgets the error that stopped the `ExecTrace` set by
+	/// `self.set_exec_trace`. Frontends report it.
+	#[inline]
+	pub fn take_exec_trace_error(&mut self) -> Option<std::io::Error> {
+		self.exec_trace_error.take()
+	}
+
+	/// Captures the registers before an instruction for
+	/// `self.record_exec_trace`, if there is an `ExecTrace`.
+	#[inline]
+	fn exec_trace_registers(&self) -> Option<crate::exec_trace::Registers> {
+		self.exec_trace
+			.as_ref()
+			.map(|_| (*self.cpu.registers(), self.cpu.index()))
+	}
+
+	/// Logs the instruction at `pc` that just ran. If the log could not be
+	/// written, logging stops and the error is kept for
+	/// `self.take_exec_trace_error`.
+	#[cold]
+	fn record_exec_trace(
+		&mut self,
+		pc: u16,
+		before: Option<crate::exec_trace::Registers>,
+	) {
+		let (Some(trace), Some(before)) = (&mut self.exec_trace, before)
+		else {
+			return;
+		};
+		let opcode = match self.ram.snapshot(pc..pc.saturating_add(2)) {
+			Ok(&[high, low]) => Some(u16::from_be_bytes([high, low])),
+			_ => None,
+		};
+		let after = (*self.cpu.registers(), self.cpu.index());
+		if let Err(e) = trace.record(pc, opcode, before, after, self.cpu.pc())
+		{
+			self.exec_trace = None;
+			self.exec_trace_error = Some(e);
+		}
+	}
+
+	#[inline]
+	fn record_watchpoint_hit(&mut self, pc: u16) {
+		if let Some(hit) = self.ram.take_watchpoint_hit() {
+			self.watchpoint_hit = Some((pc, hit));
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// Pauses or resumes execution. The buzzer is silenced while paused and
+	/// all keys are released.
+	#[inline]
+	pub fn set_paused(&mut self, paused: bool) {
+		self.advance_timers();
+		self.paused = paused;
+		// Releases are not reported to a paused ROM, so no key may stay held
+		self.keyboard.release_all_keys();
+		self.update_audio();
+	}
+
+	#[inline]
+	#[cfg_attr(not(feature = "audio"), allow(clippy::unused_self))]
+	fn update_audio(&self) {
+		#[cfg(feature = "audio")]
+		if let Some(audio) = &self.audio {
+			let playing = self.is_beeping();
+			let xochip = self.cpu.is_xochip();
+			audio.set_playing(playing && !xochip);
+			audio.set_pattern_playing(
+				(playing && xochip)
+					.then(|| (self.cpu.audio_pattern(), self.cpu.pitch())),
+			);
+		}
+	}
+
+	/// Runs exactly `cycles` instructions as fast as possible, ignoring the
+	/// wall-clock `can_run_instruction` gate, so the result only depends on
+	/// the ROM. Seed the random generator with `self.set_rng_seed` and use
+	/// the `TimerMode::Cycles` timer mode for fully reproducible runs.
+	///
+	/// # Errors
+	///
+	/// If the `Cpu` could not run an instruction. The remaining cycles are
+	/// not run then.
+	pub fn run_cycles(
+		&mut self,
+		cycles: usize,
+	) -> Result<(), crate::cpu::CpuError> {
+		for _ in 0..cycles {
+			let pc = self.cpu.pc();
+			let registers = self.exec_trace_registers();
+			self.cpu.step(
+				&mut self.ram,
+				&mut self.timer,
+				&mut self.screen,
+				&mut self.keyboard,
+			)?;
+			self.record_exec_trace(pc, registers);
+			self.record_watchpoint_hit(pc);
+			self.count_timer_cycle();
+		}
+		Ok(())
+	}
+
+	/// Runs one 60Hz frame: `self.instructions_per_frame` instructions, or
+	/// fewer if one waits for the vertical blank, then ticks the `Timer`
+	/// once in the `TimerMode::Frames` mode. Display the frame afterwards.
+	///
+	/// # Errors
+	///
+	/// If the `Cpu` could not run an instruction. The rest of the frame is
+	/// not run then.
+	pub fn run_frame(&mut self) -> Result<(), crate::cpu::CpuError> {
+		for _ in 0..self.instructions_per_frame().get() {
+			self.step()?;
+			if self.cpu.is_waiting_for_vblank() || self.cpu.has_ended() {
+				break;
+			}
+		}
+		if self.timer_mode == crate::timer::TimerMode::Frames {
+			self.tick_timers();
+		}
+
+		// Stay on the grid of frames unless a whole frame was missed
+		let interval =
+			self.cpu.wall_clock_duration(crate::consts::TIMER_TICK_INTERVAL);
+		let now = crate::time::Instant::now();
+		self.next_frame_at += interval;
+		if self.next_frame_at + interval <= now {
+			self.next_frame_at = now;
+		}
+		Ok(())
+	}
+
+	/// Runs whatever is due now: a frame with `self.run_frame` in the
+	/// `TimerMode::Frames` mode, otherwise an instruction with
+	/// `self.run_instruction`. Does nothing until then, see
+	/// `self.time_until_next_event`, and once the ROM has ended only the
+	/// timers advance. Returns whether anything ran.
+	///
+	/// # Errors
+	///
+	/// If the `Cpu` could not run an instruction.
+	#[inline]
+	pub fn run_due(&mut self) -> Result<bool, crate::cpu::CpuError> {
+		if self.timer_mode == crate::timer::TimerMode::Frames {
+			if crate::time::Instant::now() < self.next_frame_at {
+				return Ok(false);
+			}
+			self.run_frame()?;
+		} else if self.cpu.has_ended() {
+			self.advance_timers();
+			self.update_audio();
+			return Ok(false);
+		} else if self.can_run_instruction() {
+			self.run_instruction()?;
+		} else {
+			return Ok(false);
+		}
+		Ok(true)
+	}
+
+	/// Advances the machine by one step with the keys of the `held_keys`
+	/// bitmask held, see `InputSource::poll`: shows a due frame first, then
+	/// runs what `self.run_due` would. Frontends call it in a loop, drawing
+	/// on `TickResult::Displayed` and sleeping on `TickResult::Idle`.
+	///
+	/// While paused, the keys do not reach the ROM and only frames are due.
+	pub fn tick(&mut self, held_keys: u16) -> TickResult {
+		if self.screen.can_display() {
+			self.screen.mark_displayed();
+			return TickResult::Displayed;
+		}
+		if self.paused {
+			return TickResult::Idle;
+		}
+
+		self.keyboard.set_state(held_keys);
+		let was_beeping = self.is_beeping();
+		let had_ended = self.cpu.has_ended();
+		match self.run_due() {
+			Err(e) => TickResult::Halted(e),
+			Ok(false) => TickResult::Idle,
+			Ok(true) if self.cpu.has_ended() && !had_ended => {
+				TickResult::Ended
+			}
+			Ok(true) if self.is_beeping() != was_beeping => {
+				TickResult::Beep(!was_beeping)
+			}
+			Ok(true) if self.cpu.is_waiting_for_key() => {
+				TickResult::WaitingForKey
+			}
+			Ok(true) => TickResult::Ran,
+		}
+	}
+
+	/// Whether the buzzer sounds: the sound timer is nonzero and the
+	/// emulator is not paused.
+	#[inline]
+	#[must_use]
+	pub fn is_beeping(&self) -> bool {
+		!self.paused && self.timer.get_sound() > 0
+	}
+
+	/// Ticks the `Timer` once per 60Hz frame of the `TimerMode`. Called
+	/// after every instruction.
+	#[inline]
+	fn count_timer_cycle(&mut self) {
+		match self.timer_mode {
+			crate::timer::TimerMode::WallClock => self.advance_timers(),
+			crate::timer::TimerMode::Frames => {}
+			crate::timer::TimerMode::Cycles => {
+				self.frame_cycles += 1;
+				if self.frame_cycles >= (self.cpu.clock_hz().get() / 60).max(1)
+				{
+					self.frame_cycles = 0;
+					self.end_frame();
+				}
+			}
+		}
+	}
+
+	/// Ticks the `Timer` for the wall-clock time since the last call, scaled
+	/// to the speed of the `Cpu`, in the `TimerMode::WallClock` mode. Time
+	/// spent paused does not count.
+	fn advance_timers(&mut self) {
+		use std::convert::TryFrom as _;
+
+		let now = crate::time::Instant::now();
+		let elapsed = now.duration_since(self.timer_updated_at);
+		self.timer_updated_at = now;
+		if self.paused || self.timer_mode != crate::timer::TimerMode::WallClock
+		{
+			return;
+		}
+
+		let interval = crate::consts::TIMER_TICK_INTERVAL;
+		self.timer_elapsed += self.cpu.emulated_duration(elapsed);
+		let ticks = self.timer_elapsed.as_nanos() / interval.as_nanos();
+		// Both timers are at zero after `u8::MAX` ticks anyway
+		for _ in 0..ticks.min(u128::from(u8::MAX)) {
+			self.end_frame();
+		}
+		let remainder = self.timer_elapsed.as_nanos() % interval.as_nanos();
+		self.timer_elapsed = std::time::Duration::from_nanos(
+			u64::try_from(remainder).unwrap_or_default(),
 		);
 	}
 
+	/// Starts counting the next 60Hz frame from now.
+	fn restart_timer_clock(&mut self) {
+		self.frame_cycles = 0;
+		self.timer_elapsed = std::time::Duration::ZERO;
+		self.timer_updated_at = crate::time::Instant::now();
+		self.next_frame_at = self.timer_updated_at;
+	}
+
+	#[inline]
+	pub fn set_rng_seed(&mut self, seed: u64) {
+		self.cpu.set_rng_seed(seed);
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn can_run_instruction(&self) -> bool {
 		self.cpu.can_run_instruction()
 	}
 
+	/// See `Cpu::has_ended`.
+	#[inline]
+	#[must_use]
+	pub const fn has_ended(&self) -> bool {
+		self.cpu.has_ended()
+	}
+
+	/// Gives read access to the `Cpu` registers, for debuggers.
+	#[inline]
+	#[must_use]
+	pub const fn cpu(&self) -> &crate::cpu::Cpu {
+		&self.cpu
+	}
+
+	/// Gives read access to the `Ram`, for debuggers. See `Ram::snapshot`.
+	#[inline]
+	#[must_use]
+	pub const fn ram(&self) -> &crate::ram::Ram {
+		&self.ram
+	}
+
+	/// Gives read access to the `Screen`, for frontends that render it
+	/// themselves.
+	#[inline]
+	#[must_use]
+	pub const fn screen(&self) -> &crate::screen::Screen {
+		&self.screen
+	}
+
+	/// # Errors
+	///
+	/// If the window buffer could not be updated.
+	#[cfg(feature = "window")]
 	#[inline]
 	pub fn display(&mut self, window: &mut minifb::Window) -> Result<()> {
+		let overlay = self.overlay_visible.then(|| self.overlay_text());
+		self.screen.set_overlay(overlay);
+		self.screen.set_beeping(self.is_beeping());
 		self.screen.display(window)
 	}
 
+	/// Draws the frame of a `TickResult::Displayed`, see `Screen::draw`.
+	///
+	/// # Errors
+	///
+	/// If the window buffer could not be updated.
+	#[cfg(feature = "window")]
+	#[inline]
+	pub fn draw(&mut self, window: &mut minifb::Window) -> Result<()> {
+		let overlay = self.overlay_visible.then(|| self.overlay_text());
+		self.screen.set_overlay(overlay);
+		self.screen.set_beeping(self.is_beeping());
+		self.screen.draw(window)
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn is_overlay_visible(&self) -> bool {
+		self.overlay_visible
+	}
+
+	/// Shows or hides the registers, the top of the stack and the timers
+	/// over the screen in the window.
+	#[inline]
+	pub fn set_overlay_visible(&mut self, visible: bool) {
+		self.overlay_visible = visible;
+	}
+
+	/// See `Screen::set_collision_counting`. The count is shown in the
+	/// overlay.
+	#[inline]
+	pub fn set_collision_counting(&mut self, enabled: bool) {
+		self.screen.set_collision_counting(enabled);
+	}
+
+	/// See `Screen::set_ghosting`.
+	#[inline]
+	pub fn set_ghosting(&mut self, enabled: bool) {
+		self.screen.set_ghosting(enabled);
+	}
+
+	/// See `Screen::set_crt`.
+	#[inline]
+	pub fn set_crt(&mut self, enabled: bool) {
+		self.screen.set_crt(enabled);
+	}
+
+	/// See `Screen::set_visual_beep`.
+	#[inline]
+	pub fn set_visual_beep(&mut self, enabled: bool) {
+		self.screen.set_visual_beep(enabled);
+	}
+
+	/// Summarizes the loaded ROM, named `rom_name`, and the effective
+	/// configuration, for the banner frontends print at startup so it can be
+	/// attached to bug reports.
+	#[must_use]
+	pub fn banner(&self, rom_name: &str) -> String {
+		let [bg, fg, fg2, blend] = self.palette();
+		[
+			crate::consts::WINDOW_TITLE.to_owned(),
+			format!(
+				"  ROM:       {rom_name} ({} bytes, fnv1a {:016x})",
+				self.rom.len(),
+				crate::rom::checksum(&self.rom),
+			),
+			format!("  Platform:  {}", self.platform()),
+			if self.timer_mode == crate::timer::TimerMode::Frames {
+				format!(
+					"  Clock:     {} instructions per frame",
+					self.instructions_per_frame()
+				)
+			} else {
+				format!("  Clock:     {} Hz", self.clock_hz())
+			},
+			format!("  Registers: {}", self.initial_registers()),
+			format!("  Memory:    {}", self.memory_fill()),
+			format!("  Quirks:    {}", self.quirks_text()),
+			format!(
+				"  Palette:   fg #{fg:06X}, bg #{bg:06X}, fg2 #{fg2:06X}, \
+				 blend #{blend:06X}"
+			),
+		]
+		.join("\n")
+	}
+
+	/// Formats the active quirks for `self.banner` and
+	/// `analysis::analyze`.
+	pub(crate) fn quirks_text(&self) -> String {
+		format!(
+			"shift={:?}, memory={:?}, jump={:?}, display_wait={:?}, \
+			 clear_waits={}, logic={:?}, clip={:?}, index_overflow={:?}, \
+			 draw_batch={:?}",
+			self.shift_quirk(),
+			self.memory_quirk(),
+			self.jump_quirk(),
+			self.display_wait_quirk(),
+			self.clear_waits(),
+			self.logic_quirk(),
+			self.clip_quirk(),
+			self.index_overflow_quirk(),
+			self.draw_batch_quirk(),
+		)
+	}
+
+	/// Formats the debug overlay, see `self.set_overlay_visible`. The
+	/// debugger dumps the registers with it too.
+	pub(crate) fn overlay_text(&self) -> String {
+		use std::fmt::Write as _;
+
+		let mut text = String::new();
+		for (row, registers) in self.cpu.registers().chunks(4).enumerate() {
+			for (column, value) in registers.iter().enumerate() {
+				let separator = if column == 0 { "" } else { " " };
+				let _ = write!(
+					text,
+					"{separator}V{:X} {value:02X}",
+					row * 4 + column
+				);
+			}
+			text.push('\n');
+		}
+		let _ = writeln!(
+			text,
+			"I {:04X} PC {:04X}",
+			self.cpu.index(),
+			self.cpu.pc()
+		);
+		match self.cpu.stack().last() {
+			Some(address) => {
+				let _ = write!(text, "S {address:04X}");
+			}
+			None => text.push_str("S ----"),
+		}
+		let _ = write!(
+			text,
+			" DT {:02X} ST {:02X}",
+			self.timer.get_delay(),
+			self.timer.get_sound()
+		);
+		if let Some(collisions) = self.screen.collision_count() {
+			let _ = write!(text, "\nC {collisions:04X}");
+		}
+		text
+	}
+
+	/// See `Screen::display_ascii`.
+	#[inline]
+	pub fn display_ascii(&mut self) -> String {
+		self.screen.display_ascii()
+	}
+
+	/// See `Cpu::profile_report`.
+	#[cfg(feature = "profile")]
+	#[inline]
+	#[must_use]
+	pub fn profile_report(&self) -> Vec<(String, u64)> {
+		self.cpu.profile_report()
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn can_display(&self) -> bool {
 		self.screen.can_display()
 	}
+
+	/// See `Screen::set_display_interval`.
+	#[inline]
+	pub fn set_display_interval(&mut self, interval: std::time::Duration) {
+		self.screen.set_display_interval(interval);
+	}
+
+	/// See `Screen::time_until_display`.
+	#[inline]
+	#[must_use]
+	pub fn time_until_display(&self) -> std::time::Duration {
+		self.screen.time_until_display()
+	}
+
+	/// Returns how long a frontend can sleep until the next instruction, or
+	/// frame in the `TimerMode::Frames` mode, or display is due, instead of
+	/// polling `self.can_run_instruction` and `self.can_display`.
+	#[inline]
+	#[must_use]
+	pub fn time_until_next_event(&self) -> std::time::Duration {
+		let run = if self.timer_mode == crate::timer::TimerMode::Frames {
+			self.next_frame_at
+				.saturating_duration_since(crate::time::Instant::now())
+		} else if self.cpu.has_ended() {
+			// The timers are advanced along with the displays
+			std::time::Duration::MAX
+		} else {
+			self.cpu.time_until_next_instruction()
+		};
+		run.min(self.screen.time_until_display())
+	}
+
+	/// Returns the platform derived from the active quirks and display mode:
+	/// XO-CHIP while its instructions are enabled, SUPER-CHIP if its
+	/// `QuirkProfile` matches the quirks or in the high resolution mode, and
+	/// plain Chip-8 otherwise.
+	#[must_use]
+	pub fn platform(&self) -> crate::platform::Platform {
+		use crate::{platform::Platform, quirks::QuirkProfile};
+
+		let matches = |profile: QuirkProfile| {
+			self.shift_quirk() == profile.shift_quirk()
+				&& self.memory_quirk() == profile.memory_quirk()
+				&& self.jump_quirk() == profile.jump_quirk()
+				&& self.display_wait_quirk() == profile.display_wait_quirk()
+				&& self.clear_waits() == profile.clear_waits()
+				&& self.logic_quirk() == profile.logic_quirk()
+				&& self.clip_quirk() == profile.clip_quirk()
+				&& self.index_overflow_quirk()
+					== profile.index_overflow_quirk()
+				&& self.draw_batch_quirk() == profile.draw_batch_quirk()
+		};
+		if self.is_xochip() {
+			Platform::XoChip
+		} else if matches(QuirkProfile::SuperChip) || self.screen.is_hires() {
+			Platform::SuperChip
+		} else {
+			Platform::Chip8
+		}
+	}
 }
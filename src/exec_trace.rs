@@ -0,0 +1,100 @@
+//! A linear, human-readable log of the executed instructions for reverse
+//! engineering ROMs, see `Emulator::set_exec_trace`. Unlike the `tracing`
+//! feature, it is one line per instruction with the registers it changed:
+//!
+//! ```text
+//! 0204: 7A01  ADD VA, #01      VA=06
+//! 0206: A300  LD I, #300       I=0300
+//! ```
+
+/// The registers an instruction may change: `V0` through `VF` and `I`.
+pub(crate) type Registers = ([u8; 16], u16);
+
+/// Writes the lines of the executed instructions to a file through a buffer.
+/// Once the file reaches its size cap, it is moved aside to `<path>.1`,
+/// replacing the previous one, and a new file is started. So at most twice
+/// the cap is kept on disk however long the ROM runs.
+pub struct ExecTrace {
+	path: std::path::PathBuf,
+	writer: std::io::BufWriter<std::fs::File>,
+	/// Bytes written to the current file.
+	written: u64,
+	max_bytes: u64,
+	/// The address of the last logged instruction.
+	last_pc: Option<u16>,
+}
+
+impl ExecTrace {
+	/// Creates the log at `path`, truncating it, and rotates it every
+	/// `max_bytes`.
+	///
+	/// # Errors
+	///
+	/// If the file could not be created.
+	pub fn create(
+		path: impl Into<std::path::PathBuf>,
+		max_bytes: u64,
+	) -> std::io::Result<Self> {
+		let path = path.into();
+		let writer = std::io::BufWriter::new(std::fs::File::create(&path)?);
+		Ok(Self { path, writer, written: 0, max_bytes, last_pc: None })
+	}
+
+	/// Logs the instruction `opcode` at `pc` that changed the registers from
+	/// `before` to `after` and moved on to `next_pc`.
+	///
+	/// An instruction that neither changes the registers nor moves on, like
+	/// a wait for a key or a jump to itself, is logged only once in a row.
+	pub(crate) fn record(
+		&mut self,
+		pc: u16,
+		opcode: Option<u16>,
+		before: Registers,
+		after: Registers,
+		next_pc: u16,
+	) -> std::io::Result<()> {
+		use std::fmt::Write as _;
+
+		if before == after && next_pc == pc && self.last_pc == Some(pc) {
+			return Ok(());
+		}
+		self.last_pc = Some(pc);
+
+		let mut line = match opcode {
+			Some(opcode) => format!(
+				"{pc:04X}: {opcode:04X}  {:<16}",
+				crate::disasm::disassemble_instruction(opcode)
+			),
+			None => format!("{pc:04X}: ????"),
+		};
+		for (register, (old, new)) in before.0.iter().zip(after.0).enumerate()
+		{
+			if *old != new {
+				let _ = write!(line, " V{register:X}={new:02X}");
+			}
+		}
+		if before.1 != after.1 {
+			let _ = write!(line, " I={:04X}", after.1);
+		}
+		let line = format!("{}\n", line.trim_end());
+
+		if self.written + line.len() as u64 > self.max_bytes {
+			self.rotate()?;
+		}
+		std::io::Write::write_all(&mut self.writer, line.as_bytes())?;
+		self.written += line.len() as u64;
+		Ok(())
+	}
+
+	/// Moves the full file to `<path>.1` and starts a new one.
+	fn rotate(&mut self) -> std::io::Result<()> {
+		std::io::Write::flush(&mut self.writer)?;
+		let mut old_path = self.path.clone().into_os_string();
+		old_path.push(".1");
+		std::fs::rename(&self.path, old_path)?;
+		self.writer =
+			std::io::BufWriter::new(std::fs::File::create(&self.path)?);
+		self.written = 0;
+		Ok(())
+	}
+}
@@ -0,0 +1,25 @@
+/// Computes the 64-bit FNV-1a hash of `data`.
+///
+/// Not cryptographic, but stable across machines and runs, which is all we
+/// need to identify ROMs and screens in logs and tests.
+#[must_use]
+pub fn fnv1a(data: &[u8]) -> u64 {
+	data.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+		(hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3)
+	})
+}
+
+/// Computes the CRC-32 of `data`, the IEEE one of zip and PNG, which ROM
+/// databases list.
+#[must_use]
+pub fn crc32(data: &[u8]) -> u32 {
+	!data.iter().fold(u32::MAX, |crc, &byte| {
+		(0..8).fold(crc ^ u32::from(byte), |crc, _| {
+			if crc & 1 == 1 {
+				(crc >> 1) ^ 0xEDB8_8320
+			} else {
+				crc >> 1
+			}
+		})
+	})
+}
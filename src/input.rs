@@ -0,0 +1,108 @@
+//! Sources of Chip-8 key presses for `Emulator::poll_input`. Several sources
+//! can be used at once with `Combined`, e.g. the window keyboard and a
+//! gamepad.
+
+/// Something that reports which Chip-8 keys are held.
+pub trait InputSource {
+	/// Returns the held keys: bit `n` is set while the key with code `n` is
+	/// held.
+	fn poll(&mut self) -> u16;
+}
+
+impl<T: InputSource + ?Sized> InputSource for &mut T {
+	#[inline]
+	fn poll(&mut self) -> u16 {
+		(**self).poll()
+	}
+}
+
+/// A missing source holds no keys.
+impl<T: InputSource> InputSource for Option<T> {
+	#[inline]
+	fn poll(&mut self) -> u16 {
+		self.as_mut().map_or(0, InputSource::poll)
+	}
+}
+
+/// Holds a key while it is held on either of the two sources.
+pub struct Combined<A, B>(pub A, pub B);
+
+impl<A: InputSource, B: InputSource> InputSource for Combined<A, B> {
+	#[inline]
+	fn poll(&mut self) -> u16 {
+		self.0.poll() | self.1.poll()
+	}
+}
+
+/// The keys held in a [`minifb::Window`], mapped like in
+/// `Keyboard::key_map`.
+#[cfg(feature = "window")]
+pub struct WindowInput<'a> {
+	window: &'a minifb::Window,
+	key_map: &'a std::collections::HashMap<minifb::Key, u8>,
+}
+
+#[cfg(feature = "window")]
+impl<'a> WindowInput<'a> {
+	#[inline]
+	#[must_use]
+	pub const fn new(
+		window: &'a minifb::Window,
+		key_map: &'a std::collections::HashMap<minifb::Key, u8>,
+	) -> Self {
+		Self { window, key_map }
+	}
+}
+
+#[cfg(feature = "window")]
+impl InputSource for WindowInput<'_> {
+	#[inline]
+	fn poll(&mut self) -> u16 {
+		self.window
+			.get_keys()
+			.unwrap_or_default()
+			.iter()
+			.filter_map(|key| self.key_map.get(key))
+			.fold(0, |held, &code| held | 1 << code)
+	}
+}
+
+/// The buttons held on any connected gamepad, mapped like in
+/// `consts::GAMEPAD_BUTTON_MAP`.
+#[cfg(feature = "gamepad")]
+pub struct GamepadInput {
+	gilrs: gilrs::Gilrs,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadInput {
+	/// Starts listening for gamepads. They can be connected later.
+	///
+	/// # Errors
+	///
+	/// If gamepads are not supported on this platform.
+	pub fn new() -> anyhow::Result<Self> {
+		let gilrs = gilrs::Gilrs::new().map_err(|e| {
+			anyhow::anyhow!("Failed to initialize gamepads: {e}")
+		})?;
+		Ok(Self { gilrs })
+	}
+}
+
+#[cfg(feature = "gamepad")]
+impl InputSource for GamepadInput {
+	fn poll(&mut self) -> u16 {
+		// The button states are only updated while handling the events
+		while self.gilrs.next_event().is_some() {}
+
+		let mut held = 0;
+		for (_, gamepad) in self.gilrs.gamepads() {
+			for &(button, code) in &crate::consts::GAMEPAD_BUTTON_MAP {
+				if gamepad.is_pressed(button) {
+					held |= 1 << code;
+				}
+			}
+		}
+		held
+	}
+}
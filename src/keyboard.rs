@@ -2,85 +2,267 @@
 ///
 /// Since the structure works with `u8` key codes, you should get pressed key
 /// ([`minifb::Key`]) code with `Self::get_key_code` before using
-/// `self.press_key_code`.
+/// `self.press_key` and `self.release_key`. The frontend reports every press
+/// and release, so `self.state` always matches the real keys.
 pub struct Keyboard {
-	pub pressed_key_code: Option<u8>,
-	pressed_key_time: std::time::Instant,
+	/// Which Chip-8 code each [`minifb::Key`] presses.
+	#[cfg(feature = "window")]
+	key_map: std::collections::HashMap<minifb::Key, u8>,
+	/// Bit `n` is set while the key with code `n` is held.
+	held_keys: u16,
+	/// The press `Fx0A` has not taken yet.
+	pressed_key_code: Option<u8>,
+}
+
+impl Default for Keyboard {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl Keyboard {
+	#[must_use]
 	pub fn new() -> Self {
 		Self {
+			#[cfg(feature = "window")]
+			key_map: crate::consts::KEYBOARD_KEY_MAP.iter().copied().collect(),
+			held_keys: 0,
 			pressed_key_code: None,
-			pressed_key_time: std::time::Instant::now(),
 		}
 	}
 
-	/// Returns the Chip-8 code of the pressed [`minifb::Key`] key.
+	/// Returns the Chip-8 code of the pressed [`minifb::Key`] key in
+	/// `self.key_map`.
+	#[cfg(feature = "window")]
 	#[cfg_attr(
 		feature = "tracing",
-		tracing::instrument(level = tracing::Level::TRACE, ret),
+		tracing::instrument(level = tracing::Level::TRACE, ret, skip(self)),
 	)]
 	#[inline]
 	#[must_use]
+	pub fn key_code(&self, key: minifb::Key) -> Option<u8> {
+		self.key_map.get(&key).copied()
+	}
+
+	/// Returns which Chip-8 code each [`minifb::Key`] presses.
+	#[cfg(feature = "window")]
+	#[inline]
+	#[must_use]
+	pub const fn key_map(
+		&self,
+	) -> &std::collections::HashMap<minifb::Key, u8> {
+		&self.key_map
+	}
+
+	/// Returns the Chip-8 code of the pressed [`minifb::Key`] key in the
+	/// default `consts::KEYBOARD_KEY_MAP` layout.
+	#[cfg(feature = "window")]
+	#[inline]
+	#[must_use]
 	pub fn get_key_code(key: minifb::Key) -> Option<u8> {
+		crate::consts::KEYBOARD_KEY_MAP
+			.iter()
+			.find(|&&(k, _)| k == key)
+			.map(|&(_, code)| code)
+	}
+
+	/// Binds the [`minifb::Key`] `key` to the Chip-8 `code`, replacing its
+	/// previous binding. Other keys bound to `code` keep working.
+	///
+	/// # Panics
+	///
+	/// If `code` is not in `0x0..=0xF`.
+	#[cfg(feature = "window")]
+	#[inline]
+	pub fn remap(&mut self, key: minifb::Key, code: u8) {
+		assert!(code <= 0xF, "Invalid key code: {:#X}.", code);
+		self.key_map.insert(key, code);
+	}
+
+	/// Parses the name of a [`minifb::Key`] for `self.remap`, ignoring the
+	/// case: a letter, a digit, `NumPad0` through `NumPad9`, an arrow such
+	/// as `Up`, `Space`, `Enter` or `Tab`.
+	#[cfg(feature = "window")]
+	#[must_use]
+	pub fn parse_key(name: &str) -> Option<minifb::Key> {
 		use minifb::Key;
-		match key {
-			Key::Key1 => Some(0x1),
-			Key::Key2 => Some(0x2),
-			Key::Key3 => Some(0x3),
-			Key::Key4 => Some(0xC),
-
-			Key::Q => Some(0x4),
-			Key::W => Some(0x5),
-			Key::E => Some(0x6),
-			Key::R => Some(0xD),
-
-			Key::A => Some(0x7),
-			Key::S => Some(0x8),
-			Key::D => Some(0x9),
-			Key::F => Some(0xE),
-
-			Key::Z => Some(0xA),
-			Key::X => Some(0x0),
-			Key::C => Some(0xB),
-			Key::V => Some(0xF),
+		const KEYS: [Key; 56] = [
+			Key::Key0,
+			Key::Key1,
+			Key::Key2,
+			Key::Key3,
+			Key::Key4,
+			Key::Key5,
+			Key::Key6,
+			Key::Key7,
+			Key::Key8,
+			Key::Key9,
+			Key::A,
+			Key::B,
+			Key::C,
+			Key::D,
+			Key::E,
+			Key::F,
+			Key::G,
+			Key::H,
+			Key::I,
+			Key::J,
+			Key::K,
+			Key::L,
+			Key::M,
+			Key::N,
+			Key::O,
+			Key::P,
+			Key::Q,
+			Key::R,
+			Key::S,
+			Key::T,
+			Key::U,
+			Key::V,
+			Key::W,
+			Key::X,
+			Key::Y,
+			Key::Z,
+			Key::NumPad0,
+			Key::NumPad1,
+			Key::NumPad2,
+			Key::NumPad3,
+			Key::NumPad4,
+			Key::NumPad5,
+			Key::NumPad6,
+			Key::NumPad7,
+			Key::NumPad8,
+			Key::NumPad9,
+			Key::Up,
+			Key::Down,
+			Key::Left,
+			Key::Right,
+			Key::Space,
+			Key::Enter,
+			Key::Tab,
+			Key::Comma,
+			Key::Period,
+			Key::Slash,
+		];
+
+		// Digits are named `Key0` through `Key9`
+		let name = if name.len() == 1 && name.as_bytes()[0].is_ascii_digit() {
+			format!("Key{name}")
+		} else {
+			name.to_owned()
+		};
+		KEYS.iter()
+			.copied()
+			.find(|key| format!("{key:?}").eq_ignore_ascii_case(&name))
+	}
+
+	/// Returns the Chip-8 code of the character typed on the same key as in
+	/// `Self::get_key_code`, for frontends without a window. It does not
+	/// follow `self.remap`.
+	#[inline]
+	#[must_use]
+	pub fn get_char_code(c: char) -> Option<u8> {
+		match c.to_ascii_lowercase() {
+			'1' => Some(0x1),
+			'2' => Some(0x2),
+			'3' => Some(0x3),
+			'4' => Some(0xC),
+
+			'q' => Some(0x4),
+			'w' => Some(0x5),
+			'e' => Some(0x6),
+			'r' => Some(0xD),
+
+			'a' => Some(0x7),
+			's' => Some(0x8),
+			'd' => Some(0x9),
+			'f' => Some(0xE),
+
+			'z' => Some(0xA),
+			'x' => Some(0x0),
+			'c' => Some(0xB),
+			'v' => Some(0xF),
 
 			_ => None,
 		}
 	}
 
-	/// Needed to register a new keystroke. If you want to null the current
-	/// keypress or reset it as invalid, consider `self.reset_pressed_key`.
-	#[cfg_attr(tracing, tracing::instrument)]
+	/// Selects the Chip-8 code to register when several [`minifb::Key`]
+	/// keys are pressed at once.
+	///
+	/// The lowest Chip-8 code wins, so `Fx0A` gets the same key regardless of
+	/// the order in which the window reports them. Unmapped keys are ignored.
+	#[cfg(feature = "window")]
+	#[inline]
+	#[must_use]
+	pub fn select_key_code(&self, keys: &[minifb::Key]) -> Option<u8> {
+		keys.iter().filter_map(|&key| self.key_code(key)).min()
+	}
+
+	/// Registers a press of the key with the Chip-8 `code`. It stays held
+	/// until `self.release_key`.
+	///
+	/// The press is also remembered for `Fx0A` until it is taken with
+	/// `self.take_pressed_key`. If several keys are pressed before that, the
+	/// lowest code wins, like in `Self::select_key_code`.
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
 	#[inline]
 	pub fn press_key(&mut self, code: u8) {
-		self.pressed_key_code = Some(code);
-		self.pressed_key_time = std::time::Instant::now();
+		debug_assert!(code <= 0xF);
+		self.held_keys |= 1 << code;
+		self.pressed_key_code =
+			Some(self.pressed_key_code.map_or(code, |c| c.min(code)));
 	}
 
-	#[cfg_attr(tracing, tracing::instrument(ret))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+	#[inline]
+	pub fn release_key(&mut self, code: u8) {
+		debug_assert!(code <= 0xF);
+		self.held_keys &= !(1 << code);
+	}
+
+	/// Returns the bitmask of the held keys: bit `n` is set while the key
+	/// with code `n` is held.
 	#[inline]
 	#[must_use]
-	pub fn is_key_pressed(&self, code: u8) -> bool {
-		self.pressed_key_code == Some(code)
+	pub const fn state(&self) -> u16 {
+		self.held_keys
 	}
 
-	/// Resets the currently pressed key. Before using this, make sure to check
-	/// `self.can_reset_pressed_key`.
-	#[cfg_attr(tracing, tracing::instrument)]
+	/// Holds exactly the keys set in the `mask`, e.g. from
+	/// `InputSource::poll`. The keys that were not held before are pressed
+	/// like with `self.press_key`.
 	#[inline]
-	pub fn reset_pressed_key(&mut self) {
-		assert!(self.can_reset_pressed_key());
+	pub fn set_state(&mut self, mask: u16) {
+		let pressed = mask & !self.held_keys;
+		for code in (0..16).filter(|code| pressed & (1 << code) != 0) {
+			self.press_key(code);
+		}
+		self.held_keys = mask;
+	}
+
+	/// Releases every key and forgets the press for `Fx0A`, e.g. when the
+	/// keys stop reaching the ROM.
+	#[inline]
+	pub fn release_all_keys(&mut self) {
+		self.held_keys = 0;
 		self.pressed_key_code = None;
 	}
 
-	/// Determines whether enough time has elapsed for us to reset pressed key.
+	/// Whether the key with the Chip-8 `code` is held right now.
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(level = tracing::Level::TRACE, ret, skip(self)),
+	)]
 	#[inline]
 	#[must_use]
-	pub fn can_reset_pressed_key(&self) -> bool {
-		self.pressed_key_code.is_some()
-			&& self.pressed_key_time.elapsed()
-				>= std::time::Duration::from_millis(200)
+	pub fn is_key_pressed(&self, code: u8) -> bool {
+		code <= 0xF && self.held_keys & (1 << code) != 0
+	}
+
+	/// Returns the key pressed since the previous call, if any.
+	#[inline]
+	pub fn take_pressed_key(&mut self) -> Option<u8> {
+		self.pressed_key_code.take()
 	}
 }
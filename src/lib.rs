@@ -0,0 +1,42 @@
+#![deny(clippy::correctness)]
+#![warn(
+	clippy::complexity,
+	clippy::pedantic,
+	clippy::perf,
+	clippy::style,
+	clippy::suspicious
+)]
+#![allow(
+	clippy::as_conversions,
+	clippy::implicit_return,
+	clippy::missing_docs_in_private_items
+)]
+
+pub mod analysis;
+pub mod asm;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod config;
+pub mod consts;
+pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+pub mod emulator;
+pub mod exec_trace;
+mod hash;
+pub mod input;
+pub mod keyboard;
+#[cfg(feature = "window")]
+mod overlay;
+pub mod palette;
+pub mod platform;
+pub mod quirks;
+pub mod ram;
+pub mod rewind;
+pub mod rom;
+pub mod screen;
+pub mod testing;
+pub mod time;
+pub mod timer;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -12,82 +12,1140 @@
 	clippy::missing_docs_in_private_items
 )]
 
-mod consts;
-mod cpu;
-mod emulator;
-mod keyboard;
-mod ram;
-mod screen;
-mod timer;
+use {
+	anyhow::{Context as _, Result},
+	chip_8::{
+		analysis, asm, config, consts, cpu, debugger, disasm, emulator,
+		exec_trace, input, keyboard, palette, platform, quirks, ram, rom,
+		timer,
+	},
+};
 
-use anyhow::{Context as _, Result};
+/// Chip-8 emulator.
+///
+/// The profile, speed, turbo, scale, colors and key bindings override those
+/// of the config file.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(clap::Parser)]
+#[command(version)]
+struct Args {
+	/// Path to the ROM, or `-` to read it from stdin. Defaults to the most
+	/// recent ROM.
+	#[arg(value_name = "PATH")]
+	rom_path: Option<std::path::PathBuf>,
+	/// Run these ROMs one after another instead. Page Down and Page Up switch
+	/// to the next and the previous one.
+	#[arg(
+		long,
+		num_args = 1..,
+		value_name = "PATH",
+		conflicts_with_all = ["rom_path", "terminal", "bench", "debug"]
+	)]
+	playlist: Vec<std::path::PathBuf>,
+	/// Move on to the next ROM of the playlist after this many seconds.
+	#[arg(
+		long,
+		value_name = "SECS",
+		requires = "playlist",
+		conflicts_with = "rom_path"
+	)]
+	dwell: Option<std::num::NonZeroU64>,
+	/// Start the playlist over after the last ROM instead of quitting.
+	#[arg(long, requires = "playlist", conflicts_with = "rom_path")]
+	loop_playlist: bool,
+	/// `rom_path`, or the most recent ROM without it.
+	#[arg(skip)]
+	path: std::path::PathBuf,
+	/// TOML file with the settings, see the README.
+	#[arg(long, value_name = "FILE")]
+	config: Option<std::path::PathBuf>,
+	/// Preset of quirks: chip8, schip or xochip.
+	#[arg(long)]
+	profile: Option<quirks::QuirkProfile>,
+	/// Instructions per second.
+	#[arg(long, value_name = "HZ")]
+	speed: Option<std::num::NonZeroU32>,
+	/// Run N instructions per 60Hz frame, then tick the timers once, instead
+	/// of pacing every instruction on its own.
+	#[arg(long, value_name = "N", conflicts_with = "debug")]
+	ipf: Option<std::num::NonZeroU32>,
+	/// How many times faster the emulator runs while Tab is held.
+	#[arg(long, value_name = "N")]
+	turbo: Option<std::num::NonZeroU32>,
+	/// Size of a pixel in the window, 2 through 30.
+	#[arg(long, value_name = "N")]
+	scale: Option<usize>,
+	/// Initial value of the registers: zero, random or random:<seed>.
+	#[arg(long, default_value = "zero")]
+	init_regs: cpu::InitialRegisters,
+	/// Initial value of the program memory: zero, ones, random,
+	/// random:<seed> or pattern:<hex bytes>, e.g. pattern:DEADBEEF.
+	#[arg(long, value_name = "FILL", default_value = "zero")]
+	mem_fill: ram::MemoryFill,
+	/// Built-in colors: classic, octo, gameboy or amber. The color flags
+	/// override them.
+	#[arg(long, value_name = "NAME")]
+	palette: Option<palette::PalettePreset>,
+	/// Color of the lit pixels, on the first XO-CHIP plane only.
+	#[arg(
+		long,
+		visible_alias = "color1",
+		value_name = "RRGGBB",
+		value_parser = config::parse_color
+	)]
+	fg: Option<u32>,
+	/// Color of the unlit pixels.
+	#[arg(
+		long,
+		visible_alias = "color0",
+		value_name = "RRGGBB",
+		value_parser = config::parse_color
+	)]
+	bg: Option<u32>,
+	/// Color of the pixels lit on the second XO-CHIP plane only.
+	#[arg(long, value_name = "RRGGBB", value_parser = config::parse_color)]
+	color2: Option<u32>,
+	/// Color of the pixels lit on both XO-CHIP planes.
+	#[arg(long, value_name = "RRGGBB", value_parser = config::parse_color)]
+	color3: Option<u32>,
+	/// File with 80 bytes of custom digit sprites: 0 through F, 5 bytes
+	/// each. 160 more bytes after them replace the SUPER-CHIP big digits,
+	/// 10 bytes each.
+	#[arg(long, value_name = "FILE")]
+	font: Option<std::path::PathBuf>,
+	/// Print the digit sprites, those of --font if given, as # and . and
+	/// exit.
+	#[arg(long)]
+	dump_font: bool,
+	/// Binds a key to a Chip-8 key code, e.g. G=5. Can be repeated.
+	#[arg(
+		long = "map",
+		value_name = "KEY=CODE",
+		value_parser = parse_key_binding
+	)]
+	key_map: Vec<(minifb::Key, u8)>,
+	/// Warn about reads of memory the program never wrote.
+	#[arg(long)]
+	warn_uninit_reads: bool,
+	/// Count the pixels erased by sprites since the last clear and show them
+	/// in the F3 overlay.
+	#[arg(long)]
+	count_collisions: bool,
+	/// Start with the buzzer muted. M unmutes it.
+	#[cfg(feature = "audio")]
+	#[arg(long)]
+	mute: bool,
+	/// Fade pixels out over a few frames instead of turning them off at
+	/// once, to reduce flicker.
+	#[arg(long, conflicts_with = "terminal")]
+	ghosting: bool,
+	/// Dim every other row of the window like the scanlines of a CRT.
+	#[arg(long, conflicts_with = "terminal")]
+	crt: bool,
+	/// Frame the window in red while the buzzer sounds.
+	#[arg(long, conflicts_with = "terminal")]
+	visual_beep: bool,
+	/// Do not print the banner.
+	#[arg(long)]
+	quiet: bool,
+	/// Print the disassembly of the ROM and exit.
+	#[arg(long)]
+	disasm: bool,
+	/// Print the size, the checksums, the platform the instructions need and
+	/// the first instructions of the ROM and exit.
+	#[arg(long, conflicts_with = "disasm")]
+	info: bool,
+	/// Write a report of the ROM to OUT and exit: its checksums and quirks,
+	/// a labeled disassembly, the instructions a short headless run covers
+	/// and the issues it finds.
+	#[arg(long, value_name = "OUT", conflicts_with_all = ["disasm", "info"])]
+	analyze: Option<std::path::PathBuf>,
+	/// Print the recently opened ROMs, the most recent first, and exit.
+	#[arg(long)]
+	recent: bool,
+	/// Assemble the source IN into the ROM OUT and exit, see the README.
+	#[arg(long, num_args = 2, value_names = ["IN", "OUT"])]
+	assemble: Vec<std::path::PathBuf>,
+	/// Draw the screen in the terminal instead of a window.
+	#[arg(long)]
+	terminal: bool,
+	/// Show the frames per second in the title bar.
+	#[arg(long, conflicts_with = "terminal")]
+	fps: bool,
+	/// Redraw the window every 10ms instead of once per 60Hz refresh.
+	#[arg(long, conflicts_with = "terminal")]
+	no_vsync: bool,
+	/// Keep running while the window is not focused instead of pausing.
+	#[arg(long, conflicts_with = "terminal")]
+	no_auto_pause: bool,
+	/// Run this many instructions as fast as possible without a display,
+	/// then print the throughput and exit.
+	#[arg(long, value_name = "CYCLES", conflicts_with = "terminal")]
+	bench: Option<usize>,
+	/// Start paused and read debugger commands from stdin, see the README.
+	/// With --terminal, nothing is displayed.
+	#[arg(long, conflicts_with = "bench")]
+	debug: bool,
+	/// Log every executed instruction with the registers it changed to
+	/// FILE. A full log is moved to FILE.1.
+	#[arg(long, value_name = "FILE")]
+	trace_exec: Option<std::path::PathBuf>,
+	/// If an instruction fails, write the registers, the stack and the
+	/// memory around it to FILE as JSON.
+	#[cfg(feature = "serde")]
+	#[arg(long, value_name = "FILE")]
+	crash_dump: Option<std::path::PathBuf>,
+	/// What to log: a level like debug, or a filter like
+	/// `chip_8::cpu=trace`. Defaults to `RUST_LOG`, or info if it is unset.
+	#[cfg(feature = "tracing")]
+	#[arg(long, value_name = "FILTER")]
+	log_level: Option<String>,
+}
+
+/// Parses a `--map` binding like `G=5`: a key name (see
+/// `Keyboard::parse_key`) and a hex Chip-8 key code.
+#[inline]
+fn parse_key_binding(s: &str) -> Result<(minifb::Key, u8)> {
+	let (name, code) =
+		s.split_once('=').context("Expected <key>=<code>, like G=5.")?;
+	let key = keyboard::Keyboard::parse_key(name)
+		.with_context(|| format!("Unknown key: {name:?}."))?;
+	let code = u8::from_str_radix(code, 16)
+		.ok()
+		.filter(|&code| code <= 0xF)
+		.with_context(|| {
+		format!("Invalid key code {code:?}, expected 0 through F.")
+	})?;
+	Ok((key, code))
+}
+
+/// Loads the `--config` file, if any, and applies the overriding flags.
+#[inline]
+fn load_config(args: &Args) -> Result<config::Config> {
+	let mut config = match &args.config {
+		Some(path) => config::Config::load(path)?,
+		None => config::Config::default(),
+	};
+	if let Some(profile) = args.profile {
+		config.profile = profile;
+	}
+	if let Some(speed) = args.speed {
+		config.clock_hz = speed;
+	}
+	if let Some(turbo) = args.turbo {
+		config.turbo_multiplier = turbo;
+	}
+	if let Some(scale) = args.scale {
+		config.window_multiplier = scale;
+	}
+	if let Some(palette) = args.palette {
+		config.palette = palette;
+	}
+	for (color, arg) in [
+		(&mut config.bg, args.bg),
+		(&mut config.fg, args.fg),
+		(&mut config.fg2, args.color2),
+		(&mut config.blend, args.color3),
+	] {
+		if arg.is_some() {
+			*color = arg;
+		}
+	}
+	config.key_map.extend_from_slice(&args.key_map);
+	config.validate()?;
+	Ok(config)
+}
+
+/// Prints the listing of the ROM at `path` to stdout.
+#[inline]
+fn print_disassembly(path: &std::path::Path) -> Result<()> {
+	let rom_data = read_rom(path)?;
+	for (address, opcode, text) in disasm::disassemble_rom(&rom_data) {
+		println!("{address:03X}: {opcode:04X}  {text}");
+	}
+	Ok(())
+}
+
+/// Prints what `--info` shows about the ROM at `path` to stdout. The
+/// platform is guessed with `rom::extended_instructions`.
+#[inline]
+fn print_info(path: &std::path::Path) -> Result<()> {
+	let rom_data = read_rom(path)?;
+	let rom::RomInfo { platform, extended_instructions: extended, .. } =
+		rom::RomInfo::scan(&rom_data);
+	let profile = match platform {
+		platform::Platform::Chip8 => "chip8",
+		platform::Platform::SuperChip => "schip",
+		platform::Platform::XoChip => "xochip",
+	};
+
+	println!("Size:      {} bytes", rom_data.len());
+	println!("CRC32:     {:08x}", rom::crc32(&rom_data));
+	println!("fnv1a:     {:016x}", rom::checksum(&rom_data));
+	if extended.is_empty() {
+		println!("Platform:  {platform} (--profile {profile})");
+	} else {
+		println!(
+			"Platform:  {platform} (--profile {profile}), {} extended \
+			 instructions:",
+			extended.len()
+		);
+	}
+	for &(address, opcode) in
+		extended.iter().take(consts::ROM_INFO_INSTRUCTIONS)
+	{
+		let text = disasm::disassemble_instruction(opcode);
+		println!("  {address:03X}: {opcode:04X}  {text}");
+	}
+	println!("Start:");
+	for (address, opcode, text) in disasm::disassemble_rom(&rom_data)
+		.into_iter()
+		.take(consts::ROM_INFO_INSTRUCTIONS)
+	{
+		println!("  {address:03X}: {opcode:04X}  {text}");
+	}
+	Ok(())
+}
+
+/// Writes the `analysis::analyze` report of the ROM at `path` to
+/// `report_path`.
+#[inline]
+fn write_analysis(
+	path: &std::path::Path,
+	report_path: &std::path::Path,
+) -> Result<()> {
+	let rom_data = read_rom(path)?;
+	let report = analysis::analyze(&rom_data, consts::ANALYSIS_CYCLES)?;
+	std::fs::write(report_path, report).with_context(|| {
+		format!("Failed to write the report to {}.", report_path.display())
+	})
+}
+
+/// Assembles the source at `source_path` into a ROM at `rom_path`.
+#[inline]
+fn assemble_file(
+	source_path: &std::path::Path,
+	rom_path: &std::path::Path,
+) -> Result<()> {
+	let source = std::fs::read_to_string(source_path).with_context(|| {
+		format!("Failed to read {}.", source_path.display())
+	})?;
+	let rom = asm::assemble(&source).with_context(|| {
+		format!("Invalid source {}.", source_path.display())
+	})?;
+	std::fs::write(rom_path, &rom)
+		.with_context(|| format!("Failed to write {}.", rom_path.display()))?;
+	eprintln!("Wrote {} bytes to {}", rom.len(), rom_path.display());
+	Ok(())
+}
+
+/// Whether the ROM `path` is `-`, which stands for stdin.
+#[inline]
+fn is_stdin(path: &std::path::Path) -> bool {
+	path.as_os_str() == "-"
+}
 
+/// Reads the ROM at `path`, or from stdin if it is `-`. The common ways
+/// for the path to be wrong get their own messages, and an empty ROM is
+/// rejected rather than run as a program of zeros.
 #[inline]
-fn extract_path_from_args() -> Result<std::path::PathBuf> {
-	match std::env::args().nth(1) {
-		Some(path) => Ok(std::path::PathBuf::from(path)),
-		None => Err(anyhow::anyhow!("Enter a path to the ROM.")),
+fn read_rom(path: &std::path::Path) -> Result<Vec<u8>> {
+	let data = if is_stdin(path) {
+		let mut data = vec![];
+		std::io::Read::read_to_end(&mut std::io::stdin(), &mut data)
+			.context("Failed to read stdin.")?;
+		data
+	} else {
+		// Opening a directory succeeds on Unix, only reading it fails
+		if path.is_dir() {
+			anyhow::bail!("{} is a directory, not a ROM.", path.display());
+		}
+		match std::fs::read(path) {
+			Ok(data) => data,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+				anyhow::bail!("No ROM at {}.", path.display())
+			}
+			Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+				anyhow::bail!("No permission to read {}.", path.display())
+			}
+			Err(e) => {
+				return Err(e).with_context(|| {
+					format!("Failed to read {}.", path.display())
+				});
+			}
+		}
+	};
+	anyhow::ensure!(!data.is_empty(), "The ROM is empty.");
+	Ok(data)
+}
+
+/// Returns where the recently opened ROMs are listed, one path per line
+/// with the most recent first.
+#[inline]
+fn recent_roms_path() -> Result<std::path::PathBuf> {
+	let home = std::env::var_os("HOME").context("HOME is not set.")?;
+	Ok(std::path::Path::new(&home).join(".config/chip-8/recent"))
+}
+
+/// Reads the recently opened ROMs, the most recent first. There are none
+/// before the first ROM is opened.
+#[inline]
+fn read_recent_roms() -> Result<Vec<std::path::PathBuf>> {
+	let path = recent_roms_path()?;
+	match std::fs::read_to_string(&path) {
+		Ok(list) => Ok(list.lines().map(std::path::PathBuf::from).collect()),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+		Err(e) => Err(e)
+			.with_context(|| format!("Failed to read {}.", path.display())),
+	}
+}
+
+/// Puts the ROM at `path` first in the recent ROMs, keeping at most
+/// `consts::RECENT_ROMS_CAPACITY` of them.
+#[inline]
+fn remember_rom(path: &std::path::Path) -> Result<()> {
+	// The list is used from other directories too
+	let path = std::fs::canonicalize(path).context("Failed to resolve.")?;
+	let mut recent = read_recent_roms()?;
+	recent.retain(|recent_path| *recent_path != path);
+	recent.insert(0, path);
+	recent.truncate(consts::RECENT_ROMS_CAPACITY);
+
+	let list_path = recent_roms_path()?;
+	if let Some(dir) = list_path.parent() {
+		std::fs::create_dir_all(dir)
+			.with_context(|| format!("Failed to create {}.", dir.display()))?;
+	}
+	let list: String =
+		recent.iter().map(|path| path.display().to_string() + "\n").collect();
+	std::fs::write(&list_path, list)
+		.with_context(|| format!("Failed to write {}.", list_path.display()))
+}
+
+/// Returns the `rom_path`, or the most recent ROM without it.
+#[inline]
+fn resolve_rom_path(
+	rom_path: Option<std::path::PathBuf>,
+) -> Result<std::path::PathBuf> {
+	if let Some(path) = rom_path {
+		return Ok(path);
+	}
+	let path = read_recent_roms()?
+		.into_iter()
+		.next()
+		.context("No ROM given and no recent ROMs.")?;
+	eprintln!("Opening the most recent ROM: {}", path.display());
+	Ok(path)
+}
+
+/// The digits of a custom font for `Emulator::set_font` and, if the file
+/// has them, the big ones for `Emulator::set_big_font`.
+type Font =
+	([u8; consts::RAM_FONT_SIZE], Option<[u8; consts::RAM_BIG_FONT_SIZE]>);
+
+/// Reads a custom font: the small digits, optionally followed by the
+/// SUPER-CHIP big ones.
+#[inline]
+fn read_font(path: &std::path::Path) -> Result<Font> {
+	use std::convert::TryFrom as _;
+
+	let data = std::fs::read(path).context("Failed to read path.")?;
+	let (small, big) = (consts::RAM_FONT_SIZE, consts::RAM_BIG_FONT_SIZE);
+	anyhow::ensure!(
+		data.len() == small || data.len() == small + big,
+		"Expected {small} bytes of digits, or {} with the {big} bytes of the \
+		 SUPER-CHIP big digits after them, got {}.",
+		small + big,
+		data.len()
+	);
+	let (small_font, big_font) = data.split_at(small);
+	Ok((
+		<[u8; consts::RAM_FONT_SIZE]>::try_from(small_font)?,
+		<[u8; consts::RAM_BIG_FONT_SIZE]>::try_from(big_font).ok(),
+	))
+}
+
+/// Loads the font at `path` into the `emulator`.
+#[inline]
+fn load_font(
+	emulator: &mut emulator::Emulator,
+	path: &std::path::Path,
+) -> Result<()> {
+	let (font, big_font) = read_font(path)?;
+	emulator.set_font(&font);
+	if let Some(big_font) = big_font {
+		emulator.set_big_font(&big_font);
+	}
+	Ok(())
+}
+
+/// Prints the digit sprites, those of the font at `path` if there is one,
+/// to stdout: every digit as a grid of `#` for the lit pixels and `.` for
+/// the others, the small digits first and the SUPER-CHIP big ones after.
+#[inline]
+fn print_font(path: Option<&std::path::Path>) -> Result<()> {
+	let mut emulator = emulator::Emulator::new();
+	if let Some(path) = path {
+		load_font(&mut emulator, path)?;
+	}
+
+	for (title, address, height) in [
+		("Digits (Fx29)", consts::RAM_DIGIT_SPRITES_ADDRESS, 5),
+		("Big digits (Fx30)", consts::RAM_BIG_DIGIT_SPRITES_ADDRESS, 10),
+	] {
+		println!("{title}:");
+		let sprites = emulator
+			.ram()
+			.snapshot(address..address + 16 * height)
+			.context("The font is out of memory bounds.")?;
+		for (digit, sprite) in sprites.chunks(height.into()).enumerate() {
+			println!("{digit:X}");
+			for row in sprite {
+				let line: String = (0..8)
+					.rev()
+					.map(|bit| if row >> bit & 1 == 1 { '#' } else { '.' })
+					.collect();
+				println!("{line}");
+			}
+		}
 	}
+	Ok(())
 }
 
 #[inline]
-fn prepare_emulator(emulator: &mut emulator::Emulator) -> Result<()> {
+fn prepare_emulator(
+	emulator: &mut emulator::Emulator,
+	args: &Args,
+) -> Result<()> {
+	emulator.set_initial_registers(args.init_regs);
+	emulator.set_memory_fill(args.mem_fill.clone());
+	emulator.set_warn_uninit_reads(args.warn_uninit_reads);
+	emulator.set_collision_counting(args.count_collisions);
+	emulator.set_ghosting(args.ghosting);
+	emulator.set_crt(args.crt);
+	emulator.set_visual_beep(args.visual_beep);
+	if let Some(instructions) = args.ipf {
+		emulator.set_instructions_per_frame(instructions);
+		emulator.set_timer_mode(timer::TimerMode::Frames);
+	}
+	if let Some(path) = &args.font {
+		load_font(emulator, path).context("Failed to load the font.")?;
+	}
+	if let Some(path) = &args.trace_exec {
+		let trace =
+			exec_trace::ExecTrace::create(path, consts::EXEC_TRACE_MAX_BYTES)
+				.context("Failed to create the execution trace.")?;
+		emulator.set_exec_trace(Some(trace));
+	}
+
 	// Load rom
-	let path = extract_path_from_args()
-		.context("Failed to extract path from args.")?;
-	let rom_data = std::fs::read(path).context("Failed to read path.")?;
-	emulator.load_rom(&rom_data);
+	let rom_data = read_rom(&args.path)?;
+	emulator.load_rom(&rom_data).context("Failed to load the ROM.")?;
+
+	if !args.quiet {
+		let rom_name = if is_stdin(&args.path) {
+			"<stdin>".into()
+		} else {
+			args.path.file_name().unwrap_or_default().to_string_lossy()
+		};
+		eprintln!("{}", emulator.banner(&rom_name));
+	}
 	Ok(())
 }
 
+/// Writes the state of the `emulator` to `path` as JSON.
+#[cfg(feature = "serde")]
+#[inline]
+fn save_state(
+	emulator: &emulator::Emulator,
+	path: &std::path::Path,
+) -> Result<()> {
+	let json = serde_json::to_vec(&emulator.save_state())
+		.context("Failed to serialize the state.")?;
+	std::fs::write(path, json).context("Failed to write the state file.")
+}
+
+/// Restores the state of the `emulator` written by `save_state`.
+#[cfg(feature = "serde")]
+#[inline]
+fn load_state(
+	emulator: &mut emulator::Emulator,
+	path: &std::path::Path,
+) -> Result<()> {
+	let json =
+		std::fs::read(path).context("Failed to read the state file.")?;
+	let state = serde_json::from_slice(&json)
+		.context("Failed to deserialize the state.")?;
+	emulator.load_state(&state).context("Failed to restore the state.")
+}
+
+/// Writes what the window shows to a PNG file named after the current time.
+#[cfg(feature = "screenshot")]
+#[allow(clippy::cast_possible_truncation)] // The window is small
+#[inline]
+fn save_screenshot(
+	emulator: &emulator::Emulator,
+) -> Result<std::path::PathBuf> {
+	let millis = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.context("The clock is before 1970.")?
+		.as_millis();
+	let path = std::path::PathBuf::from(format!("screenshot-{millis}.png"));
+	image::save_buffer(
+		&path,
+		&emulator.screen().to_image(),
+		emulator.screen().window_width() as u32,
+		emulator.screen().window_height() as u32,
+		image::ExtendedColorType::Rgb8,
+	)
+	.context("Failed to write the PNG.")?;
+	Ok(path)
+}
+
+/// Counts the frames displayed during the last second.
+struct FpsCounter {
+	frames: u32,
+	since: std::time::Instant,
+}
+
+impl FpsCounter {
+	#[inline]
+	fn new() -> Self {
+		Self { frames: 0, since: std::time::Instant::now() }
+	}
+
+	/// Counts a displayed frame. Returns the frames per second once a second
+	/// has passed since the last time it did.
+	#[inline]
+	fn frame(&mut self) -> Option<u32> {
+		self.frames += 1;
+		if self.since.elapsed() < std::time::Duration::from_secs(1) {
+			return None;
+		}
+		let fps = self.frames;
+		*self = Self::new();
+		Some(fps)
+	}
+}
+
+/// The ROMs of `--playlist`, swapped in place with
+/// `Emulator::load_rom_reset`.
+struct Playlist {
+	roms: Vec<(std::path::PathBuf, Vec<u8>)>,
+	current: usize,
+	/// How long each ROM runs. Without it, only the hotkeys move on.
+	dwell: Option<std::time::Duration>,
+	/// Whether the last ROM is followed by the first one again.
+	looping: bool,
+	/// When the current ROM was loaded.
+	since: std::time::Instant,
+	/// Where the save states of the current ROM go.
+	state_path: std::path::PathBuf,
+	quiet: bool,
+}
+
+impl Playlist {
+	/// Reads all the ROMs up front, so a wrong path is reported before the
+	/// first one starts. The first one is expected to be loaded already.
+	#[inline]
+	fn new(args: &Args) -> Result<Self> {
+		let roms = args
+			.playlist
+			.iter()
+			.map(|path| {
+				let data = read_rom(path).with_context(|| {
+					format!("Failed to read {}.", path.display())
+				})?;
+				Ok((path.clone(), data))
+			})
+			.collect::<Result<Vec<_>>>()?;
+		Ok(Self {
+			state_path: roms[0].0.with_extension("state"),
+			roms,
+			current: 0,
+			dwell: args
+				.dwell
+				.map(|secs| std::time::Duration::from_secs(secs.get())),
+			looping: args.loop_playlist,
+			since: std::time::Instant::now(),
+			quiet: args.quiet,
+		})
+	}
+
+	/// Moves on to the next ROM once the current one has run for the dwell
+	/// time, or to the next or the previous one on Page Down or Page Up.
+	/// Returns `false` when the playlist is over.
+	#[inline]
+	fn update(
+		&mut self,
+		window: &mut minifb::Window,
+		emulator: &mut emulator::Emulator,
+	) -> Result<bool> {
+		let last = self.roms.len() - 1;
+		let next = if window
+			.is_key_pressed(minifb::Key::PageUp, minifb::KeyRepeat::No)
+		{
+			match self.current.checked_sub(1) {
+				Some(previous) => previous,
+				None if self.looping => last,
+				// Restart the first ROM
+				None => 0,
+			}
+		} else if window
+			.is_key_pressed(minifb::Key::PageDown, minifb::KeyRepeat::No)
+			|| self.dwell.is_some_and(|dwell| self.since.elapsed() >= dwell)
+		{
+			match self.current {
+				current if current < last => current + 1,
+				_ if self.looping => 0,
+				_ => return Ok(false),
+			}
+		} else {
+			return Ok(true);
+		};
+
+		let (path, data) = &self.roms[next];
+		emulator
+			.load_rom_reset(data)
+			.with_context(|| format!("Failed to load {}.", path.display()))?;
+		if !self.quiet {
+			let name = path.file_name().unwrap_or_default().to_string_lossy();
+			eprintln!("{}", emulator.banner(&name));
+		}
+		window.set_title(&window_title(emulator));
+		self.state_path = path.with_extension("state");
+		self.current = next;
+		self.since = std::time::Instant::now();
+		Ok(true)
+	}
+}
+
+/// Names the platform and the speed of the `emulator`, like
+/// `Chip-8 Emulator [CHIP-8] 500 Hz`, and whether the ROM has ended.
+#[inline]
+fn window_title(emulator: &emulator::Emulator) -> String {
+	let slow_motion = if emulator.is_slow_motion() {
+		format!(" x1/{}", consts::CPU_SLOW_MOTION_DIVISOR)
+	} else {
+		String::new()
+	};
+	let ended = if emulator.has_ended() { " - Ended" } else { "" };
+	format!(
+		"{} [{}] {} Hz{slow_motion}{ended}",
+		consts::WINDOW_TITLE,
+		emulator.platform(),
+		emulator.clock_hz(),
+	)
+}
+
+/// Handles the hotkeys of the speed: fast-forwarding while the turbo
+/// hotkey is held, the clock and slow motion. The title shows the new clock
+/// and slow motion.
+#[inline]
+fn handle_speed_hotkeys(
+	window: &mut minifb::Window,
+	emulator: &mut emulator::Emulator,
+) {
+	let turbo = window.is_key_down(minifb::Key::Tab);
+	if turbo != emulator.is_turbo() {
+		emulator.set_turbo(turbo);
+	}
+
+	let pressed = |keys: &[minifb::Key]| {
+		keys.iter()
+			.any(|&key| window.is_key_pressed(key, minifb::KeyRepeat::Yes))
+	};
+	if pressed(&[minifb::Key::Equal, minifb::Key::NumPadPlus]) {
+		emulator.step_clock_hz(true);
+	} else if pressed(&[minifb::Key::Minus, minifb::Key::NumPadMinus]) {
+		emulator.step_clock_hz(false);
+	} else if window.is_key_pressed(minifb::Key::F4, minifb::KeyRepeat::No) {
+		emulator.set_slow_motion(!emulator.is_slow_motion());
+	} else if window.is_key_pressed(minifb::Key::F6, minifb::KeyRepeat::No) {
+		emulator.reset_speed();
+	} else {
+		return;
+	}
+	window.set_title(&window_title(emulator));
+}
+
+/// Handles the hotkeys of the resets, save states, screenshots, the
+/// overlay and the log level. A failure is reported, but does not stop the
+/// game. The title is updated if they restart an ended ROM.
+#[inline]
+fn handle_utility_hotkeys(
+	window: &mut minifb::Window,
+	emulator: &mut emulator::Emulator,
+	#[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+	state_path: &std::path::Path,
+	#[cfg(feature = "tracing")] log_level: &mut LogLevel,
+) {
+	let had_ended = emulator.has_ended();
+	if window.is_key_pressed(minifb::Key::F1, minifb::KeyRepeat::No) {
+		emulator.cold_reset();
+	} else if window.is_key_pressed(minifb::Key::F2, minifb::KeyRepeat::No) {
+		emulator.warm_reset();
+	}
+
+	// Handle save state hotkeys
+	#[cfg(feature = "serde")]
+	if window.is_key_pressed(minifb::Key::F5, minifb::KeyRepeat::No) {
+		if let Err(e) = save_state(emulator, state_path) {
+			eprintln!("Failed to save the state: {e:#}");
+		}
+	} else if window.is_key_pressed(minifb::Key::F9, minifb::KeyRepeat::No) {
+		if let Err(e) = load_state(emulator, state_path) {
+			eprintln!("Failed to load the state: {e:#}");
+		}
+	}
+
+	#[cfg(feature = "screenshot")]
+	if window.is_key_pressed(minifb::Key::F12, minifb::KeyRepeat::No) {
+		match save_screenshot(emulator) {
+			Ok(path) => eprintln!("Saved {}", path.display()),
+			Err(e) => eprintln!("Failed to save a screenshot: {e:#}"),
+		}
+	}
+
+	if window.is_key_pressed(minifb::Key::F3, minifb::KeyRepeat::No) {
+		emulator.set_overlay_visible(!emulator.is_overlay_visible());
+	}
+
+	#[cfg(feature = "audio")]
+	if window.is_key_pressed(minifb::Key::M, minifb::KeyRepeat::No) {
+		if let Err(e) = emulator.set_muted(!emulator.is_muted()) {
+			eprintln!("Failed to unmute: {e:#}");
+		}
+	}
+
+	if emulator.has_ended() != had_ended {
+		window.set_title(&window_title(emulator));
+	}
+
+	// Handle log level hotkeys
+	#[cfg(feature = "tracing")]
+	for (key, more_verbose) in
+		[(minifb::Key::F7, false), (minifb::Key::F8, true)]
+	{
+		if window.is_key_pressed(key, minifb::KeyRepeat::No) {
+			if let Err(e) = log_level.step(more_verbose) {
+				eprintln!("Failed to change the log level: {e:#}");
+			}
+		}
+	}
+}
+
+/// Pauses the `emulator` while the `window` is not focused and resumes it
+/// when it is again. `auto_paused` remembers whether the pause is ours, so a
+/// pause by the user is left alone. Returns `true` while it is.
+fn update_auto_pause(
+	window: &mut minifb::Window,
+	emulator: &mut emulator::Emulator,
+	auto_paused: &mut bool,
+) -> bool {
+	let active = window.is_active();
+	if !active && !emulator.is_paused() {
+		emulator.set_paused(true);
+		*auto_paused = true;
+	} else if active && *auto_paused {
+		emulator.set_paused(false);
+		*auto_paused = false;
+	}
+	*auto_paused
+}
+
+/// Opens the gamepads, or reports why they are disabled.
+#[cfg(feature = "gamepad")]
+#[inline]
+fn open_gamepad() -> Option<input::GamepadInput> {
+	match input::GamepadInput::new() {
+		Ok(gamepad) => Some(gamepad),
+		Err(e) => {
+			eprintln!("Gamepads are disabled: {e:#}");
+			None
+		}
+	}
+}
+
 #[inline]
 fn process_window(
 	window: &mut minifb::Window,
 	emulator: &mut emulator::Emulator,
+	args: &Args,
+	mut debug_session: Option<&mut DebugSession>,
+	mut playlist: Option<&mut Playlist>,
+	#[cfg(feature = "tracing")] log_level: &mut LogLevel,
 ) -> Result<()> {
+	let mut fps_counter = FpsCounter::new();
+	let mut auto_paused = false;
+	let state_path = args.path.with_extension("state");
+	// Keys are not remapped while the window is open
+	let key_map = emulator.key_map().clone();
+	#[cfg(feature = "gamepad")]
+	let mut gamepad = open_gamepad();
+
 	while window.is_open() && !window.is_key_down(minifb::Key::Escape) {
-		// Get pressed key
-		let key = match window.get_keys_pressed(minifb::KeyRepeat::Yes) {
-			Some(keys) => {
-				if keys.is_empty() {
-					None
-				} else {
-					#[allow(clippy::indexing_slicing)]
-					Some(keys[0])
+		// Only process the window events while another window is focused
+		if !args.no_auto_pause
+			&& update_auto_pause(window, emulator, &mut auto_paused)
+		{
+			window.update();
+			std::thread::sleep(consts::SCREEN_DISPLAY_INTERVAL);
+			continue;
+		}
+
+		if let Some(playlist) = &mut playlist {
+			if !playlist.update(window, emulator)? {
+				break;
+			}
+		}
+		if let Some(session) = &mut debug_session {
+			session.poll(emulator)?;
+		}
+
+		handle_utility_hotkeys(
+			window,
+			emulator,
+			playlist.as_ref().map_or(&state_path, |p| &p.state_path),
+			#[cfg(feature = "tracing")]
+			log_level,
+		);
+
+		handle_speed_hotkeys(window, emulator);
+
+		// Rewind one frame per display while the rewind hotkey is held
+		if window.is_key_down(minifb::Key::Backspace) {
+			if emulator.can_display() {
+				emulator.rewind();
+				emulator.display(window).context("Failed to display.")?;
+			}
+			std::thread::sleep(emulator.time_until_display());
+			continue;
+		}
+
+		// Handle debug hotkeys. While paused, the ROM only runs one
+		// instruction per step and does not see any key presses.
+		if window.is_key_pressed(minifb::Key::P, minifb::KeyRepeat::No) {
+			emulator.set_paused(!emulator.is_paused());
+		}
+		if emulator.is_paused() {
+			if window.is_key_pressed(minifb::Key::N, minifb::KeyRepeat::Yes) {
+				if let Err(e) = emulator.step() {
+					#[cfg(feature = "tracing")]
+					tracing::error!("Halted: {}", e);
+					return Err(e).context("Failed to run an instruction.");
 				}
 			}
-			None => None,
-		};
+			if emulator.can_display() {
+				emulator.display(window).context("Failed to display.")?;
+			}
+			std::thread::sleep(emulator.time_until_display());
+			continue;
+		}
+
+		// A breakpoint pauses the emulator before the instruction runs
+		if let Some(session) = &debug_session {
+			session.check_breakpoint(emulator);
+		}
 
-		// Check that key is valid
-		let mut key_is_valid = false;
-		if let Some(k) = key {
-			if let Some(c) = keyboard::Keyboard::get_key_code(k) {
-				emulator.press_key(c);
-				key_is_valid = true;
+		// Pass the held keys to the emulator and advance it
+		#[cfg(not(feature = "gamepad"))]
+		let held_keys = input::InputSource::poll(&mut input::WindowInput::new(
+			window, &key_map,
+		));
+		#[cfg(feature = "gamepad")]
+		let held_keys = input::InputSource::poll(&mut input::Combined(
+			input::WindowInput::new(window, &key_map),
+			&mut gamepad,
+		));
+		match emulator.tick(held_keys) {
+			emulator::TickResult::Displayed => {
+				emulator.draw(window).context("Failed to display.")?;
+				emulator.record_rewind_state();
+				print_uninit_reads(emulator);
+				print_exec_trace_error(emulator);
+				if let Some(fps) = fps_counter.frame().filter(|_| args.fps) {
+					window.set_title(&format!(
+						"{} - {fps} FPS",
+						window_title(emulator)
+					));
+				}
+			}
+			emulator::TickResult::Halted(e) => {
+				#[cfg(feature = "tracing")]
+				tracing::error!("Halted: {}", e);
+				return Err(e).context("Failed to run an instruction.");
+			}
+			emulator::TickResult::Ended => {
+				window.set_title(&window_title(emulator));
 			}
+			// Sleep instead of spinning until something is due
+			emulator::TickResult::Idle => {
+				std::thread::sleep(emulator.time_until_next_event());
+			}
+			emulator::TickResult::Ran
+			| emulator::TickResult::Beep(_)
+			| emulator::TickResult::WaitingForKey => {}
 		}
+	}
+	Ok(())
+}
+
+/// A `debugger::Debugger` fed with the lines typed on stdin, see `--debug`.
+struct DebugSession {
+	debugger: debugger::Debugger,
+	lines: std::sync::mpsc::Receiver<String>,
+}
+
+impl DebugSession {
+	/// Pauses the `emulator` and starts reading commands from stdin.
+	fn start(emulator: &mut emulator::Emulator) -> Self {
+		emulator.set_paused(true);
 
-		// Reset pressed key, run instruction and display
-		if emulator.can_reset_pressed_key() && !key_is_valid {
-			emulator.reset_pressed_key();
+		// Reading stdin blocks, so do it on another thread
+		let (sender, lines) = std::sync::mpsc::channel();
+		std::thread::spawn(move || {
+			for line in std::io::stdin().lines() {
+				let Ok(line) = line else { break };
+				if sender.send(line).is_err() {
+					break;
+				}
+			}
+		});
+		eprintln!(
+			"Paused. Debugger commands: s, c, b <addr>, w <addr> [r|w] \
+			 [value], r, m <addr> <len>, d."
+		);
+		Self { debugger: debugger::Debugger::new(), lines }
+	}
+
+	/// Runs the commands typed since the last call and prints their output.
+	/// Returns `false` once stdin is closed.
+	fn poll(&mut self, emulator: &mut emulator::Emulator) -> Result<bool> {
+		loop {
+			let line = match self.lines.try_recv() {
+				Ok(line) => line,
+				Err(std::sync::mpsc::TryRecvError::Empty) => return Ok(true),
+				Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+					return Ok(false)
+				}
+			};
+			if line.trim().is_empty() {
+				continue;
+			}
+			match line.parse() {
+				Ok(command) => {
+					let output = self
+						.debugger
+						.execute(emulator, command)
+						.context("Failed to run an instruction.")?;
+					println!("{output}");
+				}
+				Err(e) => println!("{e}"),
+			}
 		}
-		if emulator.can_run_instruction() {
-			emulator.run_instruction();
+	}
+
+	/// Prints where the `emulator` stopped if it reached a breakpoint, see
+	/// `Debugger::check_breakpoint`.
+	fn check_breakpoint(&self, emulator: &mut emulator::Emulator) -> bool {
+		let text = self.debugger.check_breakpoint(emulator);
+		if let Some(text) = &text {
+			println!("{text}");
 		}
-		if emulator.can_display() {
-			emulator.display(window).context("Failed to display.")?;
+		text.is_some()
+	}
+}
+
+/// Runs the `emulator` without a display, controlled by the debugger only.
+/// Quits once stdin is closed.
+#[inline]
+fn process_debugger(
+	emulator: &mut emulator::Emulator,
+	session: &mut DebugSession,
+) -> Result<()> {
+	while session.poll(emulator)? {
+		if !emulator.is_paused()
+			&& emulator.can_run_instruction()
+			&& !session.check_breakpoint(emulator)
+		{
+			emulator
+				.run_instruction()
+				.context("Failed to run an instruction.")?;
 		}
+		std::thread::sleep(if emulator.is_paused() {
+			consts::SCREEN_DISPLAY_INTERVAL
+		} else {
+			emulator.cpu().time_until_next_instruction()
+		});
 	}
 	Ok(())
 }
 
+/// Changes the verbosity of the logs while the emulator runs.
+#[cfg(feature = "tracing")]
+struct LogLevel {
+	handle: tracing_subscriber::reload::Handle<
+		tracing_subscriber::EnvFilter,
+		tracing_subscriber::Registry,
+	>,
+	level: tracing::level_filters::LevelFilter,
+}
+
+#[cfg(feature = "tracing")]
+impl LogLevel {
+	/// Makes every target log one level more or less than the most verbose
+	/// one did before.
+	#[inline]
+	fn step(&mut self, more_verbose: bool) -> Result<()> {
+		use tracing::level_filters::LevelFilter;
+		const LEVELS: [LevelFilter; 6] = [
+			LevelFilter::OFF,
+			LevelFilter::ERROR,
+			LevelFilter::WARN,
+			LevelFilter::INFO,
+			LevelFilter::DEBUG,
+			LevelFilter::TRACE,
+		];
+
+		let index = LEVELS.iter().position(|&level| level == self.level);
+		let index = index.unwrap_or_default();
+		let index = if more_verbose {
+			(index + 1).min(LEVELS.len() - 1)
+		} else {
+			index.saturating_sub(1)
+		};
+		self.level = LEVELS[index];
+		self.handle
+			.reload(tracing_subscriber::EnvFilter::new(self.level.to_string()))
+			.context("Failed to reload the filter.")?;
+		eprintln!("Log level: {}", self.level);
+		Ok(())
+	}
+}
+
+/// Logs to `consts::LOGS_FILENAME` what the `filter` selects, else what
+/// `RUST_LOG` selects, else `consts::LOG_LEVEL`.
 #[cfg(feature = "tracing")]
 #[inline]
 fn set_tracing_subscriber(
-) -> Result<tracing_appender::non_blocking::WorkerGuard> {
+	filter: Option<&str>,
+) -> Result<(tracing_appender::non_blocking::WorkerGuard, LogLevel)> {
 	use tracing_subscriber::layer::SubscriberExt as _;
 
+	let filter = match filter {
+		Some(filter) => tracing_subscriber::EnvFilter::try_new(filter)
+			.context("Invalid log filter.")?,
+		None => tracing_subscriber::EnvFilter::try_from_default_env()
+			.unwrap_or_else(|_| {
+				tracing_subscriber::EnvFilter::new(consts::LOG_LEVEL)
+			}),
+	};
+	let level = filter
+		.max_level_hint()
+		.unwrap_or(tracing::level_filters::LevelFilter::TRACE);
+	let (filter, handle) = tracing_subscriber::reload::Layer::new(filter);
+
 	// Create the writer
 	let (writer, guard) = tracing_appender::non_blocking(
 		tracing_appender::rolling::never("", consts::LOGS_FILENAME),
@@ -95,7 +1153,7 @@ fn set_tracing_subscriber(
 
 	// Create and set the subscriber
 	let subscriber = tracing_subscriber::Registry::default()
-		.with(tracing_subscriber::EnvFilter::new(consts::LOG_LEVEL))
+		.with(filter)
 		.with(tracing_bunyan_formatter::JsonStorageLayer)
 		.with(tracing_bunyan_formatter::BunyanFormattingLayer::new(
 			consts::WINDOW_TITLE.to_owned(),
@@ -103,27 +1161,342 @@ fn set_tracing_subscriber(
 		));
 	tracing::subscriber::set_global_default(subscriber)
 		.context("Failed to set.")?;
-	Ok(guard)
+	Ok((guard, LogLevel { handle, level }))
+}
+
+/// Puts the terminal into raw mode with `stty` while alive, so keys reach
+/// the emulator as soon as they are typed, and restores the previous mode on
+/// drop.
+struct RawTerminal {
+	saved_mode: String,
+}
+
+impl RawTerminal {
+	#[inline]
+	fn enable() -> Result<Self> {
+		let output = std::process::Command::new("stty")
+			.arg("-g")
+			.stdin(std::process::Stdio::inherit())
+			.output()
+			.context("Failed to run stty.")?;
+		anyhow::ensure!(output.status.success(), "stdin is not a terminal.");
+		let saved_mode = String::from_utf8(output.stdout)
+			.context("Invalid stty output.")?
+			.trim()
+			.to_owned();
+
+		let status = std::process::Command::new("stty")
+			.args(["raw", "-echo"])
+			.stdin(std::process::Stdio::inherit())
+			.status()
+			.context("Failed to run stty.")?;
+		anyhow::ensure!(status.success(), "Failed to enable raw mode.");
+		Ok(Self { saved_mode })
+	}
+}
+
+impl Drop for RawTerminal {
+	fn drop(&mut self) {
+		let _ = std::process::Command::new("stty")
+			.arg(&self.saved_mode)
+			.stdin(std::process::Stdio::inherit())
+			.status();
+	}
+}
+
+/// Runs the `emulator` like `process_window`, but draws the screen to stdout
+/// and reads keys from stdin. Escape or Ctrl+C quits.
+#[inline]
+fn process_terminal(emulator: &mut emulator::Emulator) -> Result<()> {
+	use std::io::{Read as _, Write as _};
+
+	const ESCAPE: u8 = 0x1B;
+	const CTRL_C: u8 = 0x03;
+	// Longer than the usual delay before a held key starts repeating
+	const TERMINAL_KEY_HOLD: std::time::Duration =
+		std::time::Duration::from_millis(600);
+
+	let _raw_terminal = match RawTerminal::enable() {
+		Ok(raw_terminal) => Some(raw_terminal),
+		Err(e) => {
+			eprintln!("Keys are line buffered: {e:#}");
+			None
+		}
+	};
+
+	// Reading stdin blocks, so do it on another thread
+	let (sender, receiver) = std::sync::mpsc::channel();
+	std::thread::spawn(move || {
+		for byte in std::io::stdin().lock().bytes() {
+			match byte {
+				Ok(byte) if sender.send(byte).is_ok() => {}
+				_ => break,
+			}
+		}
+	});
+
+	let mut stdout = std::io::stdout();
+	// Clear the terminal and hide the cursor
+	write!(stdout, "\x1B[2J\x1B[?25l").context("Failed to write.")?;
+	// Terminals only report key presses, repeated while a key is held, so
+	// a key counts as released once it has not repeated for a while
+	let mut key_times = [None; 16];
+	let result = (|| loop {
+		while let Ok(byte) = receiver.try_recv() {
+			if byte == ESCAPE || byte == CTRL_C {
+				return Ok(());
+			}
+			if let Some(c) = keyboard::Keyboard::get_char_code(byte.into()) {
+				key_times[c as usize] = Some(std::time::Instant::now());
+			}
+		}
+		let mut held_keys = 0;
+		for (c, time) in (0..).zip(&mut key_times) {
+			if time.is_some_and(|t| t.elapsed() >= TERMINAL_KEY_HOLD) {
+				*time = None;
+			}
+			if time.is_some() {
+				held_keys |= 1 << c;
+			}
+		}
+
+		match emulator.tick(held_keys) {
+			emulator::TickResult::Displayed => {
+				// Raw mode does not return the carriage on a line feed
+				let frame =
+					emulator.screen().render_ascii().replace('\n', "\r\n");
+				write!(stdout, "\x1B[H{frame}")
+					.and_then(|()| stdout.flush())
+					.context("Failed to display.")?;
+				print_uninit_reads(emulator);
+				print_exec_trace_error(emulator);
+			}
+			emulator::TickResult::Halted(e) => {
+				return Err(e).context("Failed to run an instruction.");
+			}
+			emulator::TickResult::Idle | emulator::TickResult::Ended => {
+				std::thread::sleep(emulator.time_until_next_event());
+			}
+			emulator::TickResult::Ran
+			| emulator::TickResult::Beep(_)
+			| emulator::TickResult::WaitingForKey => {}
+		}
+	})();
+	// Show the cursor again
+	write!(stdout, "\x1B[?25h").context("Failed to write.")?;
+	result
 }
 
 fn main() -> Result<()> {
+	let mut args = <Args as clap::Parser>::parse();
 	#[cfg(feature = "tracing")]
-	let _guard = set_tracing_subscriber()
-		.context("Failed to set a tracing subscriber.")?;
+	let (_guard, mut log_level) =
+		set_tracing_subscriber(args.log_level.as_deref())
+			.context("Failed to set a tracing subscriber.")?;
+
+	if args.recent {
+		let recent =
+			read_recent_roms().context("Failed to read the recent ROMs.")?;
+		for path in recent {
+			println!("{}", path.display());
+		}
+		return Ok(());
+	}
+	if args.dump_font {
+		return print_font(args.font.as_deref())
+			.context("Failed to print the font.");
+	}
+	if let [source_path, rom_path] = args.assemble.as_slice() {
+		return assemble_file(source_path, rom_path)
+			.context("Failed to assemble.");
+	}
+	args.path = match args.playlist.first() {
+		Some(path) => path.clone(),
+		None => resolve_rom_path(args.rom_path.take())
+			.context("Failed to find the ROM.")?,
+	};
+
+	anyhow::ensure!(
+		!((args.terminal || args.debug) && is_stdin(&args.path)),
+		"The terminal frontend and the debugger read stdin, so they cannot \
+		 read the ROM from there too."
+	);
+	anyhow::ensure!(
+		!args.playlist.iter().any(|path| is_stdin(path)),
+		"The ROMs of a playlist cannot be read from stdin."
+	);
+	if args.disasm {
+		return print_disassembly(&args.path)
+			.context("Failed to disassemble the ROM.");
+	}
+	if args.info {
+		return print_info(&args.path).context("Failed to inspect the ROM.");
+	}
+	if let Some(report_path) = &args.analyze {
+		return write_analysis(&args.path, report_path)
+			.context("Failed to analyze the ROM.");
+	}
 
 	// Create the emulator
-	let mut emulator = emulator::Emulator::new();
-	prepare_emulator(&mut emulator)
+	let config = load_config(&args).context("Failed to load the config.")?;
+	let mut emulator = emulator::Emulator::with_config(&config);
+	prepare_emulator(&mut emulator, &args)
 		.context("Failed to prepare the emulator.")?;
+	if !is_stdin(&args.path) && args.playlist.is_empty() {
+		if let Err(e) = remember_rom(&args.path) {
+			eprintln!("Failed to remember the ROM: {e:#}");
+		}
+	}
+	let result = if let Some(cycles) = args.bench {
+		run_benchmark(&mut emulator, cycles)
+			.context("Failed to run the benchmark.")
+	} else {
+		// Muted, the output is only opened once the game is unmuted
+		#[cfg(feature = "audio")]
+		if !args.mute {
+			if let Err(e) = emulator.enable_audio() {
+				eprintln!("Audio is disabled: {e:#}");
+			}
+		}
+
+		let result = run(
+			&mut emulator,
+			&args,
+			#[cfg(feature = "tracing")]
+			&mut log_level,
+		);
+		#[cfg(feature = "profile")]
+		print_profile_report(&emulator);
+		result
+	};
+
+	#[cfg(feature = "serde")]
+	if let (Err(e), Some(path)) = (&result, &args.crash_dump) {
+		let error = e.chain().find_map(|e| e.downcast_ref::<cpu::CpuError>());
+		if let Some(error) = error {
+			match write_crash_dump(&emulator, error, path) {
+				Ok(()) => eprintln!("Wrote {}", path.display()),
+				Err(e) => eprintln!("Failed to write the crash dump: {e:#}"),
+			}
+		}
+	}
+	result
+}
+
+/// Writes the `Emulator::crash_dump` of the `error` to `path` as JSON.
+#[cfg(feature = "serde")]
+#[inline]
+fn write_crash_dump(
+	emulator: &emulator::Emulator,
+	error: &cpu::CpuError,
+	path: &std::path::Path,
+) -> Result<()> {
+	let json = serde_json::to_vec_pretty(&emulator.crash_dump(error))
+		.context("Failed to serialize the crash dump.")?;
+	std::fs::write(path, json).context("Failed to write the file.")
+}
+
+/// Prints the reads of uninitialized memory since the last call, see
+/// `--warn-uninit-reads`.
+#[inline]
+fn print_uninit_reads(emulator: &emulator::Emulator) {
+	for address in emulator.take_uninit_reads() {
+		eprintln!("Warning: read of uninitialized memory at {address:#05X}.");
+	}
+}
+
+/// Prints why the execution trace of `--exec-trace` stopped, once.
+#[inline]
+fn print_exec_trace_error(emulator: &mut emulator::Emulator) {
+	if let Some(e) = emulator.take_exec_trace_error() {
+		eprintln!("Warning: stopped the execution trace: {e}.");
+	}
+}
+
+/// Runs `cycles` instructions as fast as possible and prints one line like
+/// `cycles=1000000 secs=0.512345 ips=1951809`, easy to compare between
+/// builds.
+#[inline]
+fn run_benchmark(
+	emulator: &mut emulator::Emulator,
+	cycles: usize,
+) -> Result<()> {
+	// Waiting for the vertical blank would measure the wall clock instead of
+	// the interpreter
+	emulator.set_display_wait_quirk(quirks::DisplayWaitQuirk::Off);
+
+	let start = std::time::Instant::now();
+	emulator.run_cycles(cycles)?;
+	let secs = start.elapsed().as_secs_f64();
+	print_uninit_reads(emulator);
+	print_exec_trace_error(emulator);
+
+	#[allow(clippy::cast_precision_loss)]
+	let ips = cycles as f64 / secs;
+	println!("cycles={cycles} secs={secs:.6} ips={ips:.0}");
+	Ok(())
+}
+
+/// Runs the `emulator` in the terminal or in a window until the user quits.
+#[inline]
+fn run(
+	emulator: &mut emulator::Emulator,
+	args: &Args,
+	#[cfg(feature = "tracing")] log_level: &mut LogLevel,
+) -> Result<()> {
+	let mut debug_session = args.debug.then(|| DebugSession::start(emulator));
+	if args.terminal {
+		if let Some(session) = &mut debug_session {
+			return process_debugger(emulator, session)
+				.context("Failed to run the debugger.");
+		}
+		return process_terminal(emulator)
+			.context("Failed to process the terminal.");
+	}
+
+	let mut playlist = if args.playlist.is_empty() {
+		None
+	} else {
+		Some(Playlist::new(args).context("Failed to read the playlist.")?)
+	};
 
 	// Create and process a window
 	let mut window = minifb::Window::new(
-		consts::WINDOW_TITLE,
-		consts::WINDOW_WIDTH,
-		consts::WINDOW_HEIGHT,
+		&window_title(emulator),
+		emulator.screen().window_width(),
+		emulator.screen().window_height(),
 		minifb::WindowOptions::default(),
 	)
 	.context("Failed to create new window.")?;
-	process_window(&mut window, &mut emulator)
-		.context("Failed to process a window.")
+	if !args.no_vsync {
+		// `Screen::can_display` paces the frames already. The limit of
+		// minifb would also sleep in the middle of running instructions
+		window.limit_update_rate(None);
+		emulator.set_display_interval(consts::SCREEN_VSYNC_INTERVAL);
+	}
+	process_window(
+		&mut window,
+		emulator,
+		args,
+		debug_session.as_mut(),
+		playlist.as_mut(),
+		#[cfg(feature = "tracing")]
+		log_level,
+	)
+	.context("Failed to process a window.")
+}
+
+/// Prints how often each kind of instruction ran, see
+/// `Cpu::profile_report`.
+#[cfg(feature = "profile")]
+#[inline]
+fn print_profile_report(emulator: &emulator::Emulator) {
+	let report = emulator.profile_report();
+	let total: u64 = report.iter().map(|(_, count)| count).sum();
+	eprintln!("Instruction profile ({total} instructions):");
+	for (mnemonic, count) in report {
+		#[allow(clippy::cast_precision_loss)]
+		let share = count as f64 * 100.0 / total as f64;
+		eprintln!("  {mnemonic:<5} {count:>12} {share:>6.2}%");
+	}
 }
@@ -0,0 +1,81 @@
+//! Draws text onto the window buffer for the debug overlay, see
+//! `Emulator::set_overlay_visible`. `minifb` cannot draw text, so it uses
+//! the 4x5 digit sprites plus the few letters the overlay needs.
+
+/// Extra glyphs in the `consts::RAM_DIGIT_SPRITES` format: 5 rows, the high
+/// nibble of each byte being the pixels.
+const LETTERS: [(char, [u8; 5]); 6] = [
+	('I', [0xE0, 0x40, 0x40, 0x40, 0xE0]),
+	('P', [0xE0, 0x90, 0xE0, 0x80, 0x80]),
+	('S', [0x70, 0x80, 0x60, 0x10, 0xE0]),
+	('T', [0xE0, 0x40, 0x40, 0x40, 0x40]),
+	('V', [0x90, 0x90, 0x90, 0xA0, 0x40]),
+	('-', [0x00, 0x00, 0xF0, 0x00, 0x00]),
+];
+
+/// Glyph pixels plus one pixel of spacing.
+const CELL_WIDTH: usize = 5;
+const CELL_HEIGHT: usize = 6;
+
+/// Returns the glyph of `c`: a hex digit or one of `LETTERS`. Anything else
+/// is blank.
+fn glyph(c: char) -> [u8; 5] {
+	if let Some(digit) = c.to_digit(16) {
+		return crate::consts::RAM_DIGIT_SPRITES[digit as usize];
+	}
+	LETTERS
+		.iter()
+		.find(|(letter, _)| *letter == c)
+		.map_or([0; 5], |(_, glyph)| *glyph)
+}
+
+/// Draws the lines of `text` in the top left corner of the `window_buffer`,
+/// `window_width` pixels wide, on a box of `background` so it stays readable
+/// over the screen. Each glyph pixel is `scale` window pixels wide and high.
+pub fn draw_text(
+	window_buffer: &mut [u32],
+	window_width: usize,
+	text: &str,
+	scale: usize,
+	(foreground, background): (u32, u32),
+) {
+	let window_height = window_buffer.len() / window_width;
+	let columns = text.lines().map(|line| line.chars().count()).max();
+	let Some(columns) = columns else {
+		return;
+	};
+	let rows = text.lines().count();
+
+	// One glyph pixel of margin around the text
+	let box_width = ((columns * CELL_WIDTH + 1) * scale).min(window_width);
+	let box_height = ((rows * CELL_HEIGHT + 1) * scale).min(window_height);
+	for row in window_buffer.chunks_exact_mut(window_width).take(box_height) {
+		row[..box_width].fill(background);
+	}
+
+	for (row, line) in text.lines().enumerate() {
+		for (column, c) in line.chars().enumerate() {
+			let left = (column * CELL_WIDTH + 1) * scale;
+			let top = (row * CELL_HEIGHT + 1) * scale;
+			for (glyph_y, bits) in glyph(c).iter().enumerate() {
+				for glyph_x in 0..4 {
+					if bits & (0x80 >> glyph_x) == 0 {
+						continue;
+					}
+					let x = left + glyph_x * scale;
+					let y = top + glyph_y * scale;
+					if x + scale > window_width || y + scale > window_height {
+						continue;
+					}
+					for window_row in window_buffer
+						.chunks_exact_mut(window_width)
+						.skip(y)
+						.take(scale)
+					{
+						window_row[x..x + scale].fill(foreground);
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,99 @@
+//! The colors of the `Screen` pixels. A pixel has four values with the two
+//! XO-CHIP planes, see `Screen`, so a palette has four colors. Without
+//! XO-CHIP only the first two are ever used.
+
+/// The `0RGB` colors of the four pixel values, see `Screen::set_palette`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Palette {
+	/// The unlit pixels.
+	pub background: u32,
+	/// The pixels lit on the first plane only, or lit at all without
+	/// XO-CHIP.
+	pub plane1: u32,
+	/// The pixels lit on the second plane only.
+	pub plane2: u32,
+	/// The pixels lit on both planes.
+	pub both: u32,
+}
+
+impl Default for Palette {
+	fn default() -> Self {
+		PalettePreset::default().palette()
+	}
+}
+
+impl Palette {
+	/// Returns the colors indexed by the pixel value, for
+	/// `Screen::set_palette`.
+	#[inline]
+	#[must_use]
+	pub const fn to_array(self) -> [u32; 4] {
+		[self.background, self.plane1, self.plane2, self.both]
+	}
+}
+
+/// A built-in `Palette`, see `Config::palette`.
+///
+/// [`Config::palette`]: crate::config::Config::palette
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PalettePreset {
+	/// White on black, with Octo's colors for the second plane.
+	#[default]
+	Classic,
+	/// Octo's yellow and brown.
+	Octo,
+	/// The four greens of the original Game Boy.
+	Gameboy,
+	/// An amber monochrome monitor.
+	Amber,
+}
+
+impl PalettePreset {
+	#[must_use]
+	pub const fn palette(self) -> Palette {
+		let [background, plane1, plane2, both] = match self {
+			Self::Classic => [
+				crate::consts::BLACK_COLOR,
+				crate::consts::WHITE_COLOR,
+				crate::consts::XOCHIP_PLANE2_COLOR,
+				crate::consts::XOCHIP_BLEND_COLOR,
+			],
+			Self::Octo => [0x0099_6600, 0x00FF_CC00, 0x00FF_6600, 0x0066_2200],
+			Self::Gameboy => {
+				[0x009B_BC0F, 0x000F_380F, 0x008B_AC0F, 0x0030_6230]
+			}
+			Self::Amber => {
+				[0x0014_0C00, 0x00FF_B000, 0x0080_5800, 0x00FF_E0A0]
+			}
+		};
+		Palette { background, plane1, plane2, both }
+	}
+}
+
+impl std::str::FromStr for PalettePreset {
+	type Err = anyhow::Error;
+
+	/// Parses `classic`, `octo`, `gameboy` or `amber`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"classic" => Ok(Self::Classic),
+			"octo" => Ok(Self::Octo),
+			"gameboy" => Ok(Self::Gameboy),
+			"amber" => Ok(Self::Amber),
+			_ => Err(anyhow::anyhow!("Unknown palette: {s}.")),
+		}
+	}
+}
+
+impl std::fmt::Display for PalettePreset {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			Self::Classic => "classic",
+			Self::Octo => "octo",
+			Self::Gameboy => "gameboy",
+			Self::Amber => "amber",
+		};
+		f.write_str(name)
+	}
+}
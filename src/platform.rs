@@ -0,0 +1,22 @@
+/// The Chip-8 variant the `crate::emulator::Emulator` currently behaves as.
+///
+/// Never stored anywhere: it is always derived from the active quirks and
+/// display mode with `Emulator::platform`. Each variant extends the ones
+/// before it, which is how they are ordered.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Platform {
+	Chip8,
+	SuperChip,
+	XoChip,
+}
+
+impl std::fmt::Display for Platform {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			Self::Chip8 => "CHIP-8",
+			Self::SuperChip => "SCHIP",
+			Self::XoChip => "XO-CHIP",
+		};
+		f.write_str(name)
+	}
+}
@@ -0,0 +1,252 @@
+//! Behaviors that differ between Chip-8 interpreters. Some ROMs only run
+//! correctly under one of them, so they are configurable. In a config file
+//! they are spelled in snake case, e.g. `"shift_vy"`.
+
+/// Which register `8xy6` (SHR) and `8xyE` (SHL) shift.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShiftQuirk {
+	/// Shift vx in place and ignore vy, like SUPER-CHIP.
+	#[default]
+	ShiftVx,
+	/// Shift vy and store the result in vx, like the original COSMAC VIP.
+	ShiftVy,
+}
+
+/// What `Fx55` and `Fx65` do with `i` after storing or loading registers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryQuirk {
+	/// Leave `i` unchanged, like SUPER-CHIP.
+	#[default]
+	KeepIndex,
+	/// Advance `i` by `x + 1`, like the original COSMAC VIP.
+	IncrementIndex,
+}
+
+/// Which register `Bnnn` adds to the jump address.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JumpQuirk {
+	/// Jump to `nnn + v0`, like the original Chip-8.
+	#[default]
+	V0,
+	/// Jump to `xnn + vx`, where `x` is the highest nibble of `nnn`, like
+	/// SUPER-CHIP.
+	Vx,
+}
+
+/// Whether drawing waits for the vertical blank, which caps draws at 60 per
+/// second and reduces flicker in ROMs written for the original COSMAC VIP.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayWaitQuirk {
+	/// Draw immediately, like SUPER-CHIP.
+	#[default]
+	Off,
+	/// `Dxyn` waits for the next 60Hz frame, like the original COSMAC VIP.
+	/// `00E0` waits too if the clear waits, see `Cpu::set_clear_waits`.
+	///
+	/// [`Cpu::set_clear_waits`]: crate::cpu::Cpu::set_clear_waits
+	Draw,
+}
+
+/// When the sprites of `Dxyn` reach the screen and set vf.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawBatchQuirk {
+	/// Draw and set vf immediately, like every common interpreter.
+	#[default]
+	Immediate,
+	/// Collect the draws of a 60Hz frame and show them at its end, setting
+	/// vf if they erased any pixel that was shown, like interpreters that
+	/// only update the screen at the vertical blank. A sprite drawn and
+	/// erased within the frame never appears and collides with nothing.
+	/// `Dxyn` leaves vf unchanged, and clearing, scrolling or switching the
+	/// resolution shows the pending draws first.
+	///
+	/// With `DisplayWaitQuirk::Draw` too, a `Dxyn` waits for the vertical
+	/// blank, the end of the frame commits the draws before it, and then the
+	/// sprite starts a new batch. So every batch holds the one sprite drawn
+	/// at the start of its frame, and its vf arrives at the end of the frame,
+	/// after the instructions that follow have run. Turn the display wait
+	/// off to batch all the draws of a frame. In `TimerMode::WallClock` the
+	/// vertical blank and the end of the frame follow separate 60Hz clocks,
+	/// in the other modes they are the same moment.
+	///
+	/// [`TimerMode::WallClock`]: crate::timer::TimerMode::WallClock
+	Frame,
+}
+
+/// Whether `8xy1` (OR), `8xy2` (AND) and `8xy3` (XOR) reset vf.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogicQuirk {
+	/// Leave vf unchanged, like SUPER-CHIP and most modern interpreters.
+	#[default]
+	KeepVf,
+	/// Set vf to 0 afterwards, like the original COSMAC VIP.
+	ResetVf,
+}
+
+/// What happens to the pixels of a sprite beyond the screen edges. The
+/// starting coordinates of `Dxyn` always wrap around.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipQuirk {
+	/// Draw them on the opposite side, like XO-CHIP.
+	#[default]
+	Wrap,
+	/// Drop them, like the original COSMAC VIP and SUPER-CHIP. They cannot
+	/// collide then.
+	Clip,
+}
+
+/// Whether `Fx1E` reports `i` overflowing the 12-bit address space. `i` is
+/// wrapped to it either way.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexOverflowQuirk {
+	/// Leave vf unchanged, like most interpreters.
+	#[default]
+	Ignore,
+	/// Set vf to 1 if `i + vx` exceeds `0xFFF` and to 0 otherwise, like the
+	/// Amiga interpreter. Spacefight 2091! relies on it.
+	SetVf,
+}
+
+/// A preset of all quirks matching a Chip-8 variant, see `Config::profile`.
+///
+/// - `Chip8`: `ShiftVy`, `IncrementIndex`, `JumpQuirk::V0`,
+///   `DisplayWaitQuirk::Draw` with a waiting clear, `ResetVf` and `Clip`.
+/// - `SuperChip`: `ShiftVx`, `KeepIndex`, `JumpQuirk::Vx`,
+///   `DisplayWaitQuirk::Off`, `KeepVf` and `Clip`.
+/// - `XoChip`: `ShiftVy`, `IncrementIndex`, `JumpQuirk::V0`,
+///   `DisplayWaitQuirk::Off`, `KeepVf` and `Wrap`.
+///
+/// None of them reports index overflows, see `IndexOverflowQuirk::Ignore`,
+/// or batches draws, see `DrawBatchQuirk::Immediate`.
+/// Only `XoChip` enables the XO-CHIP instructions, see `Cpu::set_xochip`.
+///
+/// [`Config::profile`]: crate::config::Config::profile
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+pub enum QuirkProfile {
+	/// The original COSMAC VIP interpreter, the most compatible with legacy
+	/// ROMs.
+	#[default]
+	#[serde(rename = "chip8")]
+	Chip8,
+	/// SUPER-CHIP 1.1 on the HP 48.
+	#[serde(rename = "schip")]
+	SuperChip,
+	/// Octo's XO-CHIP.
+	#[serde(rename = "xochip")]
+	XoChip,
+}
+
+impl QuirkProfile {
+	#[inline]
+	#[must_use]
+	pub const fn shift_quirk(self) -> ShiftQuirk {
+		match self {
+			Self::Chip8 | Self::XoChip => ShiftQuirk::ShiftVy,
+			Self::SuperChip => ShiftQuirk::ShiftVx,
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn memory_quirk(self) -> MemoryQuirk {
+		match self {
+			Self::Chip8 | Self::XoChip => MemoryQuirk::IncrementIndex,
+			Self::SuperChip => MemoryQuirk::KeepIndex,
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn jump_quirk(self) -> JumpQuirk {
+		match self {
+			Self::Chip8 | Self::XoChip => JumpQuirk::V0,
+			Self::SuperChip => JumpQuirk::Vx,
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn logic_quirk(self) -> LogicQuirk {
+		match self {
+			Self::Chip8 => LogicQuirk::ResetVf,
+			Self::SuperChip | Self::XoChip => LogicQuirk::KeepVf,
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn clip_quirk(self) -> ClipQuirk {
+		match self {
+			Self::Chip8 | Self::SuperChip => ClipQuirk::Clip,
+			Self::XoChip => ClipQuirk::Wrap,
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn index_overflow_quirk(self) -> IndexOverflowQuirk {
+		IndexOverflowQuirk::Ignore
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn draw_batch_quirk(self) -> DrawBatchQuirk {
+		DrawBatchQuirk::Immediate
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn display_wait_quirk(self) -> DisplayWaitQuirk {
+		match self {
+			Self::Chip8 => DisplayWaitQuirk::Draw,
+			Self::SuperChip | Self::XoChip => DisplayWaitQuirk::Off,
+		}
+	}
+
+	/// Whether `00E0` waits for the vertical blank along with `Dxyn`, as on
+	/// the COSMAC VIP, whose clear routine syncs to the display too.
+	#[inline]
+	#[must_use]
+	pub const fn clear_waits(self) -> bool {
+		matches!(self, Self::Chip8)
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn xochip(self) -> bool {
+		matches!(self, Self::XoChip)
+	}
+}
+
+impl std::str::FromStr for QuirkProfile {
+	type Err = anyhow::Error;
+
+	/// Parses `chip8`, `schip` or `xochip`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"chip8" => Ok(Self::Chip8),
+			"schip" => Ok(Self::SuperChip),
+			"xochip" => Ok(Self::XoChip),
+			_ => Err(anyhow::anyhow!("Unknown profile: {s}.")),
+		}
+	}
+}
+
+impl std::fmt::Display for QuirkProfile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			Self::Chip8 => "chip8",
+			Self::SuperChip => "schip",
+			Self::XoChip => "xochip",
+		};
+		f.write_str(name)
+	}
+}
@@ -1,35 +1,404 @@
+/// Errors of the fallible `Ram` methods.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RamError {
+	/// `address` is past the end of the memory.
+	OutOfBounds { address: u16 },
+	/// The ROM of `size` bytes does not fit into the memory of `capacity`
+	/// bytes.
+	RomTooLarge { size: usize, capacity: usize },
+}
+
+impl std::fmt::Display for RamError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::OutOfBounds { address } => {
+				write!(f, "Address {address:#06X} is out of memory bounds.")
+			}
+			Self::RomTooLarge { size, capacity } => write!(
+				f,
+				"ROM too large: {size} bytes, at most {capacity} bytes fit."
+			),
+		}
+	}
+}
+
+impl std::error::Error for RamError {}
+
+/// What the program memory holds before the ROM is loaded, see
+/// `Ram::new_with_fill`.
+///
+/// Real hardware did not zero it, so ROMs that read memory they never wrote
+/// may only work by accident with `Self::Zero`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MemoryFill {
+	/// Every byte is `0x00`.
+	Zero,
+	/// Every byte is `0xFF`.
+	Ones,
+	/// Random bytes generated from the seed, so the same seed always
+	/// produces the same memory.
+	Random(u64),
+	/// The bytes repeated over and over.
+	Pattern(Vec<u8>),
+}
+
+impl std::str::FromStr for MemoryFill {
+	type Err = anyhow::Error;
+
+	/// Parses `zero`, `ones`, `random`, `random:<seed>` or
+	/// `pattern:<hex bytes>` like `pattern:DEADBEEF`. Plain `random` picks a
+	/// random seed.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.split_once(':') {
+			None if s == "zero" => Ok(Self::Zero),
+			None if s == "ones" => Ok(Self::Ones),
+			None if s == "random" => Ok(Self::Random(rand::random())),
+			Some(("random", seed)) => seed
+				.parse()
+				.map(Self::Random)
+				.map_err(|_| anyhow::anyhow!("Invalid seed: {seed}.")),
+			Some(("pattern", hex)) => {
+				anyhow::ensure!(
+					!hex.is_empty() && hex.len() % 2 == 0,
+					"Expected pairs of hex digits: {hex}."
+				);
+				(0..hex.len())
+					.step_by(2)
+					.map(|i| {
+						hex.get(i..i + 2)
+							.and_then(|pair| u8::from_str_radix(pair, 16).ok())
+							.ok_or_else(|| {
+								anyhow::anyhow!("Invalid pattern: {hex}.")
+							})
+					})
+					.collect::<anyhow::Result<_>>()
+					.map(Self::Pattern)
+			}
+			_ => Err(anyhow::anyhow!("Unknown memory fill: {s}.")),
+		}
+	}
+}
+
+impl std::fmt::Display for MemoryFill {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Zero => f.write_str("zero"),
+			Self::Ones => f.write_str("ones"),
+			Self::Random(seed) => write!(f, "random:{seed}"),
+			Self::Pattern(bytes) => {
+				f.write_str("pattern:")?;
+				bytes.iter().try_for_each(|byte| write!(f, "{byte:02X}"))
+			}
+		}
+	}
+}
+
+/// The kind of memory access a `Watchpoint` stops at.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Access {
+	Read,
+	Write,
+}
+
+impl std::fmt::Display for Access {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::Read => "read",
+			Self::Write => "write",
+		})
+	}
+}
+
+/// Stops execution when `address` is accessed the `access` way, but only if
+/// the byte read or written is `value`, when there is one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Watchpoint {
+	pub address: u16,
+	pub access: Access,
+	pub value: Option<u8>,
+}
+
+/// An access that matched a `Watchpoint`, see `Ram::take_watchpoint_hit`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WatchpointHit {
+	pub address: u16,
+	pub access: Access,
+	pub value: u8,
+}
+
 /// Ram of our Chip-8 emulator.
 ///
 /// The first `512` bytes (ending at the `0x200` address) reserved for
 /// hexadecimal digits (`consts::RAM_DIGIT_SPRITES`) and the VM implementation.
 /// Our ROM we must load starting at `0x200`, which is constant
 /// `consts::RAM_PROGRAM_START_ADDRESS`.
+///
+/// The memory is `consts::RAM_SIZE` bytes long, unless it is resized with
+/// `self.set_size`, e.g. to the `consts::RAM_XOCHIP_SIZE` of XO-CHIP.
+///
+/// The program memory from `0x200` on starts out as `self.fill`, the
+/// reserved memory before it is zeroed.
+///
+/// Every address written with `self.write` is remembered in `self.written`,
+/// so reads of memory that was never initialized by the font loader or the
+/// ROM can be reported (see `self.set_warn_uninit_reads`). The fill does not
+/// count as initialized.
+///
+/// Accesses by `self.read` and `self.write` are also checked against the
+/// `Watchpoint`s, which costs nothing while there are none.
 pub struct Ram {
-	memory: [u8; 4096],
+	memory: Vec<u8>,
+	/// One bit per address.
+	written: Vec<u64>,
+	warned: Vec<std::cell::Cell<u64>>,
+	warn_uninit_reads: bool,
+	/// Warned addresses not taken by `self.take_uninit_reads` yet.
+	uninit_reads: std::cell::RefCell<Vec<u16>>,
+	watchpoints: Vec<Watchpoint>,
+	watchpoint_hit: std::cell::Cell<Option<WatchpointHit>>,
+	fill: MemoryFill,
+}
+
+impl Default for Ram {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl Ram {
+	/// Creates a zeroed memory.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self::new_with_fill(MemoryFill::Zero)
+	}
+
+	/// Creates a memory whose program part holds the `fill`.
+	#[must_use]
+	pub fn new_with_fill(fill: MemoryFill) -> Self {
+		let mut ram = Self {
+			memory: vec![],
+			written: vec![],
+			warned: vec![],
+			uninit_reads: std::cell::RefCell::default(),
+			warn_uninit_reads: false,
+			watchpoints: vec![],
+			watchpoint_hit: std::cell::Cell::new(None),
+			fill,
+		};
+		ram.set_size(crate::consts::RAM_SIZE);
+		ram
+	}
+
+	#[inline]
 	#[must_use]
-	pub const fn new() -> Self {
-		Self { memory: [0; 4096] }
+	pub const fn fill(&self) -> &MemoryFill {
+		&self.fill
 	}
 
-	/// Loads `consts::RAM_DIGIT_SPRITES` into the first 80 bytes of memory.
+	/// Replaces the fill of the program memory, see `MemoryFill`. It is
+	/// applied right away, so set it before loading the ROM, and again by
+	/// every `self.clear`.
+	pub fn set_fill(&mut self, fill: MemoryFill) {
+		self.fill = fill;
+		self.apply_fill(0);
+	}
+
+	/// Writes `self.fill` to the program memory from `start` on. Every byte
+	/// only depends on its address, so memory added by `self.set_size`
+	/// continues the fill of the rest.
+	fn apply_fill(&mut self, start: usize) {
+		use rand::{Rng as _, SeedableRng as _};
+
+		let program_start = usize::from(crate::consts::RAM_ROM_START_ADDRESS);
+		let Some(bytes) = self.memory.get_mut(program_start..) else {
+			return;
+		};
+		let skipped = start.saturating_sub(program_start).min(bytes.len());
+		match &self.fill {
+			MemoryFill::Zero => bytes[skipped..].fill(0),
+			MemoryFill::Ones => bytes[skipped..].fill(0xFF),
+			MemoryFill::Random(seed) => {
+				let mut random = vec![0; bytes.len()];
+				rand::rngs::StdRng::seed_from_u64(*seed).fill(&mut random[..]);
+				bytes[skipped..].copy_from_slice(&random[skipped..]);
+			}
+			MemoryFill::Pattern(pattern) => {
+				let repeated = pattern.iter().cycle().skip(skipped);
+				for (byte, &value) in bytes[skipped..].iter_mut().zip(repeated)
+				{
+					*byte = value;
+				}
+			}
+		}
+	}
+
+	/// Returns the size of the memory in bytes.
+	#[inline]
+	#[must_use]
+	pub fn size(&self) -> usize {
+		self.memory.len()
+	}
+
+	/// Resizes the memory to `size` bytes. The contents of the addresses
+	/// that remain are kept, new ones hold `self.fill`.
 	///
-	/// # Debug panic
+	/// # Panics
 	///
-	/// If sprites already loaded.
+	/// If `size` is not a multiple of 64 in
+	/// `consts::RAM_SIZE..=consts::RAM_XOCHIP_SIZE`.
+	pub fn set_size(&mut self, size: usize) {
+		assert!(
+			(crate::consts::RAM_SIZE..=crate::consts::RAM_XOCHIP_SIZE)
+				.contains(&size)
+				&& size % 64 == 0,
+			"Invalid memory size {}.",
+			size
+		);
+		let old_size = self.memory.len();
+		self.memory.resize(size, 0);
+		self.apply_fill(old_size);
+		self.written.resize(size / 64, 0);
+		self.warned.resize_with(size / 64, Default::default);
+	}
+
+	/// Zeroes the reserved memory, fills the program memory with
+	/// `self.fill` and forgets which addresses were written, keeping the
+	/// configuration. Then loads the digit sprites again, see
+	/// `self.load_digit_sprites`.
+	pub fn clear(&mut self) {
+		self.memory.fill(0);
+		self.apply_fill(0);
+		self.written.fill(0);
+		for warned in &self.warned {
+			warned.set(0);
+		}
+		self.uninit_reads.get_mut().clear();
+		self.watchpoint_hit.set(None);
+		self.load_digit_sprites();
+	}
+
+	/// Returns the whole memory, for save states.
+	#[inline]
+	#[must_use]
+	pub fn memory(&self) -> &[u8] {
+		&self.memory
+	}
+
+	/// Returns the bytes at the `range` of addresses, for debuggers. Unlike
+	/// `self.read` it never warns about uninitialized reads.
+	///
+	/// # Errors
+	///
+	/// If `range` is not within the memory.
+	#[inline]
+	pub fn snapshot(
+		&self,
+		range: std::ops::Range<u16>,
+	) -> Result<&[u8], RamError> {
+		let address = range.end.saturating_sub(1);
+		self.memory
+			.get(usize::from(range.start)..usize::from(range.end))
+			.ok_or(RamError::OutOfBounds { address })
+	}
+
+	/// Replaces the whole memory, for save states. Every address counts as
+	/// written afterwards.
+	///
+	/// # Panics
+	///
+	/// If `memory` is not `self.size` bytes long.
+	pub fn set_memory(&mut self, memory: &[u8]) {
+		self.memory.copy_from_slice(memory);
+		self.written.fill(u64::MAX);
+	}
+
+	/// When enabled, `self.read` records the first read of each address
+	/// that was never written, see `self.take_uninit_reads`. Off by default.
+	pub fn set_warn_uninit_reads(&mut self, enabled: bool) {
+		self.warn_uninit_reads = enabled;
+	}
+
+	/// Returns and forgets the uninitialized addresses read since the last
+	/// call, in the order of the reads. Each address is returned once until
+	/// `self.clear`, frontends print them as warnings.
+	#[inline]
+	#[must_use]
+	pub fn take_uninit_reads(&self) -> Vec<u16> {
+		self.uninit_reads.take()
+	}
+
+	/// Returns the watchpoints in the order they were added.
+	#[inline]
+	#[must_use]
+	pub fn watchpoints(&self) -> &[Watchpoint] {
+		&self.watchpoints
+	}
+
+	/// Adds the `watchpoint`, or removes it if it is already there. Returns
+	/// `true` if it was added.
+	pub fn toggle_watchpoint(&mut self, watchpoint: Watchpoint) -> bool {
+		if let Some(index) =
+			self.watchpoints.iter().position(|w| *w == watchpoint)
+		{
+			self.watchpoints.remove(index);
+			false
+		} else {
+			self.watchpoints.push(watchpoint);
+			true
+		}
+	}
+
+	/// Returns and forgets the last access that matched a watchpoint.
+	/// Instruction fetches count as reads too.
+	#[inline]
+	pub fn take_watchpoint_hit(&self) -> Option<WatchpointHit> {
+		self.watchpoint_hit.take()
+	}
+
+	/// Loads `consts::RAM_DIGIT_SPRITES` and `consts::RAM_BIG_DIGIT_SPRITES`
+	/// at their addresses, below `consts::RAM_ROM_START_ADDRESS`. Whatever
+	/// is there is overwritten, so it may be called again to restore them.
 	#[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
 	pub fn load_digit_sprites(&mut self) {
-		debug_assert!(self.memory[..80].iter().all(|b| *b == 0));
-
-		let mut address = 0;
+		let mut address = crate::consts::RAM_DIGIT_SPRITES_ADDRESS;
 		for sprite in &crate::consts::RAM_DIGIT_SPRITES {
 			for part in sprite {
-				self.write(address, *part);
+				self.store(address, *part);
 				address += 1;
 			}
 		}
+
+		let mut address = crate::consts::RAM_BIG_DIGIT_SPRITES_ADDRESS;
+		for sprite in &crate::consts::RAM_BIG_DIGIT_SPRITES {
+			for part in sprite {
+				self.store(address, *part);
+				address += 1;
+			}
+		}
+	}
+
+	/// Replaces the `consts::RAM_DIGIT_SPRITES` loaded by
+	/// `self.load_digit_sprites` with a custom `font` of the same layout, so
+	/// `Fx29` keeps addressing digit `n` at `n * 5`.
+	pub fn load_font(&mut self, font: &[u8; crate::consts::RAM_FONT_SIZE]) {
+		let addresses = crate::consts::RAM_DIGIT_SPRITES_ADDRESS..;
+		for (address, &part) in addresses.zip(font) {
+			self.store(address, part);
+		}
+	}
+
+	/// Like `self.load_font`, but replaces the SUPER-CHIP
+	/// `consts::RAM_BIG_DIGIT_SPRITES`, so `Fx30` keeps addressing digit `n`
+	/// at `n * 10` after them.
+	pub fn load_big_font(
+		&mut self,
+		font: &[u8; crate::consts::RAM_BIG_FONT_SIZE],
+	) {
+		let addresses = crate::consts::RAM_BIG_DIGIT_SPRITES_ADDRESS..;
+		for (address, &part) in addresses.zip(font) {
+			self.store(address, part);
+		}
 	}
 
 	#[cfg_attr(
@@ -39,7 +408,14 @@ impl Ram {
 	#[inline]
 	#[must_use]
 	pub fn read(&self, address: u16) -> u8 {
-		self.memory[address as usize]
+		if self.warn_uninit_reads {
+			self.warn_if_uninit(address);
+		}
+		let value = self.memory[address as usize];
+		if !self.watchpoints.is_empty() {
+			self.check_watchpoints(address, Access::Read, value);
+		}
+		value
 	}
 
 	#[cfg_attr(
@@ -48,19 +424,128 @@ impl Ram {
 	)]
 	#[inline]
 	pub fn write(&mut self, address: u16, value: u8) {
+		if !self.watchpoints.is_empty() {
+			self.check_watchpoints(address, Access::Write, value);
+		}
+		self.store(address, value);
+	}
+
+	/// Like `self.write`, but ignores the watchpoints. Used to load the
+	/// sprites, which are not accesses of the program.
+	#[inline]
+	fn store(&mut self, address: u16, value: u8) {
 		self.memory[address as usize] = value;
+		self.written[address as usize / 64] |= 1 << (address % 64);
+	}
+
+	/// Like `self.read`, but returns an error instead of panicking if
+	/// `address` is out of bounds.
+	///
+	/// # Errors
+	///
+	/// If `address` is out of bounds.
+	#[inline]
+	pub fn try_read(&self, address: u16) -> Result<u8, RamError> {
+		self.check_bounds(address)?;
+		Ok(self.read(address))
+	}
+
+	/// Like `self.write`, but returns an error instead of panicking if
+	/// `address` is out of bounds.
+	///
+	/// # Errors
+	///
+	/// If `address` is out of bounds.
+	#[inline]
+	pub fn try_write(
+		&mut self,
+		address: u16,
+		value: u8,
+	) -> Result<(), RamError> {
+		self.check_bounds(address)?;
+		self.write(address, value);
+		Ok(())
+	}
+
+	#[inline]
+	fn check_bounds(&self, address: u16) -> Result<(), RamError> {
+		if (address as usize) < self.memory.len() {
+			Ok(())
+		} else {
+			Err(RamError::OutOfBounds { address })
+		}
 	}
 
-	/// Loads ROM into `self.memory` using `self.write` starting from
-	/// `consts::RAM_PROGRAM_START_ADDRESS`.
+	/// Checks that a ROM of `size` bytes fits into the memory after
+	/// `consts::RAM_ROM_START_ADDRESS`.
+	///
+	/// # Errors
+	///
+	/// If the ROM does not fit.
+	#[inline]
+	pub fn check_rom_size(&self, size: usize) -> Result<(), RamError> {
+		let start = crate::consts::RAM_ROM_START_ADDRESS;
+		let capacity = self.memory.len() - start as usize;
+		if size > capacity {
+			return Err(RamError::RomTooLarge { size, capacity });
+		}
+		Ok(())
+	}
+
+	/// Remembers the access for `self.take_watchpoint_hit` if it matches a
+	/// watchpoint.
+	#[cold]
+	fn check_watchpoints(&self, address: u16, access: Access, value: u8) {
+		let matches = self.watchpoints.iter().any(|w| {
+			w.address == address
+				&& w.access == access
+				&& (w.value.is_none() || w.value == Some(value))
+		});
+		if matches {
+			self.watchpoint_hit.set(Some(WatchpointHit {
+				address,
+				access,
+				value,
+			}));
+		}
+	}
+
+	/// Records `address` for `self.take_uninit_reads` if it was never
+	/// written and was not recorded yet.
+	#[cold]
+	fn warn_if_uninit(&self, address: u16) {
+		let mask = 1 << (address % 64);
+		let warned = &self.warned[address as usize / 64];
+		if self.written[address as usize / 64] & mask != 0
+			|| warned.get() & mask != 0
+		{
+			return;
+		}
+
+		warned.set(warned.get() | mask);
+		#[cfg(feature = "tracing")]
+		tracing::warn!("Read of uninitialized memory at {:#05X}", address);
+		self.uninit_reads.borrow_mut().push(address);
+	}
+
+	/// Loads ROM into `self.memory` starting from
+	/// `consts::RAM_ROM_START_ADDRESS`, marking it written but ignoring the
+	/// watchpoints like `self.store`.
+	///
+	/// # Errors
+	///
+	/// If the ROM does not fit into `self.memory`. Nothing is written then.
 	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
-	pub fn load_rom(&mut self, data: &[u8]) {
-		use std::convert::TryFrom as _;
-		for (i, &byte) in data.iter().enumerate() {
-			// `Result::unwrap` because always `i < 4096`
-			let address = crate::consts::RAM_ROM_START_ADDRESS
-				+ u16::try_from(i).unwrap();
-			self.write(address, byte);
+	pub fn load_rom(&mut self, data: &[u8]) -> Result<(), RamError> {
+		self.check_rom_size(data.len())?;
+		// Offsets rather than `u16` addresses, which would overflow past the
+		// last byte of a ROM that fills the 64KB XO-CHIP memory
+		let start = usize::from(crate::consts::RAM_ROM_START_ADDRESS);
+		let addresses = start..start + data.len();
+		self.memory[addresses.clone()].copy_from_slice(data);
+		for address in addresses {
+			self.written[address / 64] |= 1 << (address % 64);
 		}
+		Ok(())
 	}
 }
@@ -0,0 +1,73 @@
+/// A capped history of `emulator::EmulatorState`s for stepping backward.
+///
+/// Once `self.capacity` states are stored, pushing a new one drops the
+/// oldest, so the memory usage stays bounded.
+pub struct RewindBuffer {
+	states: std::collections::VecDeque<crate::emulator::EmulatorState>,
+	capacity: usize,
+}
+
+impl Default for RewindBuffer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl RewindBuffer {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::with_capacity(crate::consts::REWIND_CAPACITY)
+	}
+
+	#[must_use]
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self { states: std::collections::VecDeque::new(), capacity }
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Sets how many states are kept, dropping the oldest ones that no longer
+	/// fit. `0` disables rewinding.
+	pub fn set_capacity(&mut self, capacity: usize) {
+		self.capacity = capacity;
+		while self.states.len() > capacity {
+			self.states.pop_front();
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.states.len()
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.states.is_empty()
+	}
+
+	/// Stores the `state` as the most recent one.
+	pub fn push(&mut self, state: crate::emulator::EmulatorState) {
+		if self.capacity == 0 {
+			return;
+		}
+		if self.states.len() == self.capacity {
+			self.states.pop_front();
+		}
+		self.states.push_back(state);
+	}
+
+	/// Takes the most recent state out of the buffer.
+	pub fn pop(&mut self) -> Option<crate::emulator::EmulatorState> {
+		self.states.pop_back()
+	}
+
+	pub fn clear(&mut self) {
+		self.states.clear();
+	}
+}
@@ -0,0 +1,208 @@
+/// Why `Emulator::load_rom_checked` rejected a ROM.
+///
+/// [`Emulator::load_rom_checked`]: crate::emulator::Emulator::load_rom_checked
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RomError {
+	/// The ROM has no bytes, e.g. from a truncated download.
+	Empty,
+	/// The memory rejected the ROM, see `RamError::RomTooLarge`.
+	Ram(crate::ram::RamError),
+}
+
+impl std::fmt::Display for RomError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Empty => write!(f, "The ROM is empty."),
+			Self::Ram(e) => write!(f, "Failed to load the ROM: {e}"),
+		}
+	}
+}
+
+impl std::error::Error for RomError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Ram(source) => Some(source),
+			Self::Empty => None,
+		}
+	}
+}
+
+impl From<crate::ram::RamError> for RomError {
+	fn from(e: crate::ram::RamError) -> Self {
+		Self::Ram(e)
+	}
+}
+
+/// What a quick scan of a ROM found, see `Emulator::load_rom_checked`.
+///
+/// [`Emulator::load_rom_checked`]: crate::emulator::Emulator::load_rom_checked
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RomInfo {
+	/// Size of the ROM in bytes.
+	pub size: usize,
+	/// The address right after the ROM once it is loaded at
+	/// `consts::RAM_ROM_START_ADDRESS`.
+	pub end_address: usize,
+	/// The earliest platform that has all of `self.extended_instructions`.
+	pub platform: crate::platform::Platform,
+	/// The SUPER-CHIP and XO-CHIP instructions, see `extended_instructions`.
+	pub extended_instructions: Vec<(u16, u16)>,
+}
+
+impl RomInfo {
+	/// Scans the ROM `data` without loading it.
+	#[must_use]
+	pub fn scan(data: &[u8]) -> Self {
+		let extended_instructions = extended_instructions(data);
+		let platform = extended_instructions
+			.iter()
+			.map(|&(_, opcode)| required_platform(opcode))
+			.max()
+			.unwrap_or(crate::platform::Platform::Chip8);
+		Self {
+			size: data.len(),
+			end_address: usize::from(crate::consts::RAM_ROM_START_ADDRESS)
+				+ data.len(),
+			platform,
+			extended_instructions,
+		}
+	}
+
+	/// Whether the ROM needs SUPER-CHIP or XO-CHIP.
+	#[inline]
+	#[must_use]
+	pub fn is_extended(&self) -> bool {
+		!self.extended_instructions.is_empty()
+	}
+}
+
+/// Computes the checksum of the ROM `data` with `hash::fnv1a`.
+#[inline]
+#[must_use]
+pub fn checksum(data: &[u8]) -> u64 {
+	crate::hash::fnv1a(data)
+}
+
+/// Computes the CRC-32 of the ROM `data` with `hash::crc32`, for looking it
+/// up in ROM databases.
+#[inline]
+#[must_use]
+pub fn crc32(data: &[u8]) -> u32 {
+	crate::hash::crc32(data)
+}
+
+/// Returns the earliest platform that has the instruction `opcode`.
+#[must_use]
+pub fn required_platform(opcode: u16) -> crate::platform::Platform {
+	use crate::platform::Platform;
+
+	match crate::disasm::mnemonic(opcode) {
+		"PLANE" | "AUDIO" | "PITCH" | "SAVE" | "LOAD" => Platform::XoChip,
+		"LD" if opcode == 0xF000 => Platform::XoChip,
+		"SCD" | "SCR" | "SCL" | "LOW" | "HIGH" => Platform::SuperChip,
+		"DRW" if opcode.trailing_zeros() >= 4 => Platform::SuperChip,
+		"LD" if matches!(opcode & 0xF0FF, 0xF030 | 0xF075 | 0xF085) => {
+			Platform::SuperChip
+		}
+		_ => Platform::Chip8,
+	}
+}
+
+/// Finds the SUPER-CHIP and XO-CHIP instructions of the ROM `data`, as
+/// addresses and opcodes in ascending order. See `required_platform`.
+///
+/// Only the code reachable from `consts::RAM_ROM_START_ADDRESS` through
+/// jumps, calls and skips is scanned, so sprites that happen to look like
+/// extended instructions are not reported. Targets of `Bnnn` are unknown
+/// and not followed.
+#[must_use]
+pub fn extended_instructions(data: &[u8]) -> Vec<(u16, u16)> {
+	let start = crate::consts::RAM_ROM_START_ADDRESS;
+	let read = |address: u16| {
+		let offset = usize::from(address.checked_sub(start)?);
+		match data.get(offset..offset + 2)? {
+			&[high, low] => Some(u16::from_be_bytes([high, low])),
+			_ => None,
+		}
+	};
+
+	let mut visited = std::collections::BTreeSet::new();
+	let mut pending = vec![start];
+	let mut found = vec![];
+	while let Some(address) = pending.pop() {
+		if !visited.insert(address) {
+			continue;
+		}
+		// Unknown opcodes are data, so the code cannot go on there
+		let Some(opcode) = read(address)
+			.filter(|&opcode| crate::disasm::mnemonic(opcode) != "DW")
+		else {
+			continue;
+		};
+		if required_platform(opcode) != crate::platform::Platform::Chip8 {
+			found.push((address, opcode));
+		}
+
+		let length = if opcode == 0xF000 { 4 } else { 2 };
+		let next = address.wrapping_add(length);
+		let nnn = opcode & 0x0FFF;
+		match opcode & 0xF000 {
+			0x1000 => pending.push(nnn),
+			0x2000 => pending.extend([nnn, next]),
+			// `5xy2` and `5xy3` do not skip
+			0x5000 if opcode & 0x000F != 0 => pending.push(next),
+			0x3000 | 0x4000 | 0x5000 | 0x9000 | 0xE000 => {
+				pending.extend([next, next.wrapping_add(2)]);
+			}
+			0xB000 => {}
+			_ if opcode == 0x00EE => {}
+			_ => pending.push(next),
+		}
+	}
+	found.sort_unstable();
+	found
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::platform::Platform;
+
+	#[test]
+	fn crc32_matches_the_check_value() {
+		assert_eq!(super::crc32(b"123456789"), 0xCBF4_3926);
+	}
+
+	#[test]
+	fn finds_extended_instructions_only_in_code() {
+		let rom = [
+			0x00, 0xFF, // 200: HIGH
+			0x22, 0x08, // 202: CALL #208
+			0x12, 0x04, // 204: JP #204
+			0xF0, 0x00, // 206: a sprite row, not LD I, LONG
+			0xD0, 0x10, // 208: DRW V0, V1, 0
+			0x00, 0xEE, // 20A: RET
+		];
+		assert_eq!(super::extended_instructions(&rom), [
+			(0x200, 0x00FF),
+			(0x208, 0xD010)
+		]);
+		assert_eq!(super::required_platform(0xF000), Platform::XoChip);
+		assert_eq!(super::required_platform(0x00FF), Platform::SuperChip);
+		assert_eq!(super::required_platform(0xD015), Platform::Chip8);
+	}
+
+	#[test]
+	fn scans_the_size_and_the_platform() {
+		let info = super::RomInfo::scan(&[0xF0, 0x00, 0x12, 0x02, 0x12, 0x04]);
+		assert_eq!((info.size, info.end_address), (6, 0x206));
+		assert_eq!(info.platform, Platform::XoChip);
+		assert!(info.is_extended());
+
+		let info = super::RomInfo::scan(&[]);
+		assert_eq!(
+			(info.end_address, info.platform),
+			(0x200, Platform::Chip8)
+		);
+		assert!(!info.is_extended());
+	}
+}
@@ -1,36 +1,626 @@
-use anyhow::{Context as _, Result};
+#[cfg(feature = "window")]
+use anyhow::Context as _;
+use anyhow::Result;
+
+/// Snapshot of the `Screen` pixels, see `Screen::save_state`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScreenState {
+	hires: bool,
+	planes: u8,
+	buffer: Vec<u8>,
+}
 
 /// Represents the `emulator::Emulator` screen.
 ///
 /// Stores a `buffer` which contains `0` and `1` for each pixel on the screen.
+/// The buffer is sized for the SUPER-CHIP high resolution, but only the first
+/// `width() * height()` pixels of the active resolution are used.
+///
+/// XO-CHIP has a second plane of pixels, so a pixel is the bitmask of the
+/// planes it is lit on: `1` for the first, `2` for the second and `3` for
+/// both. Drawing, clearing and scrolling only affect the planes selected
+/// with `self.select_planes`, the first one by default.
 pub struct Screen {
-	buffer: [u8; crate::consts::SCREEN_SIZE],
-	last_display_time: std::time::Instant,
+	buffer: [u8; crate::consts::SCREEN_HIRES_SIZE],
+	/// The size of the active resolution in pixels, see `self.set_hires`.
+	width: usize,
+	height: usize,
+	planes: u8,
+	clip_quirk: crate::quirks::ClipQuirk,
+	draw_batch_quirk: crate::quirks::DrawBatchQuirk,
+	/// `self.buffer` with the draws batched since the last
+	/// `self.commit_draws`, see `DrawBatchQuirk::Frame`.
+	staged: Option<Box<[u8; crate::consts::SCREEN_HIRES_SIZE]>>,
+	/// The color of each pixel value.
+	palette: [u32; 4],
+	window_multiplier: usize,
+	window_buffer: Box<[u32]>,
+	overlay: Option<String>,
+	dirty_rows: Option<std::ops::Range<usize>>,
+	last_display_time: crate::time::Instant,
+	display_interval: std::time::Duration,
+	/// Erased pixels since the last clear, if counted.
+	collisions: Option<u64>,
+	ghosting: Option<Box<Ghosting>>,
+	/// Whether every other window row is dimmed, see `self.set_crt`.
+	crt: bool,
+	/// Whether the window is framed while beeping, see
+	/// `self.set_visual_beep`.
+	visual_beep: bool,
+	/// Whether the buzzer sounded at the last `self.set_beeping`.
+	beeping: bool,
+}
+
+/// Pixel persistence for `Screen::set_ghosting`. Indices are the ones of
+/// `Screen::buffer`.
+struct Ghosting {
+	/// The value of each pixel at the last display, or the one it fades
+	/// from.
+	values: [u8; crate::consts::SCREEN_HIRES_SIZE],
+	/// Displays left until each pixel has faded out.
+	fades: [u8; crate::consts::SCREEN_HIRES_SIZE],
+}
+
+impl Ghosting {
+	fn new() -> Self {
+		Self {
+			values: [0; crate::consts::SCREEN_HIRES_SIZE],
+			fades: [0; crate::consts::SCREEN_HIRES_SIZE],
+		}
+	}
+
+	/// Starts fading the pixels of `buffer` that turned off since the last
+	/// call and advances the ones already fading. Returns whether any of
+	/// them looks different now.
+	#[cfg(feature = "window")]
+	fn update(&mut self, buffer: &[u8]) -> bool {
+		let mut changed = false;
+		let pixels = buffer.iter().zip(&mut self.values).zip(&mut self.fades);
+		for ((&pixel, value), fade) in pixels {
+			if pixel != 0 {
+				*value = pixel;
+				*fade = 0;
+			} else if *value != 0 {
+				if *fade == 0 {
+					*fade = crate::consts::SCREEN_GHOST_FRAMES;
+				} else {
+					*fade -= 1;
+					if *fade == 0 {
+						*value = 0;
+					}
+				}
+				changed = true;
+			}
+		}
+		changed
+	}
+
+	/// Returns the color of the unlit `index` pixel: the one it fades from,
+	/// blended into `background`, or `None` if it is not fading.
+	fn color(&self, index: usize, palette: &[u32; 4]) -> Option<u32> {
+		let fade = u32::from(self.fades[index]);
+		if fade == 0 {
+			return None;
+		}
+		let from = palette[usize::from(self.values[index])];
+		let total = u32::from(crate::consts::SCREEN_GHOST_FRAMES) + 1;
+		Some(
+			(0..3)
+				.map(|channel| {
+					let shift = channel * 8;
+					let from = (from >> shift) & 0xFF;
+					let to = (palette[0] >> shift) & 0xFF;
+					((from * fade + to * (total - fade)) / total) << shift
+				})
+				.sum(),
+		)
+	}
+}
+
+impl Default for Screen {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl Screen {
 	#[must_use]
 	pub fn new() -> Self {
-		Self {
-			buffer: [0; crate::consts::SCREEN_SIZE],
-			last_display_time: std::time::Instant::now(),
+		let (width, height) = Self::resolution(false);
+		let mut screen = Self {
+			buffer: [0; crate::consts::SCREEN_HIRES_SIZE],
+			width,
+			height,
+			planes: 1,
+			clip_quirk: crate::quirks::ClipQuirk::default(),
+			draw_batch_quirk: crate::quirks::DrawBatchQuirk::default(),
+			staged: None,
+			palette: [
+				crate::consts::BLACK_COLOR,
+				crate::consts::WHITE_COLOR,
+				crate::consts::XOCHIP_PLANE2_COLOR,
+				crate::consts::XOCHIP_BLEND_COLOR,
+			],
+			window_multiplier: crate::consts::WINDOW_MULTIPLIER,
+			window_buffer: Box::default(),
+			overlay: None,
+			dirty_rows: None,
+			last_display_time: crate::time::Instant::now(),
+			display_interval: crate::consts::SCREEN_DISPLAY_INTERVAL,
+			collisions: None,
+			ghosting: None,
+			crt: false,
+			visual_beep: false,
+			beeping: false,
+		};
+		screen.window_buffer =
+			vec![0; screen.window_size()].into_boxed_slice();
+		screen.mark_all_dirty();
+		screen
+	}
+
+	/// Clears the screen and returns to the low resolution and the first
+	/// plane, keeping the colors.
+	pub fn reset(&mut self) {
+		self.planes = 1;
+		self.set_hires(false);
+		self.last_display_time = crate::time::Instant::now();
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn clip_quirk(&self) -> crate::quirks::ClipQuirk {
+		self.clip_quirk
+	}
+
+	#[inline]
+	pub fn set_clip_quirk(&mut self, quirk: crate::quirks::ClipQuirk) {
+		self.clip_quirk = quirk;
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn draw_batch_quirk(&self) -> crate::quirks::DrawBatchQuirk {
+		self.draw_batch_quirk
+	}
+
+	/// Sets when drawn sprites reach `self.buffer`. Draws still batched
+	/// are shown right away.
+	#[inline]
+	pub fn set_draw_batch_quirk(
+		&mut self,
+		quirk: crate::quirks::DrawBatchQuirk,
+	) {
+		let _ = self.commit_draws();
+		self.draw_batch_quirk = quirk;
+	}
+
+	/// Copies the draws batched by `DrawBatchQuirk::Frame` into
+	/// `self.buffer`. Returns whether they erased any of its pixels, or
+	/// `None` if nothing was drawn since the last call.
+	pub fn commit_draws(&mut self) -> Option<bool> {
+		let staged = self.staged.take()?;
+		let (width, size) = (self.width(), self.width() * self.height());
+		let (shown, staged) = (&self.buffer[..size], &staged[..size]);
+
+		let changed = |(shown, staged): (&u8, &u8)| shown != staged;
+		let pixels = || shown.iter().zip(staged);
+		let changed_rows = pixels()
+			.position(changed)
+			.zip(pixels().rposition(changed))
+			.map(|(first, last)| first / width..last / width + 1);
+		let erased: u32 = pixels()
+			.map(|(shown, staged)| (shown & !staged).count_ones())
+			.sum();
+		self.buffer[..size].copy_from_slice(staged);
+		if let Some(rows) = changed_rows {
+			self.mark_dirty(rows);
+		}
+		if let Some(collisions) = &mut self.collisions {
+			*collisions += u64::from(erased);
 		}
+		Some(erased != 0)
+	}
+
+	/// Returns the `0x00RRGGBB` color of the pixels that are on.
+	#[inline]
+	#[must_use]
+	pub const fn foreground_color(&self) -> u32 {
+		self.palette[1]
+	}
+
+	#[inline]
+	pub fn set_foreground_color(&mut self, color: u32) {
+		self.palette[1] = color;
+		self.mark_all_dirty();
+	}
+
+	/// Returns the `0x00RRGGBB` color of the pixels that are off.
+	#[inline]
+	#[must_use]
+	pub const fn background_color(&self) -> u32 {
+		self.palette[0]
+	}
+
+	#[inline]
+	pub fn set_background_color(&mut self, color: u32) {
+		self.palette[0] = color;
+		self.mark_all_dirty();
+	}
+
+	/// Returns the `0x00RRGGBB` colors of the four pixel values: off, lit on
+	/// the first plane, on the second plane and on both.
+	#[inline]
+	#[must_use]
+	pub const fn palette(&self) -> [u32; 4] {
+		self.palette
+	}
+
+	#[inline]
+	pub fn set_palette(&mut self, palette: [u32; 4]) {
+		self.palette = palette;
+		self.mark_all_dirty();
+	}
+
+	/// Returns the selected planes one by one, as the single-plane bitmasks
+	/// `self.draw_byte` takes.
+	#[inline]
+	pub fn selected_planes(&self) -> impl Iterator<Item = u8> {
+		let planes = self.planes;
+		[1, 2].iter().copied().filter(move |plane| planes & plane != 0)
+	}
+
+	/// Selects the planes affected by drawing, clearing and scrolling with
+	/// the bitmask `planes` (`Fn01`, XO-CHIP). Only its lowest 2 bits are
+	/// used, `0` selects no plane at all.
+	#[inline]
+	pub fn select_planes(&mut self, planes: u8) {
+		self.planes = planes & 0b11;
+	}
+
+	/// Returns how many window pixels wide and high a low resolution pixel
+	/// is. High resolution pixels are half of it.
+	#[inline]
+	#[must_use]
+	pub const fn window_multiplier(&self) -> usize {
+		self.window_multiplier
+	}
+
+	/// Sets the size of a low resolution pixel in the window. Recreate the
+	/// window with the new `self.window_width` and `self.window_height`
+	/// afterwards. With an odd `multiplier` high resolution pixels are
+	/// alternately rounded down and up.
+	///
+	/// # Panics
+	///
+	/// If `multiplier` is not in
+	/// `consts::WINDOW_MIN_MULTIPLIER..=consts::WINDOW_MAX_MULTIPLIER`.
+	pub fn set_window_multiplier(&mut self, multiplier: usize) {
+		assert!(
+			(crate::consts::WINDOW_MIN_MULTIPLIER
+				..=crate::consts::WINDOW_MAX_MULTIPLIER)
+				.contains(&multiplier),
+			"Invalid window multiplier {}.",
+			multiplier
+		);
+		self.window_multiplier = multiplier;
+		self.window_buffer = vec![0; self.window_size()].into_boxed_slice();
+		self.mark_all_dirty();
+	}
+
+	/// Sets the text drawn over the screen by `self.display`, or removes it.
+	/// It is only drawn onto the window, never into `self.buffer`.
+	pub fn set_overlay(&mut self, text: Option<String>) {
+		if text != self.overlay {
+			// The rows under the previous text need to be drawn again
+			self.mark_all_dirty();
+			self.overlay = text;
+		}
+	}
+
+	/// Returns the width of the window the screen is displayed in. It is the
+	/// same in both resolutions.
+	#[inline]
+	#[must_use]
+	pub const fn window_width(&self) -> usize {
+		crate::consts::SCREEN_WIDTH * self.window_multiplier
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn window_height(&self) -> usize {
+		crate::consts::SCREEN_HEIGHT * self.window_multiplier
+	}
+
+	#[inline]
+	const fn window_size(&self) -> usize {
+		self.window_width() * self.window_height()
+	}
+
+	/// Returns the pixels row by row, `0` for off and the bitmask of the
+	/// planes it is lit on otherwise (just `1` without XO-CHIP), so frontends
+	/// can render the screen themselves.
+	#[inline]
+	#[must_use]
+	pub fn buffer(&self) -> &[u8] {
+		&self.buffer[..self.width() * self.height()]
+	}
+
+	/// Returns how many pixels sprites erased since the screen was last
+	/// cleared, or `None` if they are not counted.
+	#[inline]
+	#[must_use]
+	pub const fn collision_count(&self) -> Option<u64> {
+		self.collisions
+	}
+
+	/// Counts the pixels erased by sprites, for debugging flickering or
+	/// corrupted graphics. With the `tracing` feature, every one of them is
+	/// logged with its coordinates too. Nothing is counted by default.
+	#[inline]
+	pub fn set_collision_counting(&mut self, enabled: bool) {
+		self.collisions = enabled.then_some(0);
+	}
+
+	/// Makes pixels that turn off fade into the background over
+	/// `consts::SCREEN_GHOST_FRAMES` displays instead of disappearing at
+	/// once, which hides most of the flicker of sprites that are erased and
+	/// drawn again every frame. Off by default.
+	#[inline]
+	pub fn set_ghosting(&mut self, enabled: bool) {
+		self.ghosting = enabled.then(|| Box::new(Ghosting::new()));
+	}
+
+	/// Dims every other row of the window like the scanlines of a CRT. Only
+	/// the window is affected, and only while a pixel spans several window
+	/// rows. Off by default.
+	#[inline]
+	pub fn set_crt(&mut self, enabled: bool) {
+		self.crt = enabled;
+		self.mark_all_dirty();
+	}
+
+	/// Frames the window in `consts::SCREEN_VISUAL_BEEP_COLOR` while the
+	/// buzzer sounds, for playing muted or without hearing it. Like the
+	/// overlay, the frame is only drawn onto the window. Off by default.
+	#[inline]
+	pub fn set_visual_beep(&mut self, enabled: bool) {
+		self.visual_beep = enabled;
+		self.mark_all_dirty();
+	}
+
+	/// Tells whether the buzzer sounds, so the frame of
+	/// `self.set_visual_beep` appears and disappears with the next draw.
+	pub fn set_beeping(&mut self, beeping: bool) {
+		if beeping != self.beeping {
+			self.beeping = beeping;
+			if self.visual_beep {
+				// The rows under the frame need to be drawn again
+				self.mark_all_dirty();
+			}
+		}
+	}
+
+	/// Returns the width of `self.buffer` in pixels.
+	#[inline]
+	#[must_use]
+	pub const fn width(&self) -> usize {
+		self.width
+	}
+
+	/// Returns the height of `self.buffer` in pixels.
+	#[inline]
+	#[must_use]
+	pub const fn height(&self) -> usize {
+		self.height
+	}
+
+	/// Returns the width and the height of the 128x64 high resolution if
+	/// `hires`, of the 64x32 one otherwise.
+	#[inline]
+	#[must_use]
+	pub const fn resolution(hires: bool) -> (usize, usize) {
+		if hires {
+			(
+				crate::consts::SCREEN_HIRES_WIDTH,
+				crate::consts::SCREEN_HIRES_HEIGHT,
+			)
+		} else {
+			(crate::consts::SCREEN_WIDTH, crate::consts::SCREEN_HEIGHT)
+		}
+	}
+
+	/// Whether the SUPER-CHIP 128x64 high resolution is active.
+	#[inline]
+	#[must_use]
+	pub const fn is_hires(&self) -> bool {
+		self.width == crate::consts::SCREEN_HIRES_WIDTH
+	}
+
+	/// Switches between the 64x32 and 128x64 resolutions (`00FE` and
+	/// `00FF`), clearing every plane of the screen.
+	#[inline]
+	pub fn set_hires(&mut self, hires: bool) {
+		(self.width, self.height) = Self::resolution(hires);
+		// Rows of the previous resolution mean nothing in the new one
+		self.dirty_rows = None;
+		self.staged = None;
+		self.buffer.fill(0);
+		self.mark_all_dirty();
+		self.reset_collision_count();
+		if let Some(ghosting) = &mut self.ghosting {
+			**ghosting = Ghosting::new();
+		}
+	}
+
+	/// Hashes `self.buffer` with `hash::fnv1a`, so a screen can be compared
+	/// against a known one without storing all of its pixels.
+	#[inline]
+	#[must_use]
+	pub fn hash(&self) -> u64 {
+		crate::hash::fnv1a(self.buffer())
 	}
 
-	/// Clears the screen by setting every bit of `self.buffer` to 0.
+	/// Clears the selected planes of the screen, so every pixel of
+	/// `self.buffer` is 0 with just the first plane.
 	pub fn clear(&mut self) {
+		let planes = self.planes;
 		for pixel in &mut self.buffer {
-			*pixel = 0;
+			*pixel &= !planes;
 		}
+		self.mark_all_dirty();
+		self.reset_collision_count();
+	}
+
+	#[inline]
+	fn reset_collision_count(&mut self) {
+		if let Some(collisions) = &mut self.collisions {
+			*collisions = 0;
+		}
+	}
+
+	/// Whether the buffer changed since the last `self.display`.
+	#[inline]
+	#[must_use]
+	pub const fn is_dirty(&self) -> bool {
+		self.dirty_rows.is_some()
+	}
+
+	/// Remembers that `rows` of the active resolution changed, growing the
+	/// dirty range to cover them.
+	#[inline]
+	fn mark_dirty(&mut self, rows: std::ops::Range<usize>) {
+		self.dirty_rows = Some(match self.dirty_rows.take() {
+			Some(dirty) => {
+				dirty.start.min(rows.start)..dirty.end.max(rows.end)
+			}
+			None => rows,
+		});
 	}
 
-	/// Displays the `self.buffer` on the [window](minifb::Window).
+	#[inline]
+	fn mark_all_dirty(&mut self) {
+		self.mark_dirty(0..self.height());
+	}
+
+	/// Captures the resolution, the selected planes and the pixels.
+	#[must_use]
+	pub fn save_state(&self) -> ScreenState {
+		ScreenState {
+			hires: self.is_hires(),
+			planes: self.planes,
+			buffer: self.buffer().to_vec(),
+		}
+	}
+
+	/// Restores the pixels captured by `self.save_state`.
 	///
-	/// Since the original screen size is very small, we display it in a large
-	/// window by incrementing each pixel by `consts::WINDOW_MULTIPLIER` and
-	/// translating `0` and `1` into `consts::BLACK_COLOR` and
-	/// `consts::WHITE_COLOR` respectively. All this is stored in
-	/// `window_argb_buffer` variable.
+	/// # Errors
+	///
+	/// If the number of pixels does not match the resolution. Nothing is
+	/// restored then.
+	pub fn load_state(&mut self, state: &ScreenState) -> Result<()> {
+		let (width, height) = Self::resolution(state.hires);
+		let size = width * height;
+		anyhow::ensure!(
+			size == state.buffer.len(),
+			"Expected {} pixels, got {}.",
+			size,
+			state.buffer.len(),
+		);
+
+		self.set_hires(state.hires);
+		self.select_planes(state.planes);
+		self.buffer[..size].copy_from_slice(&state.buffer);
+		Ok(())
+	}
+
+	/// Scrolls the screen down by `lines` (`00Cn`), clearing the lines at the
+	/// top.
+	///
+	/// The amounts of all scrolls are in high resolution pixels, so in low
+	/// resolution they are halved, as on SUPER-CHIP 1.1.
+	pub fn scroll_down(&mut self, lines: usize) {
+		let (width, height) = (self.width(), self.height());
+		let lines = self.scaled_scroll(lines).min(height);
+		let size = width * height;
+		let shift = lines * width;
+
+		let unscrolled = self.buffer;
+		self.buffer.copy_within(..size - shift, shift);
+		self.buffer[..shift].fill(0);
+		self.keep_unselected_planes(&unscrolled);
+	}
+
+	/// Scrolls the screen right by 4 pixels (`00FB`), clearing the columns
+	/// on the left. See `self.scroll_down` for the low resolution.
+	pub fn scroll_right(&mut self) {
+		let (width, height) = (self.width(), self.height());
+		let shift = self.scaled_scroll(4);
+
+		let unscrolled = self.buffer;
+		for row in self.buffer[..width * height].chunks_exact_mut(width) {
+			row.copy_within(..width - shift, shift);
+			row[..shift].fill(0);
+		}
+		self.keep_unselected_planes(&unscrolled);
+	}
+
+	/// Scrolls the screen left by 4 pixels (`00FC`), clearing the columns
+	/// on the right. See `self.scroll_down` for the low resolution.
+	pub fn scroll_left(&mut self) {
+		let (width, height) = (self.width(), self.height());
+		let shift = self.scaled_scroll(4);
+
+		let unscrolled = self.buffer;
+		for row in self.buffer[..width * height].chunks_exact_mut(width) {
+			row.copy_within(shift.., 0);
+			row[width - shift..].fill(0);
+		}
+		self.keep_unselected_planes(&unscrolled);
+	}
+
+	/// Undoes a scroll of the planes that are not selected, restoring them
+	/// from the `unscrolled` buffer.
+	fn keep_unselected_planes(
+		&mut self,
+		unscrolled: &[u8; crate::consts::SCREEN_HIRES_SIZE],
+	) {
+		let planes = self.planes;
+		for (pixel, unscrolled) in self.buffer.iter_mut().zip(unscrolled) {
+			*pixel = (*pixel & planes) | (unscrolled & !planes);
+		}
+		self.mark_all_dirty();
+	}
+
+	/// Converts a scroll amount in high resolution pixels to the active
+	/// resolution.
+	#[inline]
+	const fn scaled_scroll(&self, pixels: usize) -> usize {
+		if self.is_hires() {
+			pixels
+		} else {
+			pixels / 2
+		}
+	}
+
+	/// Displays the `self.buffer` on the [window](minifb::Window), scaled
+	/// with `self.fill_window_colors` into `self.window_buffer`. The window
+	/// has the same size in both resolutions, so the buffer is allocated
+	/// once in `Self::new` and reused for every frame.
+	///
+	/// Only the rows changed since the previous call are converted. If
+	/// nothing changed, the window just processes its events.
+	///
+	/// # Errors
+	///
+	/// If the window buffer could not be updated.
+	///
+	/// # Panics
+	///
+	/// If `self.can_display()` is `false`.
+	#[cfg(feature = "window")]
 	#[cfg_attr(
 		feature = "tracing",
 		tracing::instrument(level = tracing::Level::TRACE, skip_all),
@@ -38,39 +628,183 @@ impl Screen {
 	#[inline]
 	pub fn display(&mut self, window: &mut minifb::Window) -> Result<()> {
 		assert!(self.can_display());
+		self.draw(window)?;
+		self.mark_displayed();
+		Ok(())
+	}
 
-		let mut window_buffer =
-			vec![0; crate::consts::WINDOW_SIZE].into_boxed_slice();
+	/// Like `self.display`, but draws right away without waiting for the
+	/// frame or scheduling the next one, for frontends that pace the frames
+	/// with `Emulator::tick`.
+	///
+	/// # Errors
+	///
+	/// If the window buffer could not be updated.
+	#[cfg(feature = "window")]
+	pub fn draw(&mut self, window: &mut minifb::Window) -> Result<()> {
+		let (width, height) = (self.window_width(), self.window_height());
+		let Some(window_buffer) = self.render_window() else {
+			window.update();
+			return Ok(());
+		};
+		window
+			.update_with_buffer(window_buffer, width, height)
+			.context("Failed to update buffer.")
+	}
 
-		for window_y in 0..crate::consts::WINDOW_HEIGHT {
-			let y = window_y / crate::consts::WINDOW_MULTIPLIER;
+	/// Converts the rows changed since the previous call into
+	/// `self.window_buffer` and returns it, `self.window_width` by
+	/// `self.window_height` pixels, or `None` if nothing changed. Reuses the
+	/// buffer, so it does not allocate.
+	#[cfg(feature = "window")]
+	pub fn render_window(&mut self) -> Option<&[u32]> {
+		let mut rows = self.dirty_rows.take();
+		let (size, height) = (self.width() * self.height(), self.height());
+		if let Some(ghosting) = &mut self.ghosting {
+			if ghosting.update(&self.buffer[..size]) {
+				rows = Some(0..height);
+			}
+		}
+		let rows = rows?;
 
-			for window_x in 0..crate::consts::WINDOW_WIDTH {
-				let x = window_x / crate::consts::WINDOW_MULTIPLIER;
+		// Taking the buffer out lets us fill it while borrowing `self`. An
+		// empty box does not allocate
+		let mut window_buffer = std::mem::take(&mut self.window_buffer);
+		self.fill_window_colors(&mut window_buffer, rows);
+		if let Some(text) = &self.overlay {
+			crate::overlay::draw_text(
+				&mut window_buffer,
+				self.window_width(),
+				text,
+				(self.window_multiplier / 4).max(1),
+				(self.foreground_color(), self.background_color()),
+			);
+		}
+		if self.visual_beep && self.beeping {
+			self.draw_beep_frame(&mut window_buffer);
+		}
+		self.window_buffer = window_buffer;
+		Some(&self.window_buffer)
+	}
+
+	/// Draws the frame of `self.set_visual_beep` along the window edges,
+	/// a quarter of a pixel thick.
+	#[cfg(feature = "window")]
+	fn draw_beep_frame(&self, window_buffer: &mut [u32]) {
+		let (width, height) = (self.window_width(), self.window_height());
+		let thickness = (self.window_multiplier / 4).max(1);
+		for (y, row) in window_buffer.chunks_exact_mut(width).enumerate() {
+			if y < thickness || y >= height - thickness {
+				row.fill(crate::consts::SCREEN_VISUAL_BEEP_COLOR);
+			} else {
+				row[..thickness].fill(crate::consts::SCREEN_VISUAL_BEEP_COLOR);
+				row[width - thickness..]
+					.fill(crate::consts::SCREEN_VISUAL_BEEP_COLOR);
+			}
+		}
+	}
 
-				let buffer_index = y * crate::consts::SCREEN_WIDTH + x;
-				let window_buffer_index =
-					window_y * crate::consts::WINDOW_WIDTH + window_x;
+	/// Returns the RGB bytes of the screen as it looks in the window, that
+	/// is `self.window_width` by `self.window_height` pixels, for
+	/// screenshots.
+	#[must_use]
+	pub fn to_image(&self) -> Vec<u8> {
+		let mut window_buffer = vec![0; self.window_size()];
+		self.fill_window_colors(&mut window_buffer, 0..self.height());
+		window_buffer
+			.iter()
+			.flat_map(|color| {
+				let [_, r, g, b] = color.to_be_bytes();
+				[r, g, b]
+			})
+			.collect()
+	}
 
-				let pixel = self.buffer[buffer_index];
-				let pixel_color = match pixel {
-					0 => crate::consts::BLACK_COLOR,
-					1 => crate::consts::WHITE_COLOR,
-					_ => unreachable!(),
-				};
-				window_buffer[window_buffer_index] = pixel_color;
+	/// Since the original screen size is very small, we display it in a large
+	/// window by scaling each pixel up to fill `self.window_width` (by
+	/// `self.window_multiplier` in low resolution, half of it in high) and
+	/// translating every pixel value into its color in `self.palette`. Only
+	/// the window rows of the screen `rows` are filled.
+	fn fill_window_colors(
+		&self,
+		window_buffer: &mut [u32],
+		rows: std::ops::Range<usize>,
+	) {
+		debug_assert_eq!(window_buffer.len(), self.window_size());
+
+		let buffer = self.buffer();
+		let (width, height) = (self.width(), self.height());
+		let (window_width, window_height) =
+			(self.window_width(), self.window_height());
+		// The first window row whose screen row is `y`
+		let window_row = |y: usize| (y * window_height).div_ceil(height);
+		// Without several window rows per pixel there is nothing to dim
+		let scanlines = self.crt && window_height >= 2 * height;
+
+		for window_y in window_row(rows.start)..window_row(rows.end) {
+			let y = window_y * height / window_height;
+			let is_scanline = scanlines && window_y % 2 == 1;
+
+			for window_x in 0..window_width {
+				let x = window_x * width / window_width;
+
+				let buffer_index = y * width + x;
+				let window_buffer_index = window_y * window_width + window_x;
+
+				let pixel = buffer[buffer_index];
+				let ghost =
+					self.ghosting.as_ref().filter(|_| pixel == 0).and_then(
+						|ghosting| ghosting.color(buffer_index, &self.palette),
+					);
+				let color =
+					ghost.unwrap_or_else(|| self.palette[usize::from(pixel)]);
+				window_buffer[window_buffer_index] =
+					if is_scanline { dim(color) } else { color };
 			}
 		}
+	}
 
-		window
-			.update_with_buffer(
-				&window_buffer,
-				crate::consts::WINDOW_WIDTH,
-				crate::consts::WINDOW_HEIGHT,
-			)
-			.context("Failed to update buffer.")?;
-		self.last_display_time = std::time::Instant::now();
-		Ok(())
+	/// Renders `self.buffer` as text, one line per row, with `█` for the
+	/// pixels that are on any plane and a space for the ones that are off.
+	#[must_use]
+	pub fn render_ascii(&self) -> String {
+		let width = self.width();
+		let mut text = String::with_capacity((width * 3 + 1) * self.height());
+		for row in self.buffer().chunks_exact(width) {
+			text.extend(row.iter().map(|&p| if p == 0 { ' ' } else { '█' }));
+			text.push('\n');
+		}
+		text
+	}
+
+	/// Renders `self.buffer` as a plain PGM image, one gray level per pixel
+	/// value so both XO-CHIP planes show, at `self.width` by `self.height`
+	/// without scaling or colors. For comparing screens with reference files
+	/// in tests.
+	#[must_use]
+	pub fn dump_pgm(&self) -> String {
+		let mut text = format!("P2\n{} {}\n3\n", self.width(), self.height());
+		for line in self.buffer().chunks(crate::consts::SCREEN_PGM_LINE_PIXELS)
+		{
+			let values: Vec<_> =
+				line.iter().map(ToString::to_string).collect();
+			text.push_str(&values.join(" "));
+			text.push('\n');
+		}
+		text
+	}
+
+	/// Like `self.display`, but returns `self.render_ascii` for the terminal
+	/// instead of updating a window.
+	///
+	/// # Panics
+	///
+	/// If `self.can_display()` is `false`.
+	#[inline]
+	pub fn display_ascii(&mut self) -> String {
+		assert!(self.can_display());
+		self.mark_displayed();
+		self.render_ascii()
 	}
 
 	/// Determines whether enough time has elapsed for us to display new
@@ -78,43 +812,248 @@ impl Screen {
 	#[inline]
 	#[must_use]
 	pub fn can_display(&self) -> bool {
-		self.last_display_time.elapsed() > std::time::Duration::from_millis(10)
+		self.last_display_time.elapsed() > self.display_interval
+	}
+
+	/// Returns how long until `self.can_display` becomes `true`, zero if it
+	/// already is.
+	#[inline]
+	#[must_use]
+	pub fn time_until_display(&self) -> std::time::Duration {
+		self.display_interval.saturating_sub(self.last_display_time.elapsed())
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn display_interval(&self) -> std::time::Duration {
+		self.display_interval
+	}
+
+	/// Sets how often `self.can_display` allows a new frame,
+	/// `consts::SCREEN_DISPLAY_INTERVAL` by default. With
+	/// `consts::SCREEN_VSYNC_INTERVAL`, one frame is shown per 60Hz refresh
+	/// of the monitor. It only paces the output, the emulated timers do not
+	/// depend on it.
+	#[inline]
+	pub fn set_display_interval(&mut self, interval: std::time::Duration) {
+		self.display_interval = interval;
+	}
+
+	/// Schedules the next frame one interval after the previous one, so the
+	/// frames stay evenly spaced instead of drifting by however late each
+	/// one was shown. After a missed frame, the schedule starts over.
+	#[inline]
+	pub(crate) fn mark_displayed(&mut self) {
+		let now = crate::time::Instant::now();
+		let scheduled = self.last_display_time + self.display_interval;
+		self.last_display_time = if now.saturating_duration_since(scheduled)
+			< self.display_interval
+		{
+			scheduled
+		} else {
+			now
+		};
 	}
 
-	/// Draws a byte in the `self.buffer` at `x` and `y` coordinates.
+	/// Packs `self.buffer` into `width() * height() / 8` bytes, one bit per
+	/// pixel, set if it is on any plane.
+	///
+	/// Pixels go row by row, left to right. Each byte holds 8 consecutive
+	/// pixels, the leftmost one in the most significant bit.
+	#[must_use]
+	pub fn packed_bits(&self) -> Vec<u8> {
+		self.buffer()
+			.chunks_exact(8)
+			.map(|pixels| {
+				pixels
+					.iter()
+					.fold(0, |byte, &p| (byte << 1) | u8::from(p != 0))
+			})
+			.collect()
+	}
+
+	/// The inverse of `self.packed_bits`: expands every bit of `packed` into
+	/// a `0` or `1` pixel, most significant bit first.
+	#[must_use]
+	pub fn unpack_bits(packed: &[u8]) -> Vec<u8> {
+		packed
+			.iter()
+			.flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1))
+			.collect()
+	}
+
+	/// Draws the 16 pixels of `word` like `self.draw_byte` does with 8, for
+	/// the SUPER-CHIP 16x16 sprites.
 	///
 	/// Returns a `bool` that informs if a bit has been erased from the screen
 	/// (`self.buffer`).
+	pub fn draw_wide_byte(
+		&mut self,
+		plane: u8,
+		word: u16,
+		x: usize,
+		y: usize,
+	) -> bool {
+		let [high, low] = word.to_be_bytes();
+		let high_is_erased = self.draw_byte(plane, high, x, y);
+		let low_is_erased = self.draw_byte(plane, low, x + 8, y);
+		high_is_erased || low_is_erased
+	}
+
+	/// Returns the row of `self.buffer` that a sprite row at `y` is drawn on:
+	/// `y` itself on the screen, and below the bottom edge the row it wraps
+	/// around to, or `None` if it is dropped, see `self.clip_quirk`.
+	#[inline]
+	#[must_use]
+	pub fn sprite_row(&self, y: usize) -> Option<usize> {
+		let height = self.height();
+		if y < height {
+			Some(y)
+		} else if self.clip_quirk == crate::quirks::ClipQuirk::Clip {
+			None
+		} else {
+			Some(y % height)
+		}
+	}
+
+	/// Draws a byte on the `plane` (`1` or `2`, see
+	/// `self.selected_planes`) of the `self.buffer` at `x` and `y`
+	/// coordinates. Pixels beyond the edges wrap around or are dropped, see
+	/// `self.clip_quirk`, and the row with `self.sprite_row`. With
+	/// `DrawBatchQuirk::Frame` it draws into a copy that
+	/// `self.commit_draws` shows later.
+	///
+	/// Returns a `bool` that informs if a bit has been erased from the
+	/// `plane`.
 	#[cfg_attr(
 		feature = "tracing",
 		tracing::instrument(level = tracing::Level::TRACE, skip(self), ret),
 	)]
 	pub fn draw_byte(
 		&mut self,
-		mut byte: u8,
-		mut x: usize,
-		mut y: usize,
+		plane: u8,
+		byte: u8,
+		x: usize,
+		y: usize,
 	) -> bool {
-		let mut is_erased = false;
-		y %= crate::consts::SCREEN_HEIGHT;
+		debug_assert!(plane == 1 || plane == 2);
+		let width = self.width();
+		let clip = self.clip_quirk == crate::quirks::ClipQuirk::Clip;
+		// Nothing changes without lit bits
+		if byte == 0 || clip && x >= width {
+			return false;
+		}
+		let Some(y) = self.sprite_row(y) else {
+			return false;
+		};
+		let x = x % width;
+		let batched =
+			self.draw_batch_quirk == crate::quirks::DrawBatchQuirk::Frame;
+		let pixels = if batched {
+			let buffer = &self.buffer;
+			&mut **self.staged.get_or_insert_with(|| Box::new(*buffer))
+		} else {
+			// Even if the byte only erases pixels, the row changes
+			self.mark_dirty(y..y + 1);
+			&mut self.buffer
+		};
 
-		for _ in 0..8 {
-			x %= crate::consts::SCREEN_WIDTH;
-			let buffer_index = y * crate::consts::SCREEN_WIDTH + x;
+		// The screen is wider than a byte, so the pixels past the right edge
+		// are all wrapped to the start of the row at once
+		let row = y * width;
+		let visible = (width - x).min(8);
+		let mut erased =
+			xor_pixels(&mut pixels[row + x..row + x + visible], byte, plane);
+		if visible < 8 && !clip {
+			erased += xor_pixels(
+				&mut pixels[row..row + 8 - visible],
+				byte << visible,
+				plane,
+			);
+		}
 
-			let previous_bit = self.buffer[buffer_index];
-			let bit = (byte & 0b1000_0000) >> 7;
-			let current_bit = previous_bit ^ bit;
+		if erased == 0 {
+			return false;
+		}
+		// Batched draws only collide once committed
+		if batched {
+			return true;
+		}
+		if let Some(collisions) = &mut self.collisions {
+			*collisions += u64::from(erased);
+			#[cfg(feature = "tracing")]
+			tracing::debug!(
+				"{erased} collisions in the byte at {x}, {y} on plane {plane}"
+			);
+		}
+		true
+	}
+}
 
-			self.buffer[buffer_index] = current_bit;
+/// Blends the `color` toward black down to
+/// `consts::SCREEN_SCANLINE_BRIGHTNESS`.
+#[inline]
+fn dim(color: u32) -> u32 {
+	(0..3)
+		.map(|channel| {
+			let shift = channel * 8;
+			let value = (color >> shift) & 0xFF;
+			(value * crate::consts::SCREEN_SCANLINE_BRIGHTNESS / 100) << shift
+		})
+		.sum()
+}
 
-			if previous_bit == 1 && current_bit == 0 {
-				is_erased = true;
-			}
+/// XORs the `plane` bit of the `pixels` with the bits of `byte`, starting
+/// from its highest one. Returns how many pixels were erased from the
+/// `plane`.
+#[inline]
+fn xor_pixels(pixels: &mut [u8], byte: u8, plane: u8) -> u32 {
+	debug_assert!(pixels.len() <= 8);
+	let mut erased = 0;
+	for (i, pixel) in pixels.iter_mut().enumerate() {
+		let mask = ((byte << i) >> 7) * plane;
+		erased += u32::from(*pixel & mask != 0);
+		*pixel ^= mask;
+	}
+	erased
+}
 
-			x += 1;
-			byte <<= 1;
-		}
-		is_erased
+#[cfg(test)]
+mod tests {
+	use super::Screen;
+
+	#[test]
+	fn packed_bits_round_trip() {
+		let mut screen = Screen::new();
+		screen.draw_byte(1, 0xA5, 0, 0);
+		screen.draw_byte(1, 0x81, 60, 31);
+
+		let packed = screen.packed_bits();
+		assert_eq!(packed.len(), crate::consts::SCREEN_SIZE / 8);
+		assert_eq!(packed[..2], [0xA5, 0x00]);
+		// The byte wraps to the start of the last row
+		assert_eq!(packed[248..], [0x10, 0, 0, 0, 0, 0, 0, 0x08]);
+		assert_eq!(Screen::unpack_bits(&packed), screen.buffer());
+	}
+
+	#[test]
+	fn smallest_window_shows_every_hires_pixel() {
+		let mut screen = Screen::new();
+		screen.set_window_multiplier(crate::consts::WINDOW_MIN_MULTIPLIER);
+		screen.set_hires(true);
+		screen.draw_byte(1, 0x55, 120, 63);
+		let (width, foreground, background) = (
+			screen.window_width(),
+			screen.foreground_color(),
+			screen.background_color(),
+		);
+
+		let window_buffer = screen.render_window().unwrap();
+		assert_eq!(window_buffer.len(), 128 * 64);
+		let last_row = &window_buffer[63 * width + 120..];
+		assert_eq!(last_row, [
+			background, foreground, background, foreground, background,
+			foreground, background, foreground
+		]);
 	}
 }
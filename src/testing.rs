@@ -0,0 +1,31 @@
+//! Helpers for comparing emulator output in regression tests.
+
+/// Compares two screen buffers, allowing up to `max_diff_pixels` pixels to
+/// differ.
+///
+/// Useful for golden-image tests that should survive small rendering changes
+/// (for example, after tweaking a quirk). Pass `0` for an exact comparison.
+/// Buffers of different lengths are never equal.
+#[must_use]
+pub fn frames_equal(a: &[u8], b: &[u8], max_diff_pixels: usize) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	let diff_pixels = a.iter().zip(b).filter(|(a, b)| a != b).count();
+	diff_pixels <= max_diff_pixels
+}
+
+#[cfg(test)]
+mod tests {
+	use super::frames_equal;
+
+	#[test]
+	fn tolerates_up_to_max_diff_pixels() {
+		let a = [0, 1, 0, 1, 0, 1];
+		let b = [1, 1, 1, 1, 0, 1];
+		assert!(frames_equal(&a, &a, 0));
+		assert!(frames_equal(&a, &b, 2));
+		assert!(!frames_equal(&a, &b, 1));
+		assert!(!frames_equal(&a, &a[..5], 6));
+	}
+}
@@ -0,0 +1,71 @@
+//! The clock of the emulator. Native builds use `std::time::Instant`, but
+//! `wasm32-unknown-unknown` has no clock in `std`, so there the host sets
+//! the time with `set_now`, e.g. from `performance.now()`, before running
+//! the emulator.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub use shim::{set_now, Instant};
+
+#[cfg(target_arch = "wasm32")]
+mod shim {
+	/// Microseconds since the host's time origin.
+	static NOW: std::sync::atomic::AtomicU64 =
+		std::sync::atomic::AtomicU64::new(0);
+
+	/// Sets the time `Instant::now` returns, as the time since any fixed
+	/// origin. It must not go backwards.
+	pub fn set_now(now: std::time::Duration) {
+		use std::convert::TryFrom as _;
+
+		let micros = u64::try_from(now.as_micros()).unwrap_or(u64::MAX);
+		NOW.store(micros, std::sync::atomic::Ordering::Relaxed);
+	}
+
+	/// The part of `std::time::Instant` the emulator uses, reading the time
+	/// set with `set_now`.
+	#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+	pub struct Instant(std::time::Duration);
+
+	impl Instant {
+		#[must_use]
+		pub fn now() -> Self {
+			let micros = NOW.load(std::sync::atomic::Ordering::Relaxed);
+			Self(std::time::Duration::from_micros(micros))
+		}
+
+		#[must_use]
+		pub fn elapsed(&self) -> std::time::Duration {
+			Self::now().saturating_duration_since(*self)
+		}
+
+		#[must_use]
+		pub fn duration_since(&self, earlier: Self) -> std::time::Duration {
+			self.saturating_duration_since(earlier)
+		}
+
+		#[must_use]
+		pub fn saturating_duration_since(
+			&self,
+			earlier: Self,
+		) -> std::time::Duration {
+			self.0.saturating_sub(earlier.0)
+		}
+	}
+
+	impl std::ops::Add<std::time::Duration> for Instant {
+		type Output = Self;
+
+		fn add(self, duration: std::time::Duration) -> Self {
+			Self(self.0 + duration)
+		}
+	}
+
+	impl std::ops::AddAssign<std::time::Duration> for Instant {
+		fn add_assign(&mut self, duration: std::time::Duration) {
+			self.0 += duration;
+		}
+	}
+}
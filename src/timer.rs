@@ -1,44 +1,100 @@
-/// Delay timer for the `crate::emulator::Emulator`.
+/// Snapshot of the `Timer` values, see `Timer::save_state`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerState {
+	delay: u8,
+	sound: u8,
+}
+
+/// How the `crate::emulator::Emulator` ticks the `Timer`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimerMode {
+	/// Once per `consts::TIMER_TICK_INTERVAL` of wall-clock time, times the
+	/// speed multiplier, while the emulator is not paused.
+	#[default]
+	WallClock,
+	/// Once per `clock_hz / 60` instructions, so a run does not depend on
+	/// how fast the host is.
+	Cycles,
+	/// Once per `Emulator::run_frame`, which runs a fixed number of
+	/// instructions per 60Hz frame instead of pacing every instruction.
+	Frames,
+}
+
+/// Delay and sound timers for the `crate::emulator::Emulator`.
 ///
 /// You can set the delay with `self.set_delay` and get the remaining delay
-/// with `self.get_delay`.
+/// with `self.get_delay`. The sound timer works the same way with
+/// `self.set_sound` and `self.get_sound`: the buzzer sounds while it is
+/// nonzero. Both are plain counters that `self.tick` decrements; the
+/// `Emulator` calls it at 60Hz according to its `TimerMode`.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct Timer {
 	delay: u8,
-	delay_set_time: std::time::Instant,
+	sound: u8,
 }
 
 impl Timer {
+	#[inline]
 	#[must_use]
-	pub fn new() -> Self {
-		Self { delay: 0, delay_set_time: std::time::Instant::now() }
+	pub const fn new() -> Self {
+		Self { delay: 0, sound: 0 }
+	}
+
+	/// Decrements both timers by one 60Hz tick, stopping at zero.
+	#[inline]
+	pub fn tick(&mut self) {
+		self.delay = self.delay.saturating_sub(1);
+		self.sound = self.sound.saturating_sub(1);
 	}
 
-	/// If the required number of ticks passes, returns `0`, otherwise it
-	/// returns the remaining ticks.
 	#[cfg_attr(
 		feature = "tracing",
 		tracing::instrument(level = tracing::Level::TRACE, ret, skip(self)),
 	)]
+	#[inline]
 	#[must_use]
 	pub fn get_delay(&self) -> u8 {
-		use std::convert::TryFrom as _;
-
-		let ticks = self.delay_set_time.elapsed().as_millis() / 16;
-		if ticks >= u128::from(self.delay) {
-			0
-		} else if let Ok(ticks) = u8::try_from(ticks) {
-			self.delay - ticks
-		} else {
-			unreachable!();
-		}
+		self.delay
 	}
 
 	#[cfg_attr(
 		feature = "tracing",
 		tracing::instrument(level =  tracing::Level::TRACE, skip(self)),
 	)]
+	#[inline]
 	pub fn set_delay(&mut self, delay: u8) {
 		self.delay = delay;
-		self.delay_set_time = std::time::Instant::now();
+	}
+
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(level = tracing::Level::TRACE, ret, skip(self)),
+	)]
+	#[inline]
+	#[must_use]
+	pub fn get_sound(&self) -> u8 {
+		self.sound
+	}
+
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(level =  tracing::Level::TRACE, skip(self)),
+	)]
+	#[inline]
+	pub fn set_sound(&mut self, sound: u8) {
+		self.sound = sound;
+	}
+
+	/// Captures the remaining ticks of both timers.
+	#[must_use]
+	pub const fn save_state(&self) -> TimerState {
+		TimerState { delay: self.delay, sound: self.sound }
+	}
+
+	/// Restores the timers captured by `self.save_state`.
+	pub fn load_state(&mut self, state: TimerState) {
+		self.delay = state.delay;
+		self.sound = state.sound;
 	}
 }
@@ -0,0 +1,123 @@
+//! Bindings for running the emulator in a browser, built for
+//! `wasm32-unknown-unknown` with the `wasm` feature (and without the default
+//! `window` one). The page owns the canvas, the keys and the audio: it loads
+//! a ROM, reports the held keys and calls `Chip8::run_frame` from
+//! `requestAnimationFrame`, then draws `Chip8::pixels` into an `ImageData`.
+
+use wasm_bindgen::prelude::*;
+
+/// An `Emulator` running in the `TimerMode::Frames` timing model, so the
+/// page's frame rate drives it.
+#[wasm_bindgen]
+pub struct Chip8 {
+	emulator: crate::emulator::Emulator,
+	/// The keys set with `Self::set_keys`, passed to every `Emulator::tick`.
+	held_keys: u16,
+}
+
+#[wasm_bindgen]
+impl Chip8 {
+	#[wasm_bindgen(constructor)]
+	#[must_use]
+	pub fn new() -> Self {
+		let mut emulator = crate::emulator::Emulator::new();
+		emulator.set_timer_mode(crate::timer::TimerMode::Frames);
+		Self { emulator, held_keys: 0 }
+	}
+
+	/// Loads the ROM and restarts with it, see `Emulator::load_rom_reset`.
+	///
+	/// # Errors
+	///
+	/// If the ROM does not fit into the memory.
+	pub fn load_rom(&mut self, data: &[u8]) -> Result<(), JsValue> {
+		self.emulator
+			.load_rom_reset(data)
+			.map_err(|e| JsValue::from_str(&e.to_string()))
+	}
+
+	/// Holds the keys of the bitmask: bit `n` is set while the key with code
+	/// `n` is held.
+	pub fn set_keys(&mut self, held_keys: u16) {
+		self.held_keys = held_keys;
+	}
+
+	/// Runs whatever is due at `now_ms`, the `performance.now()` of the
+	/// page. Returns whether the screen should be redrawn.
+	///
+	/// # Errors
+	///
+	/// If the `Cpu` could not run an instruction. The emulator stays halted
+	/// on it.
+	pub fn run_frame(&mut self, now_ms: f64) -> Result<bool, JsValue> {
+		#[cfg(target_arch = "wasm32")]
+		crate::time::set_now(
+			std::time::Duration::try_from_secs_f64(now_ms / 1000.0)
+				.unwrap_or_default(),
+		);
+		#[cfg(not(target_arch = "wasm32"))]
+		let _ = now_ms;
+
+		let mut displayed = false;
+		loop {
+			match self.emulator.tick(self.held_keys) {
+				crate::emulator::TickResult::Displayed => displayed = true,
+				crate::emulator::TickResult::Halted(e) => {
+					return Err(JsValue::from_str(&e.to_string()));
+				}
+				crate::emulator::TickResult::Idle
+				| crate::emulator::TickResult::Ended => return Ok(displayed),
+				crate::emulator::TickResult::Ran
+				| crate::emulator::TickResult::Beep(_)
+				| crate::emulator::TickResult::WaitingForKey => {}
+			}
+		}
+	}
+
+	/// Returns the RGBA bytes of the screen, `self.width` by `self.height`
+	/// pixels in the colors of `Emulator::palette`, for an `ImageData`.
+	#[must_use]
+	pub fn pixels(&self) -> Vec<u8> {
+		let palette = self.emulator.palette();
+		self.emulator
+			.screen()
+			.buffer()
+			.iter()
+			.flat_map(|&pixel| {
+				let [_, r, g, b] = palette[usize::from(pixel)].to_be_bytes();
+				[r, g, b, 0xFF]
+			})
+			.collect()
+	}
+
+	/// The width of the screen in the current resolution.
+	#[must_use]
+	pub fn width(&self) -> usize {
+		self.emulator.screen().width()
+	}
+
+	/// The height of the screen in the current resolution.
+	#[must_use]
+	pub fn height(&self) -> usize {
+		self.emulator.screen().height()
+	}
+
+	/// Whether the ROM has ended by jumping to itself, see
+	/// `Emulator::has_ended`. It only restarts with `Self::load_rom`.
+	#[must_use]
+	pub fn has_ended(&self) -> bool {
+		self.emulator.has_ended()
+	}
+
+	/// Whether the page should play the buzzer, see `Emulator::is_beeping`.
+	#[must_use]
+	pub fn is_beeping(&self) -> bool {
+		self.emulator.is_beeping()
+	}
+}
+
+impl Default for Chip8 {
+	fn default() -> Self {
+		Self::new()
+	}
+}
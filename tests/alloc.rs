@@ -0,0 +1,49 @@
+//! Checks that rendering a frame for the window does not allocate, with an
+//! allocator that counts the allocations of the current thread.
+#![cfg(feature = "window")]
+
+struct CountingAllocator;
+
+std::thread_local! {
+	static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+		ALLOCATIONS.with(|count| count.set(count.get() + 1));
+		std::alloc::System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+		std::alloc::System.dealloc(ptr, layout);
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+	ALLOCATIONS.with(std::cell::Cell::get)
+}
+
+#[test]
+fn rendering_frames_does_not_allocate() {
+	let mut screen = chip_8::screen::Screen::new();
+	assert!(screen.render_window().is_some());
+
+	let before = allocations();
+	for frame in 0..50 {
+		// Both resolutions share the window buffer
+		if frame == 25 {
+			screen.set_hires(true);
+		}
+		screen.draw_byte(1, 0b1010_1010, frame, frame % 32);
+		let window_buffer =
+			screen.render_window().expect("The sprite changed no row.");
+		assert_eq!(
+			window_buffer.len(),
+			screen.window_width() * screen.window_height()
+		);
+	}
+	assert_eq!(allocations(), before);
+}
@@ -0,0 +1,193 @@
+//! Checks the `Emulator` API that frontends and embedders build on.
+
+use chip_8::{emulator::Emulator, platform::Platform, quirks::QuirkProfile};
+
+fn with_profile(profile: QuirkProfile) -> Emulator {
+	Emulator::with_config(&chip_8::config::Config {
+		profile,
+		..chip_8::config::Config::default()
+	})
+}
+
+#[test]
+fn platform_follows_the_profile() {
+	assert_eq!(with_profile(QuirkProfile::Chip8).platform(), Platform::Chip8);
+	assert_eq!(
+		with_profile(QuirkProfile::SuperChip).platform(),
+		Platform::SuperChip
+	);
+	assert_eq!(
+		with_profile(QuirkProfile::XoChip).platform(),
+		Platform::XoChip
+	);
+}
+
+#[test]
+fn rom_larger_than_the_memory_is_rejected() {
+	let mut emulator = Emulator::new();
+	assert_eq!(
+		emulator.load_rom(&[0xFF; 4000]),
+		Err(chip_8::ram::RamError::RomTooLarge { size: 4000, capacity: 3584 })
+	);
+	let memory = emulator.ram().memory();
+	assert!(memory[0x200..].iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn draws_wait_for_the_frames_of_the_cycles() {
+	let mut emulator = with_profile(QuirkProfile::Chip8);
+	emulator.set_timer_mode(chip_8::timer::TimerMode::Cycles);
+	emulator
+		.load_rom(&[0xD0, 0x11, 0x70, 0x01, 0x12, 0x00])
+		.expect("The ROM does not fit.");
+	// 500 Hz: a frame every 8 cycles, each with one draw right after the
+	// vertical blank. The first draw waits for the end of the first frame
+	emulator.run_cycles(8 * 10).expect("The ROM crashed.");
+	assert_eq!(emulator.cpu().registers()[0], 9);
+}
+
+#[test]
+fn timers_tick_in_every_mode() {
+	use chip_8::timer::TimerMode;
+
+	// LD VA, 30; LD DT, VA; then LD VB, DT in a loop
+	let rom = [0x6A, 0x1E, 0xFA, 0x15, 0xFB, 0x07, 0x12, 0x04];
+	let start = |mode| {
+		let mut emulator = Emulator::new();
+		emulator.set_timer_mode(mode);
+		emulator.load_rom(&rom).expect("The ROM does not fit.");
+		emulator.run_cycles(2).expect("The ROM crashed.");
+		emulator
+	};
+	// The timers advance after an instruction, and VB holds the delay after
+	// either of the next two
+	let delay = |emulator: &mut Emulator| {
+		emulator.run_cycles(3).expect("The ROM crashed.");
+		emulator.cpu().registers()[0xB]
+	};
+
+	// 500 Hz: a tick every 8 instructions, counting the first two
+	let mut emulator = start(TimerMode::Cycles);
+	emulator.run_cycles(8 * 10 - 2).expect("The ROM crashed.");
+	assert_eq!(delay(&mut emulator), 20);
+
+	let mut emulator = start(TimerMode::Frames);
+	for _ in 0..10 {
+		emulator.run_frame().expect("The ROM crashed.");
+	}
+	assert_eq!(delay(&mut emulator), 20);
+
+	// At least 6 ticks of 1/60s, however slow the host is
+	let mut emulator = start(TimerMode::WallClock);
+	std::thread::sleep(std::time::Duration::from_millis(100));
+	assert!(delay(&mut emulator) <= 24);
+}
+
+#[test]
+fn recorded_input_replays_the_same() {
+	// Counts in V0 the loops with key 5 held, 3 instructions each
+	let rom = [
+		0x61, 0x05, // 200: LD V1, 5
+		0xE1, 0x9E, // 202: SKP V1
+		0x12, 0x08, // 204: JP 208
+		0x70, 0x01, // 206: ADD V0, 1
+		0x12, 0x02, // 208: JP 202
+	];
+	let recording = [0, 1 << 5, 1 << 5, 0, 1 << 5 | 1 << 3, 1 << 3];
+	let replay = || {
+		let mut emulator = Emulator::new();
+		emulator.load_rom(&rom).expect("The ROM does not fit.");
+		emulator.run_cycles(1).expect("The ROM crashed.");
+		for &mask in &recording {
+			emulator.set_keyboard_state(mask);
+			assert_eq!(emulator.keyboard_state(), mask);
+			emulator.run_cycles(3).expect("The ROM crashed.");
+		}
+		(emulator.save_state(), emulator.cpu().registers()[0])
+	};
+
+	let (state, loops) = replay();
+	assert_eq!(loops, 3);
+	assert_eq!(replay(), (state, loops));
+}
+
+#[test]
+fn watchpoint_hits_report_their_instruction() {
+	use chip_8::ram::{Access, Watchpoint, WatchpointHit};
+
+	let mut emulator = Emulator::new();
+	emulator
+		.load_rom(&[0x60, 0x12, 0xA3, 0x00, 0xF0, 0x55, 0x12, 0x06])
+		.expect("The ROM does not fit.");
+	emulator.toggle_watchpoint(Watchpoint {
+		address: 0x300,
+		access: Access::Write,
+		value: None,
+	});
+	emulator.run_cycles(3).expect("The ROM crashed.");
+	let hit =
+		WatchpointHit { address: 0x300, access: Access::Write, value: 0x12 };
+	assert_eq!(emulator.take_watchpoint_hit(), Some((0x204, hit)));
+
+	emulator.step().expect("The ROM crashed.");
+	assert_eq!(emulator.take_watchpoint_hit(), None);
+}
+
+#[test]
+fn checked_load_reports_the_rom() {
+	use chip_8::{ram::RamError, rom::RomError};
+
+	let mut emulator = Emulator::new();
+	let info = emulator
+		.load_rom_checked(&[0x00, 0xFF, 0x12, 0x02])
+		.expect("The ROM was rejected.");
+	assert_eq!((info.size, info.end_address), (4, 0x204));
+	assert_eq!(info.platform, Platform::SuperChip);
+
+	assert_eq!(emulator.load_rom_checked(&[]), Err(RomError::Empty));
+	assert_eq!(
+		emulator.load_rom_checked(&[0; 4000]),
+		Err(RomError::Ram(RamError::RomTooLarge {
+			size: 4000,
+			capacity: 3584
+		}))
+	);
+}
+
+#[test]
+fn warm_reset_keeps_the_memory_and_cold_reset_clears_it() {
+	let stored = |emulator: &Emulator| {
+		emulator.ram().snapshot(0x300..0x301).expect("Out of memory.")[0]
+	};
+	let mut emulator = Emulator::new();
+	emulator
+		.load_rom(&[0xA3, 0x00, 0x60, 0x42, 0xF0, 0x55, 0x12, 0x06])
+		.expect("The ROM does not fit.");
+	emulator.run_cycles(3).expect("The ROM crashed.");
+	assert_eq!(stored(&emulator), 0x42);
+
+	emulator.warm_reset();
+	assert_eq!(emulator.cpu().pc(), 0x200);
+	assert_eq!(stored(&emulator), 0x42);
+	emulator.cold_reset();
+	assert_eq!(stored(&emulator), 0);
+}
+
+#[test]
+fn banner_shows_the_effective_configuration() {
+	let mut emulator = with_profile(QuirkProfile::SuperChip);
+	emulator.load_rom(&[0x12, 0x00]).expect("The ROM does not fit.");
+	assert_eq!(
+		emulator.banner("loop.ch8"),
+		"Chip-8 Emulator
+  ROM:       loop.ch8 (2 bytes, fnv1a 086fb407b51f68cf)
+  Platform:  SCHIP
+  Clock:     500 Hz
+  Registers: zero
+  Memory:    zero
+  Quirks:    shift=ShiftVx, memory=KeepIndex, jump=Vx, display_wait=Off, \
+		 clear_waits=false, logic=KeepVf, clip=Clip, index_overflow=Ignore, \
+		 draw_batch=Immediate
+  Palette:   fg #FFFFFF, bg #000000, fg2 #FF6600, blend #662200"
+	);
+}
@@ -0,0 +1,382 @@
+//! Runs ROMs headlessly for a fixed number of cycles and compares the screen
+//! with a reference in `tests/screens`. After an intended change of the
+//! output, run the tests with `UPDATE_SCREENS=1` to rewrite the references.
+//!
+//! Besides the bundled games, there are small ROMs assembled by hand below.
+//! Most print their results as decimal numbers, so the references can be
+//! checked by eye.
+
+use chip_8::quirks::{DisplayWaitQuirk, QuirkProfile};
+
+/// Checks the tricky arithmetic, the logic operations, `Fx55`/`Fx65` and the
+/// skips. The expected numbers, row by row:
+///
+/// ```text
+/// 044 001 246 000   200 + 100, 10 - 20
+/// 010 001 064 001   20 - 10 with SUBN, 129 >> 1
+/// 002 001 001 006   129 << 1, 255 + 2 with 7xnn, 1 + 2 + 3 through memory
+/// 008 255 012 240   skips, #0F | #F0, #3C & #0F, #FF ^ #0F
+/// 030 000 000 001   10 + 20, 5 - 5
+/// ```
+///
+/// Each operation with a carry or borrow is followed by vf.
+const OPCODES_ROM: &[u16] = &[
+	0x1224, // 200: JP main
+	// print:
+	0xA2D8, // 202: LD I, digits
+	0xFA33, // 204: LD B, VA
+	0xF265, // 206: LD V2, [I]
+	0xF029, // 208: LD F, V0
+	0xDBC5, // 20A: DRW VB, VC, 5
+	0x7B05, // 20C: ADD VB, 5
+	0xF129, // 20E: LD F, V1
+	0xDBC5, // 210: DRW VB, VC, 5
+	0x7B05, // 212: ADD VB, 5
+	0xF229, // 214: LD F, V2
+	0xDBC5, // 216: DRW VB, VC, 5
+	0x7B06, // 218: ADD VB, 6
+	0x3B40, // 21A: SE VB, 64
+	0x00EE, // 21C: RET
+	0x6B00, // 21E: LD VB, 0
+	0x7C06, // 220: ADD VC, 6
+	0x00EE, // 222: RET
+	// main:
+	0x6AC8, // 224: LD VA, 200
+	0x6D64, // 226: LD VD, 100
+	0x8AD4, // 228: ADD VA, VD
+	0x8EF0, // 22A: LD VE, VF
+	0x2202, // 22C: CALL print
+	0x8AE0, // 22E: LD VA, VE
+	0x2202, // 230: CALL print
+	0x6A0A, // 232: LD VA, 10
+	0x6D14, // 234: LD VD, 20
+	0x8AD5, // 236: SUB VA, VD
+	0x8EF0, // 238: LD VE, VF
+	0x2202, // 23A: CALL print
+	0x8AE0, // 23C: LD VA, VE
+	0x2202, // 23E: CALL print
+	0x6A0A, // 240: LD VA, 10
+	0x6D14, // 242: LD VD, 20
+	0x8AD7, // 244: SUBN VA, VD
+	0x8EF0, // 246: LD VE, VF
+	0x2202, // 248: CALL print
+	0x8AE0, // 24A: LD VA, VE
+	0x2202, // 24C: CALL print
+	0x6A81, // 24E: LD VA, 129
+	0x8DA0, // 250: LD VD, VA
+	0x8AD6, // 252: SHR VA, VD
+	0x8EF0, // 254: LD VE, VF
+	0x2202, // 256: CALL print
+	0x8AE0, // 258: LD VA, VE
+	0x2202, // 25A: CALL print
+	0x6A81, // 25C: LD VA, 129
+	0x8DA0, // 25E: LD VD, VA
+	0x8ADE, // 260: SHL VA, VD
+	0x8EF0, // 262: LD VE, VF
+	0x2202, // 264: CALL print
+	0x8AE0, // 266: LD VA, VE
+	0x2202, // 268: CALL print
+	0x6AFF, // 26A: LD VA, 255
+	0x7A02, // 26C: ADD VA, 2
+	0x2202, // 26E: CALL print
+	0x6001, // 270: LD V0, 1
+	0x6102, // 272: LD V1, 2
+	0x6203, // 274: LD V2, 3
+	0xA2DC, // 276: LD I, data
+	0xF255, // 278: LD [I], V2
+	0x6000, // 27A: LD V0, 0
+	0x6100, // 27C: LD V1, 0
+	0x6200, // 27E: LD V2, 0
+	0xA2DC, // 280: LD I, data
+	0xF265, // 282: LD V2, [I]
+	0x8A00, // 284: LD VA, V0
+	0x8A14, // 286: ADD VA, V1
+	0x8A24, // 288: ADD VA, V2
+	0x2202, // 28A: CALL print
+	0x6A00, // 28C: LD VA, 0
+	0x3A00, // 28E: SE VA, 0
+	0x7A01, // 290: ADD VA, 1
+	0x4A05, // 292: SNE VA, 5
+	0x7A02, // 294: ADD VA, 2
+	0x6D00, // 296: LD VD, 0
+	0x5AD0, // 298: SE VA, VD
+	0x7A04, // 29A: ADD VA, 4
+	0x9AD0, // 29C: SNE VA, VD
+	0x7A08, // 29E: ADD VA, 8
+	0x2202, // 2A0: CALL print
+	0x6A0F, // 2A2: LD VA, #0F
+	0x6DF0, // 2A4: LD VD, #F0
+	0x8AD1, // 2A6: OR VA, VD
+	0x2202, // 2A8: CALL print
+	0x6A3C, // 2AA: LD VA, #3C
+	0x6D0F, // 2AC: LD VD, #0F
+	0x8AD2, // 2AE: AND VA, VD
+	0x2202, // 2B0: CALL print
+	0x6AFF, // 2B2: LD VA, #FF
+	0x6D0F, // 2B4: LD VD, #0F
+	0x8AD3, // 2B6: XOR VA, VD
+	0x2202, // 2B8: CALL print
+	0x6A0A, // 2BA: LD VA, 10
+	0x6D14, // 2BC: LD VD, 20
+	0x8AD4, // 2BE: ADD VA, VD
+	0x8EF0, // 2C0: LD VE, VF
+	0x2202, // 2C2: CALL print
+	0x8AE0, // 2C4: LD VA, VE
+	0x2202, // 2C6: CALL print
+	0x6A05, // 2C8: LD VA, 5
+	0x6D05, // 2CA: LD VD, 5
+	0x8AD5, // 2CC: SUB VA, VD
+	0x8EF0, // 2CE: LD VE, VF
+	0x2202, // 2D0: CALL print
+	0x8AE0, // 2D2: LD VA, VE
+	0x2202, // 2D4: CALL print
+	// halt:
+	0x12D6, // 2D6: JP halt
+	// digits:
+	0x0000, // 2D8: BCD digits
+	0x0000, // 2DA:
+	// data:
+	0x0000, // 2DC: Fx55 round trip
+	0x0000, // 2DE:
+];
+
+/// Prints one number per quirk: the shift, memory, jump and logic quirks.
+/// They are `064 007 001 000` on Chip-8 and `000 001 002 005` on SUPER-CHIP.
+const QUIRKS_ROM: &[u16] = &[
+	0x121C, // 200: JP main
+	// print:
+	0xA250, // 202: LD I, digits
+	0xFA33, // 204: LD B, VA
+	0xF265, // 206: LD V2, [I]
+	0xF029, // 208: LD F, V0
+	0xDBC5, // 20A: DRW VB, VC, 5
+	0x7B05, // 20C: ADD VB, 5
+	0xF129, // 20E: LD F, V1
+	0xDBC5, // 210: DRW VB, VC, 5
+	0x7B05, // 212: ADD VB, 5
+	0xF229, // 214: LD F, V2
+	0xDBC5, // 216: DRW VB, VC, 5
+	0x7B06, // 218: ADD VB, 6
+	0x00EE, // 21A: RET
+	// main:
+	0x6A01, // 21C: LD VA, 1
+	0x6D80, // 21E: LD VD, 128
+	0x8AD6, // 220: SHR VA, VD
+	0x2202, // 222: CALL print
+	0xA254, // 224: LD I, data
+	0x6001, // 226: LD V0, 1
+	0x6102, // 228: LD V1, 2
+	0xF155, // 22A: LD [I], V1
+	0xF065, // 22C: LD V0, [I]
+	0x8A00, // 22E: LD VA, V0
+	0x2202, // 230: CALL print
+	0x6000, // 232: LD V0, 0
+	0x6204, // 234: LD V2, 4
+	0xB238, // 236: JP V0, jump
+	// jump:
+	0x6A01, // 238: LD VA, 1
+	0x1240, // 23A: JP jumped
+	0x6A02, // 23C: LD VA, 2
+	0x1240, // 23E: JP jumped
+	// jumped:
+	0x2202, // 240: CALL print
+	0x6A00, // 242: LD VA, 0
+	0x6D00, // 244: LD VD, 0
+	0x6F05, // 246: LD VF, 5
+	0x8AD1, // 248: OR VA, VD
+	0x8AF0, // 24A: LD VA, VF
+	0x2202, // 24C: CALL print
+	// halt:
+	0x124E, // 24E: JP halt
+	// digits:
+	0x0000, // 250: BCD digits
+	0x0000, // 252:
+	// data:
+	0x0000, // 254: Fx55 target
+	0x0700, // 256: Read after an incremented I
+];
+
+/// Draws a `0` on the first XO-CHIP plane and another one two pixels to the
+/// right on the second, so the screen has pixels on either plane and on
+/// both. Text references cannot tell them apart, so it is compared as PGM.
+const PLANES_ROM: &[u16] = &[
+	0x6A00, // 200: LD VA, 0
+	0xFA29, // 202: LD F, VA
+	0x6B00, // 204: LD VB, 0
+	0x6C00, // 206: LD VC, 0
+	0xF101, // 208: PLANE 1
+	0xDBC5, // 20A: DRW VB, VC, 5
+	0x6B02, // 20C: LD VB, 2
+	0xF201, // 20E: PLANE 2
+	0xDBC5, // 210: DRW VB, VC, 5
+	// halt:
+	0x1212, // 212: JP halt
+];
+
+/// The classic IBM logo test ROM: clears the screen, draws the logo from six
+/// 8x15 sprites and halts.
+const IBM_LOGO_ROM: &[u16] = &[
+	0x00E0, 0xA22A, 0x600C, 0x6108, 0xD01F, 0x7009, 0xA239, 0xD01F, 0xA248,
+	0x7008, 0xD01F, 0x7004, 0xA257, 0xD01F, 0x7008, 0xA266, 0xD01F, 0x7008,
+	0xA275, 0xD01F, 0x1228, // 228: JP 228
+	// The sprites from 22A
+	0xFF00, 0xFF00, 0x3C00, 0x3C00, 0x3C00, 0x3C00, 0xFF00, 0xFFFF, 0x00FF,
+	0x0038, 0x003F, 0x003F, 0x0038, 0x00FF, 0x00FF, 0x8000, 0xE000, 0xE000,
+	0x8000, 0x8000, 0xE000, 0xE000, 0x80F8, 0x00FC, 0x003E, 0x003F, 0x003B,
+	0x0039, 0x00F8, 0x00F8, 0x0300, 0x0700, 0x0F00, 0xBF00, 0xFB00, 0xF300,
+	0xE300, 0x43E0, 0x00E0, 0x0080, 0x0080, 0x0080, 0x0080, 0x00E0, 0x00E0,
+];
+
+fn words_to_bytes(words: &[u16]) -> Vec<u8> {
+	words.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
+
+/// Runs `cycles` instructions of the `rom` with the quirks of the `profile`
+/// and returns the screen. The random generator is seeded, the timers follow
+/// the cycles and draws do not wait for the wall clock, so the result only
+/// depends on the ROM.
+fn run(rom: &[u8], profile: QuirkProfile, cycles: usize) -> String {
+	run_emulator(rom, profile, cycles).screen().render_ascii()
+}
+
+/// Like `run`, but returns the whole emulator.
+fn run_emulator(
+	rom: &[u8],
+	profile: QuirkProfile,
+	cycles: usize,
+) -> chip_8::emulator::Emulator {
+	let config = chip_8::config::Config {
+		profile,
+		display_wait_quirk: Some(DisplayWaitQuirk::Off),
+		..chip_8::config::Config::default()
+	};
+	let mut emulator = chip_8::emulator::Emulator::with_config(&config);
+	emulator.set_timer_mode(chip_8::timer::TimerMode::Cycles);
+	emulator.set_rng_seed(0);
+	emulator.load_rom(rom).expect("The ROM does not fit.");
+	emulator.run_cycles(cycles).expect("The ROM crashed.");
+	emulator
+}
+
+/// Compares the `screen` with `tests/screens/{name}.txt`, or writes it there
+/// with `UPDATE_SCREENS` set.
+fn assert_screen(name: &str, screen: &str) {
+	assert_reference(&format!("{name}.txt"), screen);
+}
+
+/// Like `assert_screen`, but for a reference with the `file_name`.
+fn assert_reference(file_name: &str, screen: &str) {
+	let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+		.join("tests/screens")
+		.join(file_name);
+	if std::env::var_os("UPDATE_SCREENS").is_some() {
+		std::fs::write(&path, screen).expect("Failed to write the reference.");
+		return;
+	}
+	let expected = std::fs::read_to_string(&path)
+		.unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+	assert!(
+		expected == screen,
+		"The screen differs from {}.\nExpected:\n{expected}\nActual:\n\
+		 {screen}",
+		path.display(),
+	);
+}
+
+#[test]
+fn opcodes() {
+	let screen = run(&words_to_bytes(OPCODES_ROM), QuirkProfile::Chip8, 1000);
+	assert_screen("opcodes", &screen);
+}
+
+#[test]
+fn quirks_chip8() {
+	let screen = run(&words_to_bytes(QUIRKS_ROM), QuirkProfile::Chip8, 500);
+	assert_screen("quirks_chip8", &screen);
+}
+
+#[test]
+fn quirks_schip() {
+	let screen =
+		run(&words_to_bytes(QUIRKS_ROM), QuirkProfile::SuperChip, 500);
+	assert_screen("quirks_schip", &screen);
+}
+
+#[test]
+fn planes() {
+	let emulator =
+		run_emulator(&words_to_bytes(PLANES_ROM), QuirkProfile::XoChip, 100);
+	assert_reference("planes.pgm", &emulator.screen().dump_pgm());
+}
+
+#[test]
+fn ibm_logo_hash() {
+	let emulator =
+		run_emulator(&words_to_bytes(IBM_LOGO_ROM), QuirkProfile::Chip8, 100);
+	assert_eq!(emulator.screen().hash(), 0x1F1D_341C_AB07_E169);
+}
+
+#[test]
+fn pong() {
+	let screen =
+		run(include_bytes!("../roms/pong"), QuirkProfile::Chip8, 3000);
+	assert_screen("pong", &screen);
+}
+
+#[test]
+fn tetris() {
+	let screen =
+		run(include_bytes!("../roms/tetris"), QuirkProfile::Chip8, 5000);
+	assert_screen("tetris", &screen);
+}
+
+#[test]
+fn invaders() {
+	let screen =
+		run(include_bytes!("../roms/invaders"), QuirkProfile::Chip8, 5000);
+	assert_screen("invaders", &screen);
+}
+
+#[test]
+fn crash_dump() {
+	let mut emulator = chip_8::emulator::Emulator::new();
+	emulator
+		.load_rom(&words_to_bytes(&[0x6A05, 0xFFFF]))
+		.expect("The ROM does not fit.");
+	let error = emulator.run_cycles(2).expect_err("The ROM did not crash.");
+	let dump = emulator.crash_dump(&error);
+	assert_eq!((dump.pc, dump.opcode), (0x202, Some(0xFFFF)));
+	assert_eq!(dump.registers[0xA], 0x05);
+	assert_eq!(dump.memory_address, 0x1F2);
+	assert_eq!(dump.memory[14..18], [0x6A, 0x05, 0xFF, 0xFF]);
+}
+
+#[test]
+fn resets_keep_the_digit_sprites() {
+	let digits = chip_8::consts::RAM_DIGIT_SPRITES_ADDRESS
+		..chip_8::consts::RAM_ROM_START_ADDRESS;
+	let mut emulator = chip_8::emulator::Emulator::new();
+	let fresh = emulator
+		.ram()
+		.snapshot(digits.clone())
+		.expect("The font is out of memory.")
+		.to_vec();
+	emulator.cold_reset();
+	emulator.cold_reset();
+	assert_eq!(
+		emulator
+			.ram()
+			.snapshot(digits.clone())
+			.expect("The font is out of memory."),
+		fresh
+	);
+
+	let mut ram = chip_8::ram::Ram::new();
+	ram.load_digit_sprites();
+	ram.load_digit_sprites();
+	ram.clear();
+	assert_eq!(
+		ram.snapshot(digits).expect("The font is out of memory."),
+		fresh
+	);
+}